@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 use blit::{blit_buffer, Color};
 use minifb::*;
 use specs::prelude::*;
-use specs_blit::{PixelBuffer, RenderSystem, Sprite};
+use specs_blit::{ClearSystem, PixelBuffer, RenderSystem, Sprite};
 
 use std::thread::sleep;
 use std::time::Duration;
@@ -59,9 +59,14 @@ fn main() -> Result<()> {
     // Setup the dispatcher with the blit system
     let mut dispatcher = DispatcherBuilder::new()
         .with(RotationSystem, "rotation", &[])
-        .with_thread_local(RenderSystem)
+        // Clear the buffer before the sprites are drawn
+        .with(ClearSystem, "clear", &[])
+        .with_thread_local(RenderSystem::default())
         .build();
 
+    // Register the components used by the systems
+    dispatcher.setup(&mut world);
+
     // Setup a minifb window
     let options = WindowOptions {
         scale: Scale::X2,
@@ -73,10 +78,6 @@ fn main() -> Result<()> {
     let mut rotation = 0.0;
     while window.is_open() && !window.is_key_down(Key::Escape) {
         {
-            // Clear the buffer
-            let mut buffer = world.write_resource::<PixelBuffer>();
-            buffer.clear(0);
-
             // Update the rotation
             let mut rot_resource = world.write_resource::<Rotation>();
             rot_resource.0 = rotation;
@@ -0,0 +1,263 @@
+use anyhow::{anyhow, bail, Result};
+use blit::{blit_buffer, Color};
+use minifb::*;
+use specs::prelude::*;
+use specs_blit::{PixelBuffer, RenderPhase, RenderProfiler, RenderStats, RenderSystem, Sprite};
+
+use std::time::{Duration, Instant};
+
+const WIDTH: usize = 640;
+const HEIGHT: usize = 480;
+
+const MASK_COLOR: u32 = 0xFF_00_FF;
+
+// Usage: cargo run --release --example stress -- [--sprites N] [--frames N] [--headless]
+//                                                [--spatial-hash CELL_SIZE] [--world-size N]
+const USAGE: &str = "usage: stress [--sprites N] [--frames N] [--headless] \
+                     [--spatial-hash CELL_SIZE] [--world-size N]";
+
+// The command line options
+#[derive(Debug)]
+struct Options {
+    // Amount of sprites spawned
+    sprites: usize,
+    // Amount of frames rendered before quitting, runs until the window is closed when not set
+    frames: Option<usize>,
+    // Render without opening a window
+    headless: bool,
+    // Size of the cells of the spatial hash, not used when not set; every moving sprite updates
+    // the grid, so it only pays off when most sprites stand still
+    spatial_hash: Option<i32>,
+    // Width and height of the world the sprites move in, bigger than the buffer so most sprites
+    // are offscreen
+    world_size: i32,
+}
+
+impl Options {
+    // Parse the options from the command line arguments
+    fn parse() -> Result<Self> {
+        let mut options = Self {
+            sprites: 100_000,
+            frames: None,
+            headless: false,
+            spatial_hash: None,
+            world_size: 4096,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+            match arg.as_str() {
+                "--sprites" => options.sprites = value()?.parse()?,
+                "--frames" => options.frames = Some(value()?.parse()?),
+                "--headless" => options.headless = true,
+                "--spatial-hash" => options.spatial_hash = Some(value()?.parse()?),
+                "--world-size" => options.world_size = value()?.parse()?,
+                _ => bail!("unknown argument {}\n{}", arg, USAGE),
+            }
+        }
+
+        // Headless runs need an end
+        if options.headless && options.frames.is_none() {
+            options.frames = Some(600);
+        }
+
+        Ok(options)
+    }
+}
+
+// The movement of a sprite in pixels per frame and degrees per frame
+#[derive(Debug)]
+pub struct Velocity {
+    x: i32,
+    y: i32,
+    rot: i16,
+}
+
+impl Component for Velocity {
+    type Storage = VecStorage<Self>;
+}
+
+// A resource with the size of the world
+#[derive(Debug, Default)]
+pub struct WorldSize(pub i32);
+
+// The system for moving and rotating the sprites
+pub struct MoveSystem;
+impl<'a> System<'a> for MoveSystem {
+    type SystemData = (
+        Read<'a, WorldSize>,
+        ReadStorage<'a, Velocity>,
+        WriteStorage<'a, Sprite>,
+    );
+
+    fn run(&mut self, (world_size, velocities, mut sprites): Self::SystemData) {
+        for (velocity, sprite) in (&velocities, &mut sprites).join() {
+            let (x, y) = sprite.pos();
+
+            // Wrap around the edges of the world
+            sprite.set_pos(
+                (x + velocity.x).rem_euclid(world_size.0),
+                (y + velocity.y).rem_euclid(world_size.0),
+            );
+            sprite.set_rot(sprite.rot() + velocity.rot);
+        }
+    }
+}
+
+// A tiny random number generator so the example doesn't need any extra dependencies
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, max: i32) -> i32 {
+        // Xorshift
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % max.max(1) as u64) as i32
+    }
+}
+
+// Print the statistics of the last frames
+fn print_stats(world: &World, frames: usize, elapsed: Duration) {
+    let profiler = world.read_resource::<RenderProfiler>();
+    let stats = world.read_resource::<RenderStats>();
+
+    println!(
+        "{} frames in {:.2?}, {:.1} FPS, per frame: sort {:.2?}, cull {:.2?}, blit {:.2?}, \
+         drawn {}, culled {}",
+        frames,
+        elapsed,
+        frames as f64 / elapsed.as_secs_f64(),
+        profiler.average(RenderPhase::Sort),
+        profiler.average(RenderPhase::Cull),
+        profiler.average(RenderPhase::Blit),
+        stats.sprites_drawn,
+        stats.sprites_culled,
+    );
+}
+
+fn main() -> Result<()> {
+    let options = Options::parse()?;
+
+    // Setup specs
+    let mut world = World::new();
+
+    // Add the pixel buffer as a resource so it can be accessed from the RenderSystem later
+    world.insert(PixelBuffer::new(WIDTH, HEIGHT));
+
+    // Measure where the time is spent
+    world.insert(RenderProfiler::new(60));
+    world.insert(RenderStats::default());
+    world.insert(WorldSize(options.world_size));
+
+    // Setup the dispatcher with the blit system
+    let render_system = match options.spatial_hash {
+        Some(cell_size) => RenderSystem::default().with_spatial_hash(cell_size),
+        None => RenderSystem::default(),
+    };
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(MoveSystem, "move", &[])
+        .with_thread_local(render_system)
+        .build();
+
+    // Register the components used by the systems
+    dispatcher.setup(&mut world);
+
+    // Load the sprite
+    let sprite_ref = {
+        // Load the image using the image crate
+        let img = image::open("examples/smiley.png")?;
+        // Create a sprite from it
+        let sprite = blit_buffer(&img, Color::from_u32(MASK_COLOR));
+
+        // Move the sprite to the render system with 32 rotations
+        specs_blit::load_rotations(sprite, 32)?
+    };
+
+    // Spawn the sprites spread over the world
+    let mut random = Random(0x2545_F491_4F6C_DD1D);
+    for _ in 0..options.sprites {
+        let mut sprite = Sprite::new(sprite_ref.clone());
+        sprite.set_pos(
+            random.next(options.world_size),
+            random.next(options.world_size),
+        );
+        sprite.set_rot(random.next(360) as i16);
+
+        world
+            .create_entity()
+            .with(sprite)
+            .with(Velocity {
+                x: random.next(5) - 2,
+                y: random.next(5) - 2,
+                rot: random.next(7) as i16 - 3,
+            })
+            .build();
+    }
+
+    println!(
+        "rendering {} sprites in a world of {}x{} pixels on a buffer of {}x{}",
+        options.sprites, options.world_size, options.world_size, WIDTH, HEIGHT
+    );
+
+    let mut window = if options.headless {
+        None
+    } else {
+        // Setup a minifb window
+        Some(
+            Window::new(
+                "Specs Blit Stress Test - ESC to exit",
+                WIDTH,
+                HEIGHT,
+                WindowOptions::default(),
+            )
+            .map_err(|err| anyhow!("{}", err))?,
+        )
+    };
+
+    let start = Instant::now();
+    let mut last_print = Instant::now();
+    let mut frame = 0;
+    loop {
+        if options.frames.is_some_and(|frames| frame >= frames) {
+            break;
+        }
+        if let Some(window) = &window {
+            if !window.is_open() || window.is_key_down(Key::Escape) {
+                break;
+            }
+        }
+
+        // Clear the buffer
+        world.exec(
+            |(mut profiler, mut buffer): (Write<RenderProfiler>, Write<PixelBuffer>)| {
+                profiler.measure(RenderPhase::Clear, || buffer.clear(0));
+            },
+        );
+
+        // Update specs
+        dispatcher.dispatch(&world);
+        world.maintain();
+        world.write_resource::<RenderProfiler>().finish_frame();
+        frame += 1;
+
+        if let Some(window) = &mut window {
+            // Render the pixel buffer
+            let buffer = world.read_resource::<PixelBuffer>();
+            window
+                .update_with_buffer(&buffer.pixels(), buffer.width(), buffer.height())
+                .map_err(|err| anyhow!("{}", err))?;
+        }
+
+        if last_print.elapsed() >= Duration::from_secs(1) {
+            print_stats(&world, frame, start.elapsed());
+            last_print = Instant::now();
+        }
+    }
+
+    print_stats(&world, frame, start.elapsed());
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use blit::BlitBuffer;
+use specs::prelude::*;
+use specs_blit::{ClearSystem, PixelBuffer, PixelFormat, RenderSystem, Sprite, SpriteRef};
+use wasm_bindgen::{prelude::*, Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use std::{cell::RefCell, rc::Rc};
+
+const WIDTH: usize = 250;
+const HEIGHT: usize = 250;
+
+const MASK_COLOR: u32 = 0xFF_00_FF;
+
+// The image is embedded in the binary because there's no filesystem in the browser
+const SMILEY: &[u8] = include_bytes!("../../smiley.png");
+
+// A resource for rotating the sprites
+#[derive(Debug, Default)]
+pub struct Rotation(pub f64);
+
+// The system for rotating the sprites
+pub struct RotationSystem;
+impl<'a> System<'a> for RotationSystem {
+    type SystemData = (Read<'a, Rotation>, WriteStorage<'a, Sprite>);
+
+    fn run(&mut self, (rot, mut sprite): Self::SystemData) {
+        // Rotate the sprites
+        for (sprite,) in (&mut sprite,).join() {
+            sprite.set_rot(rot.0 as i16);
+        }
+    }
+}
+
+// Convert any error to something that can be thrown in JavaScript
+fn js_error<E: ToString>(err: E) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
+
+// Load the embedded image as a sprite
+fn load_smiley() -> Result<SpriteRef, JsValue> {
+    // Decode the PNG using the image crate
+    let img = image::load_from_memory(SMILEY).map_err(js_error)?.to_rgb8();
+
+    // Pack the pixels in the 0x00RRGGBB format expected by blit
+    let pixels: Vec<u32> = img
+        .pixels()
+        .map(|pixel| (pixel[0] as u32) << 16 | (pixel[1] as u32) << 8 | pixel[2] as u32)
+        .collect();
+    let sprite = BlitBuffer::from_buffer(&pixels, img.width() as i32, MASK_COLOR);
+
+    // Move the sprite to the render system with 16 rotations
+    specs_blit::load_rotations(sprite, 16).map_err(js_error)
+}
+
+// Schedule the closure to be called before the next repaint of the browser
+fn request_animation_frame(f: &Closure<dyn FnMut()>) {
+    web_sys::window()
+        .expect("no window")
+        .request_animation_frame(f.as_ref().unchecked_ref())
+        .expect("requesting an animation frame failed");
+}
+
+// Called automatically when the module is loaded
+#[wasm_bindgen(start)]
+pub fn start() -> Result<(), JsValue> {
+    // Show Rust panics in the browser console
+    console_error_panic_hook::set_once();
+
+    // Get the 2D context of the canvas in `index.html`
+    let canvas: HtmlCanvasElement = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("canvas"))
+        .ok_or_else(|| js_error("no canvas element with the id \"canvas\""))?
+        .dyn_into()?;
+    canvas.set_width(WIDTH as u32);
+    canvas.set_height(HEIGHT as u32);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or_else(|| js_error("the canvas doesn't support a 2D context"))?
+        .dyn_into()?;
+
+    // Setup specs
+    let mut world = World::new();
+
+    // Load the blit components into the world
+    world.register::<Sprite>();
+
+    // Add the pixel buffer as a resource so it can be accessed from the RenderSystem later
+    world.insert(PixelBuffer::new(WIDTH, HEIGHT));
+
+    // Add the rotation of the sprites
+    world.insert(Rotation(0.0));
+
+    // Create a few entities with the sprite
+    let sprite_ref = load_smiley()?;
+    for &(x, y) in &[(20, 20), (140, 20), (20, 140), (140, 140)] {
+        let mut sprite = Sprite::new(sprite_ref.clone());
+        sprite.set_pos(x, y);
+
+        world.create_entity().with(sprite).build();
+    }
+
+    // Setup the dispatcher with the blit system
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(RotationSystem, "rotation", &[])
+        // Clear the buffer before the sprites are drawn
+        .with(ClearSystem, "clear", &[])
+        .with_thread_local(RenderSystem::default())
+        .build();
+
+    // Register the components used by the systems
+    dispatcher.setup(&mut world);
+
+    // The canvas expects the pixels as RGBA bytes, the buffer is reused every frame
+    let mut rgba = vec![0; WIDTH * HEIGHT * PixelFormat::Rgba8.bytes_per_pixel()];
+
+    // The closure needs a reference to itself to request the next frame
+    let frame = Rc::new(RefCell::new(None));
+    let first_frame = frame.clone();
+
+    let mut rotation = 0.0;
+    *first_frame.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        {
+            // Update the rotation
+            let mut rot_resource = world.write_resource::<Rotation>();
+            rot_resource.0 = rotation;
+            rotation += 1.0;
+        }
+
+        // Update specs
+        dispatcher.dispatch(&world);
+
+        // Add/remove entities added in dispatch through `LazyUpdate`
+        world.maintain();
+
+        // Convert the pixel buffer to RGBA and draw it on the canvas
+        let buffer = world.read_resource::<PixelBuffer>();
+        buffer.copy_to_format(PixelFormat::Rgba8, &mut rgba);
+        let image_data = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&rgba[..]),
+            buffer.width() as u32,
+            buffer.height() as u32,
+        )
+        .expect("creating the image data failed");
+        context
+            .put_image_data(&image_data, 0.0, 0.0)
+            .expect("drawing on the canvas failed");
+
+        // Render the next frame
+        request_animation_frame(frame.borrow().as_ref().unwrap());
+    }) as Box<dyn FnMut()>));
+
+    request_animation_frame(first_frame.borrow().as_ref().unwrap());
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+/// The point of a sprite that is placed at its position.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Anchor, Sprite};
+///
+/// const MASK_COLOR: u32 = 0xFF00FF;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // A character of 2 by 4 pixels with red feet
+/// #[rustfmt::skip]
+/// let pixels = [
+///     0xFF00, 0xFF00,
+///     0xFF00, 0xFF00,
+///     0xFF00, 0xFF00,
+///     0xFF0000, 0xFF0000,
+/// ];
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&pixels, 2, MASK_COLOR))?);
+///
+/// // Position the character by its feet
+/// sprite.set_anchor(Anchor::BottomCenter);
+/// sprite.set_pos(5, 8);
+/// world.create_entity().with(sprite).build();
+///
+/// let buffer = testing::render(&mut world, 10, 10);
+/// assert_eq!(buffer.pixels()[7 * 10 + 4] & 0xFFFFFF, 0xFF0000);
+/// assert_eq!(buffer.pixels()[4 * 10 + 4] & 0xFFFFFF, 0xFF00);
+/// assert_eq!(buffer.pixels()[8 * 10 + 4], 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    /// The position is the top left corner of the sprite.
+    #[default]
+    TopLeft,
+    /// The position is the center of the sprite.
+    Center,
+    /// The position is the center of the bottom edge of the sprite.
+    BottomCenter,
+    /// The position is the pixel at the (x, y) coordinates of the unrotated sprite.
+    Offset(i32, i32),
+}
+
+impl Anchor {
+    /// Get the position of the anchor relative to the top left corner of a sprite with this size.
+    pub(crate) fn offset(self, width: i32, height: i32) -> (i32, i32) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Center => (width / 2, height / 2),
+            Anchor::BottomCenter => (width / 2, height),
+            Anchor::Offset(x, y) => (x, y),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Sprites changing over time.
+
+use crate::{
+    time::{self, PausedLayers},
+    Sprite, SpriteRef, Time,
+};
+use specs::prelude::*;
+use std::time::Duration;
+
+/// Specs component cycling through a list of sprites, replacing the sprite of the [`Sprite`]
+/// component of the same entity.
+///
+/// The [`AnimationSystem`] advances the animations with the [`Time`](crate::Time) resource.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::{load, Animation};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let frame1 = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// let frame2 = load(BlitBuffer::from_buffer(&[0x00_FF_00], 1, 0xFF_00_FF))?;
+///
+/// // Show every frame for 100 milliseconds and start again after the last one
+/// let animation = Animation::new(vec![
+///     (frame1, Duration::from_millis(100)),
+///     (frame2, Duration::from_millis(100)),
+/// ]);
+/// assert!(animation.is_looping());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Animation {
+    /// The sprites with the time they are shown.
+    frames: Vec<(SpriteRef, Duration)>,
+    /// Whether to start again after the last frame.
+    looping: bool,
+    /// Time passed since the start of the animation.
+    elapsed: Duration,
+    /// Index of the frame that's currently shown.
+    current: usize,
+}
+
+impl Component for Animation {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Animation {
+    /// Create a looping animation from sprites and the time they are shown.
+    ///
+    /// # Panics
+    ///
+    /// When there are no frames.
+    pub fn new(frames: Vec<(SpriteRef, Duration)>) -> Self {
+        assert!(!frames.is_empty(), "an animation needs at least one frame");
+
+        Self {
+            frames,
+            looping: true,
+            elapsed: Duration::default(),
+            current: 0,
+        }
+    }
+
+    /// Set whether to start again after the last frame, otherwise the last frame stays visible.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Whether the animation starts again after the last frame.
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Start the animation from the first frame.
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::default();
+    }
+
+    /// Whether the last frame of a non-looping animation has been shown completely.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.total_duration()
+    }
+
+    /// The sprite of the frame that should be shown.
+    pub fn current_frame(&self) -> &SpriteRef {
+        &self.frames[self.current].0
+    }
+
+    /// Time it takes to show all frames once.
+    pub fn total_duration(&self) -> Duration {
+        self.frames.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// Move the animation forward in time, returns whether a different frame should be shown.
+    pub(crate) fn advance(&mut self, delta: Duration) -> bool {
+        self.elapsed += delta;
+
+        let total = self.total_duration();
+        let mut time = if total == Duration::default() {
+            Duration::default()
+        } else if self.looping {
+            Duration::from_nanos((self.elapsed.as_nanos() % total.as_nanos()) as u64)
+        } else {
+            self.elapsed.min(total)
+        };
+
+        let previous = self.current;
+        self.current = self.frames.len() - 1;
+        for (index, (_, duration)) in self.frames.iter().enumerate() {
+            if time < *duration {
+                self.current = index;
+                break;
+            }
+            time -= *duration;
+        }
+
+        self.current != previous
+    }
+}
+
+/// Specs system advancing every [`Animation`] and setting the sprite of its [`Sprite`]
+/// component.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{AnimationSystem, RenderSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(AnimationSystem, "animation", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct AnimationSystem;
+impl<'a> System<'a> for AnimationSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        Option<Read<'a, PausedLayers>>,
+        WriteStorage<'a, Animation>,
+        WriteStorage<'a, Sprite>,
+    );
+
+    fn run(&mut self, (time, paused_layers, mut animations, mut sprites): Self::SystemData) {
+        for (animation, sprite) in (&mut animations, &mut sprites).join() {
+            let delta = time::layer_delta(&time, paused_layers.as_deref(), Some(sprite));
+            if animation.advance(delta) {
+                sprite.reference = animation.current_frame().clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Packing the pixels of loaded sprites together.
+
+use crate::{sprite_image, SPRITES};
+
+/// Move the pixels of all loaded sprites into a few large blocks of memory, returns the amount
+/// of blocks.
+///
+/// Every block holds at most `block_size` pixels, sprites that are larger get a block of their
+/// own. Sprites loaded one after another end up next to each other, which keeps the memory that's
+/// read while drawing close together. [`SpriteRef`](crate::SpriteRef)s keep working as before.
+///
+/// The rotations of every sprite are already loaded into a single block, sprites loaded
+/// afterwards aren't packed with the others so call it after loading. The memory of a block is
+/// only freed when all sprites in it are [reclaimed](crate::reclaim_sprites), packing again
+/// compacts the remaining sprites.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, pack_sprites, testing, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// for x in 0..3 {
+///     let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 16], 4, 0xFF_00_FF))?;
+///     let mut sprite = Sprite::new(sprite_ref);
+///     sprite.set_pos(x * 4, 0);
+///     world.create_entity().with(sprite).build();
+/// }
+///
+/// // Two sprites of 16 pixels fit in every block
+/// assert_eq!(pack_sprites(32), 2);
+///
+/// let buffer = testing::render(&mut world, 12, 4);
+/// assert!(buffer.pixels().iter().all(|pixel| pixel & 0xFF_FF_FF == 0xFF_FF_FF));
+/// # Ok(())
+/// # }
+/// ```
+pub fn pack_sprites(block_size: usize) -> usize {
+    let mut store = SPRITES.write().unwrap();
+
+    sprite_image::pack(&mut store, block_size)
+}
@@ -0,0 +1,141 @@
+//! Sprites following a point of the sprite of another entity.
+
+use crate::Sprite;
+use specs::prelude::*;
+
+/// Specs component placing the [`Sprite`] of the entity at a socket of the sprite of another
+/// entity with the [`AttachmentSystem`].
+///
+/// The anchor of the attached sprite is placed on the socket, so a weapon or a hat follows the
+/// right pixel of an animated and rotating parent. Sockets are added to loaded sprites with
+/// [`SpriteRef::with_socket`](crate::SpriteRef::with_socket).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachedTo {
+    /// Entity with the sprite that has the socket.
+    pub entity: Entity,
+    /// Name of the socket.
+    pub socket: String,
+    /// Whether the sprite is turned along with the parent.
+    pub follow_rotation: bool,
+}
+
+impl AttachedTo {
+    /// Attach to the socket with the name of the sprite of the entity, turning along with it.
+    pub fn new<S>(entity: Entity, socket: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            entity,
+            socket: socket.into(),
+            follow_rotation: true,
+        }
+    }
+
+    /// Set whether the sprite is turned along with the parent, when it's not its rotation is
+    /// kept.
+    pub fn with_follow_rotation(mut self, follow_rotation: bool) -> Self {
+        self.follow_rotation = follow_rotation;
+
+        self
+    }
+}
+
+impl Component for AttachedTo {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system moving every sprite with an [`AttachedTo`] component to the socket of its
+/// parent.
+///
+/// Run it after everything that moves, turns or animates the sprites and before the
+/// [`RenderSystem`](crate::RenderSystem). Parents are placed before the sprites attached to them,
+/// so chains of attachments are placed in a single run. Sprites whose parent or socket doesn't
+/// exist stay where they are.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load_rotations, AttachedTo, AttachmentSystem, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(AttachmentSystem, "attachment", &[])
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let body = BlitBuffer::from_buffer(&[0xFF_FF_FF; 8], 4, 0xFF_00_FF);
+/// let body_ref = load_rotations(body, 4)?.with_socket("hand", 3, 0);
+/// let mut body = Sprite::new(body_ref);
+/// body.set_pos(10, 10);
+/// body.set_rot(90);
+/// let body = world.create_entity().with(body).build();
+///
+/// let sword = BlitBuffer::from_buffer(&[0xAA_AA_AA; 4], 2, 0xFF_00_FF);
+/// let sword = Sprite::new(load_rotations(sword, 4)?);
+/// let sword = world
+///     .create_entity()
+///     .with(sword)
+///     .with(AttachedTo::new(body, "hand"))
+///     .build();
+///
+/// dispatcher.dispatch(&world);
+///
+/// // The sword is held in the turned hand and turned along with it
+/// let sprites = world.read_storage::<Sprite>();
+/// assert_eq!(sprites.get(sword).unwrap().pos(), (12, 12));
+/// assert_eq!(sprites.get(sword).unwrap().rot(), 90);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct AttachmentSystem;
+impl<'a> System<'a> for AttachmentSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Sprite>,
+        ReadStorage<'a, AttachedTo>,
+    );
+
+    fn run(&mut self, (entities, mut sprites, attachments): Self::SystemData) {
+        // Count the parents of every attached sprite, a cycle stops at the amount of attachments
+        let limit = (&attachments).join().count();
+        let depth = |entity: Entity| {
+            let mut current = entity;
+            let mut depth = 0;
+            while let Some(attachment) = attachments.get(current) {
+                depth += 1;
+                if depth > limit {
+                    break;
+                }
+                current = attachment.entity;
+            }
+
+            depth
+        };
+
+        let mut ordered: Vec<(usize, Entity, &AttachedTo)> = (&entities, &attachments)
+            .join()
+            .map(|(entity, attachment)| (depth(entity), entity, attachment))
+            .collect();
+        ordered.sort_by_key(|(depth, _, _)| *depth);
+
+        for (_, entity, attachment) in ordered {
+            let (pos, rot) = match sprites
+                .get(attachment.entity)
+                .and_then(|parent| Some((parent.socket_pos(&attachment.socket)?, parent.rot())))
+            {
+                Some(placement) => placement,
+                None => continue,
+            };
+
+            if let Some(sprite) = sprites.get_mut(entity) {
+                sprite.set_pos(pos.0, pos.1);
+                if attachment.follow_rotation {
+                    sprite.set_rot(rot);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! Backgrounds drawn behind everything else.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    Camera, OverdrawHeatmap, PixelBuffer, SpriteImage, SpriteRef, Time, SPRITES,
+};
+use specs::prelude::*;
+use std::f32::consts::TAU;
+use web_time::Instant;
+
+/// Specs component repeating a sprite over the buffer and moving it over time, drawn by the
+/// [`ScrollingBackgroundSystem`].
+///
+/// The sprite is repeated endlessly on the axes it wraps on, so only a single entity is needed
+/// for an endless backdrop. With a parallax factor the background also moves with the
+/// [`Camera`], a factor of `1.0` moves it as fast as the world and smaller factors make it appear
+/// further away. The zoom of the camera is ignored.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, ScrollingBackground, Time};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<ScrollingBackground>();
+///
+/// // A sky of two columns
+/// let sky = load(BlitBuffer::from_buffer(&[0x00_00_FF, 0x00_FF_FF], 2, 0xFF_00_FF))?;
+///
+/// // Move the sky to the left with one pixel per second
+/// let mut background = ScrollingBackground::new(sky);
+/// background.set_speed(-1.0, 0.0);
+/// world.create_entity().with(background).build();
+///
+/// let mut time = Time::default();
+/// time.advance(Duration::from_secs(1));
+/// world.insert(time);
+///
+/// let frame = testing::render(&mut world, 3, 1);
+/// let pixels: Vec<u32> = frame.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+/// assert_eq!(pixels, [0x00_FF_FF, 0x00_00_FF, 0x00_FF_FF]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScrollingBackground {
+    /// The repeated sprite.
+    sprite: SpriteRef,
+    /// Buffer position of the top left corner of the sprite before scrolling.
+    pos: (i32, i32),
+    /// Pixels per second the sprite moves.
+    speed: (f64, f64),
+    /// How much the sprite moves with the camera.
+    parallax: (f64, f64),
+    /// Whether the sprite repeats horizontally and vertically.
+    repeat: (bool, bool),
+    /// Backgrounds on higher layers are drawn over backgrounds on lower layers.
+    layer: u8,
+    /// Distance the sprite moved because of the speed.
+    offset: (f64, f64),
+}
+
+impl Component for ScrollingBackground {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl ScrollingBackground {
+    /// Create a background repeating the sprite in both directions without moving.
+    pub fn new(sprite: SpriteRef) -> Self {
+        Self {
+            sprite,
+            pos: (0, 0),
+            speed: (0.0, 0.0),
+            parallax: (0.0, 0.0),
+            repeat: (true, true),
+            layer: 0,
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /// Set the buffer position of the top left corner of the sprite before it's moved.
+    ///
+    /// This places the sprite on the axes it doesn't repeat on.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the buffer position of the top left corner of the sprite before it's moved.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Set how many pixels per second the sprite moves, positive values move it to the right and
+    /// down.
+    pub fn set_speed(&mut self, x: f64, y: f64) {
+        self.speed = (x, y);
+    }
+
+    /// Get how many pixels per second the sprite moves.
+    pub fn speed(&self) -> (f64, f64) {
+        self.speed
+    }
+
+    /// Set how much the sprite moves with the camera, `0.0` keeps it in place on the screen and
+    /// `1.0` moves it like the world.
+    pub fn set_parallax(&mut self, x: f64, y: f64) {
+        self.parallax = (x, y);
+    }
+
+    /// Get how much the sprite moves with the camera.
+    pub fn parallax(&self) -> (f64, f64) {
+        self.parallax
+    }
+
+    /// Set whether the sprite repeats horizontally and vertically.
+    pub fn set_repeat(&mut self, x: bool, y: bool) {
+        self.repeat = (x, y);
+    }
+
+    /// Get whether the sprite repeats horizontally and vertically.
+    pub fn repeat(&self) -> (bool, bool) {
+        self.repeat
+    }
+
+    /// Set the layer, backgrounds on higher layers are drawn over backgrounds on lower layers.
+    pub fn set_layer(&mut self, layer: u8) {
+        self.layer = layer;
+    }
+
+    /// Get the layer.
+    pub fn layer(&self) -> u8 {
+        self.layer
+    }
+
+    /// Move the sprite by its speed.
+    pub fn advance(&mut self, seconds: f64) {
+        self.offset.0 += self.speed.0 * seconds;
+        self.offset.1 += self.speed.1 * seconds;
+
+        // Keep the offset small so it doesn't lose precision, the wrapped positions are the same
+        if let Some((width, height)) = self.sprite_size() {
+            if self.repeat.0 {
+                self.offset.0 = self.offset.0.rem_euclid(width as f64);
+            }
+            if self.repeat.1 {
+                self.offset.1 = self.offset.1.rem_euclid(height as f64);
+            }
+        }
+    }
+
+    /// Size of the unrotated sprite, `None` when it doesn't exist anymore.
+    fn sprite_size(&self) -> Option<(usize, usize)> {
+        let sprites = SPRITES.read().unwrap();
+
+        sprites
+            .get(self.sprite.sprites[0].0)
+            .map(|image| (image.width(), image.height()))
+    }
+
+    /// Draw the sprite repeated over the buffer as seen by the camera.
+    pub fn draw(&self, buffer: &mut PixelBuffer, camera: &Camera) {
+        let buffer_size = (buffer.width(), buffer.height());
+        self.for_each_copy(buffer_size, camera, |image, pos| {
+            image.blit_with(buffer, pos, |src, dst, _, _| *dst = src)
+        });
+    }
+
+    /// Call the function with the image and the buffer position of every copy of the sprite
+    /// that's drawn on a buffer of the size.
+    pub(crate) fn for_each_copy<F>(&self, buffer_size: (usize, usize), camera: &Camera, mut f: F)
+    where
+        F: FnMut(&SpriteImage, (i32, i32)),
+    {
+        let sprites = SPRITES.read().unwrap();
+        let image = match sprites.get(self.sprite.sprites[0].0) {
+            Some(image) => image,
+            None => return,
+        };
+        let (width, height) = (image.width() as i32, image.height() as i32);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let camera_pos = camera.pos();
+        let x = f64::from(self.pos.0) + self.offset.0 - f64::from(camera_pos.0) * self.parallax.0;
+        let y = f64::from(self.pos.1) + self.offset.1 - f64::from(camera_pos.1) * self.parallax.1;
+        let (x, y) = (x.floor() as i32, y.floor() as i32);
+
+        // Start at the last copy before the edge of the buffer on the repeating axes
+        let (start_x, end_x) = if self.repeat.0 {
+            (x.rem_euclid(width) - width, buffer_size.0 as i32)
+        } else {
+            (x, x + 1)
+        };
+        let (start_y, end_y) = if self.repeat.1 {
+            (y.rem_euclid(height) - height, buffer_size.1 as i32)
+        } else {
+            (y, y + 1)
+        };
+
+        for copy_y in (start_y..end_y).step_by(height as usize) {
+            for copy_x in (start_x..end_x).step_by(width as usize) {
+                f(image, (copy_x, copy_y));
+            }
+        }
+    }
+}
+
+/// Specs system moving every [`ScrollingBackground`] and drawing them through the [`Camera`]
+/// resource.
+///
+/// Run it before the [`RenderSystem`](crate::RenderSystem) with `.with_thread_local()` so
+/// everything else is drawn on top of the backgrounds.
+#[derive(Debug, Default)]
+pub struct ScrollingBackgroundSystem;
+impl<'a> System<'a> for ScrollingBackgroundSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Camera>>,
+        Read<'a, Time>,
+        WriteStorage<'a, ScrollingBackground>,
+        Option<Write<'a, RenderProfiler>>,
+        Option<Write<'a, OverdrawHeatmap>>,
+    );
+
+    fn run(
+        &mut self,
+        (mut buffer, camera, time, mut backgrounds, profiler, mut heatmap): Self::SystemData,
+    ) {
+        let start = Instant::now();
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+        let buffer_size = (buffer.width(), buffer.height());
+
+        let mut backgrounds: Vec<&mut ScrollingBackground> = (&mut backgrounds).join().collect();
+        backgrounds.sort_by_key(|background| background.layer);
+
+        for background in backgrounds {
+            background.advance(time.delta().as_secs_f64());
+            background.draw(&mut buffer, &camera);
+
+            if let Some(heatmap) = heatmap.as_mut() {
+                background.for_each_copy(buffer_size, &camera, |image, pos| {
+                    heatmap.add(image, pos, (image.width(), image.height()), buffer_size)
+                });
+            }
+        }
+
+        profiler::record(
+            profiler,
+            RenderPhase::Blit,
+            "ScrollingBackgroundSystem",
+            start,
+        );
+    }
+}
+
+/// A group of stars of the [`Starfield`] at the same distance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StarLayer {
+    /// Amount of stars.
+    pub count: usize,
+    /// Color of the stars at full brightness.
+    pub color: u32,
+    /// Pixels per second the stars move, positive values move them to the right and down.
+    pub speed: (f32, f32),
+    /// How much the stars move with the camera, `0.0` keeps them in place on the screen and `1.0`
+    /// moves them like the world.
+    pub parallax: (f32, f32),
+}
+
+/// Resource with the stars drawn by the [`StarfieldSystem`].
+///
+/// Every star is a single pixel placed randomly on the buffer, stars moving out of the buffer
+/// come back on the other side. The same seed always places the stars at the same positions.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{testing, Starfield};
+///
+/// let mut world = World::new();
+///
+/// // Three layers of stars without twinkling
+/// let mut starfield = Starfield::new(42);
+/// starfield.twinkle = 0.0;
+/// let stars: usize = starfield.layers.iter().map(|layer| layer.count).sum();
+/// world.insert(starfield);
+///
+/// let frame = testing::render(&mut world, 64, 64);
+/// let drawn = frame.pixels().iter().filter(|&&pixel| pixel != 0).count();
+/// assert!(drawn > 0 && drawn <= stars);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Starfield {
+    /// Determines where the stars are placed.
+    pub seed: u64,
+    /// Groups of stars, drawn from the first to the last so the nearest layer should be last.
+    pub layers: Vec<StarLayer>,
+    /// How much the stars fade in and out, from `0.0` for not at all to `1.0` for fading out
+    /// completely.
+    pub twinkle: f32,
+    /// Times per second a star fades out and in again.
+    pub twinkle_speed: f32,
+}
+
+impl Starfield {
+    /// Create a starfield of three layers of stars moving down, the nearer layers have brighter
+    /// and faster stars.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            layers: vec![
+                StarLayer {
+                    count: 64,
+                    color: 0x60_60_60,
+                    speed: (0.0, 4.0),
+                    parallax: (0.1, 0.1),
+                },
+                StarLayer {
+                    count: 32,
+                    color: 0xA0_A0_A0,
+                    speed: (0.0, 12.0),
+                    parallax: (0.3, 0.3),
+                },
+                StarLayer {
+                    count: 16,
+                    color: 0xFF_FF_FF,
+                    speed: (0.0, 32.0),
+                    parallax: (0.6, 0.6),
+                },
+            ],
+            twinkle: 0.5,
+            twinkle_speed: 0.5,
+        }
+    }
+
+    /// Draw the stars on the buffer at the moment, moved by the camera.
+    pub fn draw(&self, buffer: &mut PixelBuffer, elapsed: f32, camera: &Camera) {
+        let (width, height) = (buffer.width(), buffer.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let camera_pos = camera.pos();
+        for (layer_index, layer) in self.layers.iter().enumerate() {
+            let offset = (
+                layer.speed.0 * elapsed - camera_pos.0 as f32 * layer.parallax.0,
+                layer.speed.1 * elapsed - camera_pos.1 as f32 * layer.parallax.1,
+            );
+
+            for star in 0..layer.count {
+                // Hash the layer and the star with the seed, based on SplitMix64
+                let mut hash = self.seed ^ ((layer_index as u64) << 32 | star as u64);
+                hash = hash.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                hash ^= hash >> 31;
+
+                let x = (hash & 0xFFFF) as f32 / 65536.0 * width as f32 + offset.0;
+                let y = (hash >> 16 & 0xFFFF) as f32 / 65536.0 * height as f32 + offset.1;
+                let x = (x.floor() as i64).rem_euclid(width as i64) as usize;
+                let y = (y.floor() as i64).rem_euclid(height as i64) as usize;
+
+                // Every star twinkles with a different phase
+                let phase = (hash >> 32 & 0xFFFF) as f32 / 65536.0;
+                let wave = 0.5 + 0.5 * ((phase + elapsed * self.twinkle_speed) * TAU).sin();
+                let brightness = 1.0 - self.twinkle.clamp(0.0, 1.0) * wave;
+
+                let color = scale_color(layer.color, brightness);
+                if color != 0 {
+                    buffer.pixels_mut()[y * width + x] = color;
+                }
+            }
+        }
+    }
+}
+
+/// Multiply the color channels by the factor.
+fn scale_color(color: u32, factor: f32) -> u32 {
+    let channel =
+        |shift: u32| (((color >> shift & 0xFF) as f32 * factor) as u32).min(0xFF) << shift;
+
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Specs system drawing the [`Starfield`] resource directly on the buffer.
+///
+/// Does nothing when the resource doesn't exist. Run it first with `.with_thread_local()` so
+/// everything else is drawn on top of the stars.
+#[derive(Debug, Default)]
+pub struct StarfieldSystem;
+impl<'a> System<'a> for StarfieldSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Camera>>,
+        Read<'a, Time>,
+        Option<Read<'a, Starfield>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, camera, time, starfield, profiler): Self::SystemData) {
+        let start = Instant::now();
+        if let Some(starfield) = starfield {
+            let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+            starfield.draw(&mut buffer, time.elapsed().as_secs_f32(), &camera);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "StarfieldSystem", start);
+    }
+}
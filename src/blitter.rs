@@ -0,0 +1,81 @@
+//! The step that writes the pixels of a sprite into the buffer.
+
+use crate::{PixelBuffer, SpriteImage};
+
+/// Writes the pixels of sprites into the buffer.
+///
+/// The [`RenderSystem`](crate::RenderSystem) uses this for every sprite it draws, implement it to
+/// change how sprites are drawn.
+///
+/// [`Blitter::blend`] is called for every visible pixel of every sprite, after the effects are
+/// applied and before the alpha channel of the sprite is mixed in. [`Blitter::blit`] is only a
+/// fast path for sprites drawn as they are: without effects, scaling, flipping or an alpha
+/// channel. When it's overwritten it must give the same result as calling [`Blitter::blend`] for
+/// every visible pixel. A [`PixelFn`](crate::PixelFn) replaces the blitter for its entity.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Blitter, PixelBuffer, RenderSystem, Sprite};
+///
+/// /// Draw all sprites at half brightness.
+/// struct DarkBlitter;
+///
+/// impl Blitter for DarkBlitter {
+///     fn blend(&self, src: u32, _dst: u32) -> u32 {
+///         (src >> 1) & 0x7F_7F_7F
+///     }
+/// }
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(3, 1));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::new(DarkBlitter))
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// // The blitter is also used for sprites that are scaled
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+/// world.create_entity().with(Sprite::new(sprite_ref.clone())).build();
+/// let mut stretched = Sprite::new(sprite_ref);
+/// stretched.set_pos(1, 0);
+/// stretched.set_size(2, 1);
+/// world.create_entity().with(stretched).build();
+///
+/// dispatcher.dispatch(&world);
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert!(buffer.pixels().iter().all(|pixel| pixel & 0xFF_FF_FF == 0x7F_7F_7F));
+/// # Ok(())
+/// # }
+/// ```
+pub trait Blitter {
+    /// Combine a visible pixel of the sprite with the pixel already in the buffer.
+    fn blend(&self, src: u32, dst: u32) -> u32;
+
+    /// Draw all visible pixels of the image on the buffer at the position.
+    ///
+    /// The default implementation calls [`Blitter::blend`] for every visible pixel, this can be
+    /// overwritten with a faster implementation giving the same result. It's only called for
+    /// sprites without effects, scaling, flipping or an alpha channel.
+    fn blit(&self, image: &SpriteImage, buffer: &mut PixelBuffer, pos: (i32, i32)) {
+        image.blit_with(buffer, pos, |src, dst, _, _| *dst = self.blend(src, *dst));
+    }
+}
+
+/// Copies the visible pixels of sprites into the buffer, overwriting what's there.
+///
+/// This is the default blitter of the [`RenderSystem`](crate::RenderSystem).
+#[derive(Debug, Default, Copy, Clone)]
+pub struct MaskedBlitter;
+
+impl Blitter for MaskedBlitter {
+    fn blend(&self, src: u32, _dst: u32) -> u32 {
+        src
+    }
+
+    fn blit(&self, image: &SpriteImage, buffer: &mut PixelBuffer, pos: (i32, i32)) {
+        image.blit_with(buffer, pos, |src, dst, _, _| *dst = src);
+    }
+}
@@ -0,0 +1,105 @@
+//! Limiting how much is drawn every frame.
+
+/// Resource limiting how much the [`RenderSystem`](crate::RenderSystem) draws every frame.
+///
+/// When the sprites inside of the buffer exceed the budget, whole layers are skipped in the drop
+/// order until the frame fits. Layers that aren't in the drop order are always drawn, so the
+/// frame can still be over the budget. What's skipped is reported in the [`RenderStats`]
+/// resource.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, RenderBudget, RenderStats, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.insert(RenderStats::default());
+///
+/// // Draw at most 2 sprites, the particles on layer 2 are dropped first
+/// let mut budget = RenderBudget::new(vec![2, 1]);
+/// budget.max_sprites = Some(2);
+/// world.insert(budget);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+/// for layer in 0..3 {
+///     let mut sprite = Sprite::new(sprite_ref.clone());
+///     sprite.set_layer(layer);
+///     world.create_entity().with(sprite).build();
+/// }
+///
+/// testing::render(&mut world, 1, 1);
+///
+/// let stats = world.read_resource::<RenderStats>();
+/// assert_eq!(stats.sprites_drawn, 2);
+/// assert_eq!(stats.dropped_layers, vec![2]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderBudget {
+    /// Maximum amount of sprites drawn, `None` for no limit.
+    pub max_sprites: Option<usize>,
+    /// Maximum amount of pixels covered by all drawn sprites together, `None` for no limit.
+    pub max_pixels: Option<usize>,
+    /// Layers that can be skipped, the first one is skipped first.
+    pub drop_order: Vec<u8>,
+}
+
+impl RenderBudget {
+    /// Create a budget without limits that skips the layers in the order.
+    pub fn new(drop_order: Vec<u8>) -> Self {
+        Self {
+            max_sprites: None,
+            max_pixels: None,
+            drop_order,
+        }
+    }
+
+    /// Whether the amount of sprites and pixels fit in the budget.
+    pub fn fits(&self, sprites: usize, pixels: usize) -> bool {
+        self.max_sprites.is_none_or(|max| sprites <= max)
+            && self.max_pixels.is_none_or(|max| pixels <= max)
+    }
+
+    /// Layers that must be skipped so the sprites fit, every sprite is its layer and the amount of
+    /// pixels it covers.
+    pub(crate) fn layers_to_drop(&self, sprites: &[(u8, usize)]) -> Vec<u8> {
+        let mut count = sprites.len();
+        let mut pixels: usize = sprites.iter().map(|(_, pixels)| pixels).sum();
+
+        let mut dropped = Vec::new();
+        for &layer in self.drop_order.iter() {
+            if self.fits(count, pixels) {
+                break;
+            }
+
+            for (_, sprite_pixels) in sprites.iter().filter(|(other, _)| *other == layer) {
+                count -= 1;
+                pixels -= sprite_pixels;
+            }
+            dropped.push(layer);
+        }
+
+        dropped
+    }
+}
+
+/// Resource with what the [`RenderSystem`](crate::RenderSystem) drew in the last frame.
+///
+/// It's only filled when it's added to the world.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Amount of sprites drawn.
+    pub sprites_drawn: usize,
+    /// Amount of pixels covered by the drawn sprites, pixels covered multiple times are counted
+    /// multiple times.
+    pub pixels_drawn: usize,
+    /// Amount of sprites skipped because they are outside of the buffer.
+    pub sprites_culled: usize,
+    /// Amount of sprites skipped because of the [`RenderBudget`].
+    pub sprites_dropped: usize,
+    /// Layers skipped because of the [`RenderBudget`], in the order they were dropped.
+    pub dropped_layers: Vec<u8>,
+}
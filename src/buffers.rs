@@ -0,0 +1,142 @@
+//! Rendering to more than one buffer every frame.
+
+use crate::{Camera, PixelBuffer};
+use std::collections::HashMap;
+
+/// Resource with extra [`PixelBuffer`]s that render systems can target by name.
+///
+/// A [`RenderSystem`](crate::RenderSystem) created with
+/// [`with_target`](crate::RenderSystem::with_target) draws into the buffer with that name instead
+/// of the [`PixelBuffer`] resource. Every buffer can have its own [`Camera`], otherwise the
+/// [`Camera`] resource is used.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Camera, PixelBuffer, PixelBuffers, RenderSystem, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(8, 8));
+///
+/// // A small picture-in-picture view looking at another part of the world
+/// let mut buffers = PixelBuffers::new();
+/// buffers.insert("pip", PixelBuffer::new(4, 4));
+/// buffers.set_camera("pip", Camera::new(100, 100));
+/// world.insert(buffers);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     .with_thread_local(RenderSystem::default().with_target("pip"))
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?);
+/// sprite.set_pos(100, 100);
+/// world.create_entity().with(sprite).build();
+///
+/// dispatcher.dispatch(&world);
+///
+/// // The sprite is only seen by the camera of the picture-in-picture view
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[0], 0);
+/// let buffers = world.read_resource::<PixelBuffers>();
+/// assert_eq!(buffers.get("pip").unwrap().pixels()[0] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct PixelBuffers {
+    /// Buffers by their name.
+    buffers: HashMap<String, PixelBuffer>,
+    /// Cameras of the buffers that don't use the camera resource.
+    cameras: HashMap<String, Camera>,
+}
+
+impl PixelBuffers {
+    /// Create an empty collection of buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a buffer with the name, returns the buffer it replaces.
+    pub fn insert<S>(&mut self, name: S, buffer: PixelBuffer) -> Option<PixelBuffer>
+    where
+        S: Into<String>,
+    {
+        self.buffers.insert(name.into(), buffer)
+    }
+
+    /// Remove the buffer with the name together with its camera.
+    pub fn remove(&mut self, name: &str) -> Option<PixelBuffer> {
+        self.cameras.remove(name);
+
+        self.buffers.remove(name)
+    }
+
+    /// Get the buffer with the name.
+    pub fn get(&self, name: &str) -> Option<&PixelBuffer> {
+        self.buffers.get(name)
+    }
+
+    /// Get the buffer with the name so it can be changed, e.g. to clear it.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut PixelBuffer> {
+        self.buffers.get_mut(name)
+    }
+
+    /// Whether there's a buffer with the name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.buffers.contains_key(name)
+    }
+
+    /// Names of all buffers in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.buffers.keys().map(String::as_str)
+    }
+
+    /// Amount of buffers.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Whether there are no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Set all pixels of every buffer to the color.
+    pub fn clear(&mut self, color: u32) {
+        for buffer in self.buffers.values_mut() {
+            buffer.clear(color);
+        }
+    }
+
+    /// Look at the buffer with the name through its own camera instead of the camera resource.
+    pub fn set_camera<S>(&mut self, name: S, camera: Camera)
+    where
+        S: Into<String>,
+    {
+        self.cameras.insert(name.into(), camera);
+    }
+
+    /// Look at the buffer with the name through the camera resource again.
+    pub fn remove_camera(&mut self, name: &str) -> Option<Camera> {
+        self.cameras.remove(name)
+    }
+
+    /// Get the camera of the buffer with the name, `None` when it uses the camera resource.
+    pub fn camera(&self, name: &str) -> Option<&Camera> {
+        self.cameras.get(name)
+    }
+
+    /// Get the camera of the buffer with the name so it can be moved.
+    pub fn camera_mut(&mut self, name: &str) -> Option<&mut Camera> {
+        self.cameras.get_mut(name)
+    }
+
+    /// Get the buffer with the name to draw on together with its own camera.
+    pub(crate) fn target_mut(&mut self, name: &str) -> Option<(&mut PixelBuffer, Option<&Camera>)> {
+        let camera = self.cameras.get(name);
+
+        self.buffers.get_mut(name).map(|buffer| (buffer, camera))
+    }
+}
@@ -0,0 +1,155 @@
+//! Creating sprite entities with all their components at once.
+
+use crate::{Anchor, Animation, Pivot, Sprite, SpriteRef};
+use specs::{prelude::*, world::Builder};
+
+/// Extension for specs entity builders to add a sprite with all related components in one go.
+///
+/// Works for both `world.create_entity()` and `lazy.create_entity(&entities)`.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Animation, Sprite, SpriteBuilderExt};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Animation>();
+///
+/// let idle = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// let walk = load(BlitBuffer::from_buffer(&[0x00_FF_00], 1, 0xFF_00_FF))?;
+///
+/// let player = world
+///     .create_entity()
+///     .with_sprite(idle.clone())
+///     .pos(10, 20)
+///     .layer(2)
+///     .z(5)
+///     .animation(Animation::new(vec![
+///         (idle, Duration::from_millis(200)),
+///         (walk, Duration::from_millis(200)),
+///     ]))
+///     .build();
+///
+/// assert_eq!(world.read_storage::<Sprite>().get(player).unwrap().pos(), (10, 20));
+/// # Ok(())
+/// # }
+/// ```
+pub trait SpriteBuilderExt: Builder + Sized {
+    /// Start adding a [`Sprite`] component, the components are added when calling
+    /// [`SpriteBundleBuilder::build`].
+    fn with_sprite(self, sprite_ref: SpriteRef) -> SpriteBundleBuilder<Self> {
+        SpriteBundleBuilder {
+            builder: self,
+            sprite: Sprite::new(sprite_ref),
+            animation: None,
+        }
+    }
+}
+
+impl<B: Builder> SpriteBuilderExt for B {}
+
+/// Entity builder for a sprite and the components related to it.
+///
+/// Created with [`SpriteBuilderExt::with_sprite`].
+#[derive(Debug)]
+pub struct SpriteBundleBuilder<B> {
+    builder: B,
+    sprite: Sprite,
+    animation: Option<Animation>,
+}
+
+impl<B: Builder> SpriteBundleBuilder<B> {
+    /// Set the pixel position, see [`Sprite::set_pos`].
+    pub fn pos(mut self, x: i32, y: i32) -> Self {
+        self.sprite.set_pos(x, y);
+
+        self
+    }
+
+    /// Set the rotation in degrees, see [`Sprite::set_rot`].
+    pub fn rot(mut self, rotation: i16) -> Self {
+        self.sprite.set_rot(rotation);
+
+        self
+    }
+
+    /// Set the point the sprite rotates around, see [`Sprite::set_pivot`].
+    pub fn pivot(mut self, pivot: Pivot) -> Self {
+        self.sprite.set_pivot(pivot);
+
+        self
+    }
+
+    /// Set the point placed at the position, see [`Sprite::set_anchor`].
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.sprite.set_anchor(anchor);
+
+        self
+    }
+
+    /// Set the size the sprite is stretched to, see [`Sprite::set_size`].
+    pub fn size(mut self, width: usize, height: usize) -> Self {
+        self.sprite.set_size(width, height);
+
+        self
+    }
+
+    /// Set the factor the size is multiplied with, see [`Sprite::set_scale`].
+    pub fn scale(mut self, x: f64, y: f64) -> Self {
+        self.sprite.set_scale(x, y);
+
+        self
+    }
+
+    /// Set an extra transparent color, see [`Sprite::set_mask_color`].
+    pub fn mask_color(mut self, mask_color: u32) -> Self {
+        self.sprite.set_mask_color(Some(mask_color));
+
+        self
+    }
+
+    /// Set the layer, see [`Sprite::set_layer`].
+    pub fn layer(mut self, layer: u8) -> Self {
+        self.sprite.set_layer(layer);
+
+        self
+    }
+
+    /// Set the order within the layer, see [`Sprite::set_z`].
+    pub fn z(mut self, z: i32) -> Self {
+        self.sprite.set_z(z);
+
+        self
+    }
+
+    /// Animate the sprite, the sprite will start with the first frame of the animation.
+    pub fn animation(mut self, animation: Animation) -> Self {
+        self.sprite.reference = animation.current_frame().clone();
+        self.animation = Some(animation);
+
+        self
+    }
+
+    /// Add any other component to the entity.
+    pub fn with<C>(mut self, component: C) -> Self
+    where
+        C: Component + Send + Sync,
+    {
+        self.builder = self.builder.with(component);
+
+        self
+    }
+
+    /// Add all components and create the entity.
+    pub fn build(self) -> Entity {
+        let builder = self.builder.with(self.sprite);
+
+        match self.animation {
+            Some(animation) => builder.with(animation).build(),
+            None => builder.build(),
+        }
+    }
+}
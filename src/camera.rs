@@ -0,0 +1,587 @@
+//! The view on the world that sprites are rendered through.
+
+use crate::{PixelBuffer, Sprite, Time};
+use specs::prelude::*;
+use std::collections::HashSet;
+
+/// Resource for moving and zooming the view of the [`RenderSystem`](crate::RenderSystem).
+///
+/// Sprite positions are in world coordinates, the camera position is the world coordinate shown
+/// at the top left corner of the buffer. Without this resource the world coordinates are the
+/// buffer coordinates.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Camera, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF0000; 4], 2, 0xFF00FF))?);
+/// sprite.set_pos(100, 50);
+/// world.create_entity().with(sprite).build();
+///
+/// // Look at the sprite from a distance
+/// let mut camera = Camera::new(96, 48);
+/// camera.set_zoom(0.5);
+/// world.insert(camera);
+///
+/// let buffer = testing::render(&mut world, 10, 10);
+/// assert_eq!(buffer.pixels()[10 + 2] & 0xFFFFFF, 0xFF0000);
+/// assert_eq!(buffer.pixels()[10 + 3], 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    /// World coordinate at the top left corner of the buffer.
+    pos: (i32, i32),
+    /// How many buffer pixels a world pixel takes up.
+    zoom: f64,
+    /// Clockwise rotation in degrees of the camera around the center of the view.
+    rotation: f64,
+    /// Rectangle in world coordinates the view must stay inside of.
+    bounds: Option<(i32, i32, u32, u32)>,
+    /// Size in buffer pixels of the buffer the camera is rendered to.
+    viewport: (usize, usize),
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl Camera {
+    /// Create a camera showing the world from the position without zooming.
+    pub fn new(x: i32, y: i32) -> Self {
+        Self {
+            pos: (x, y),
+            zoom: 1.0,
+            rotation: 0.0,
+            bounds: None,
+            viewport: (0, 0),
+        }
+    }
+
+    /// Set the world coordinate shown at the top left corner of the buffer.
+    ///
+    /// When the camera has bounds the position is moved so the view stays inside of them.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+        self.clamp();
+    }
+
+    /// Get the world coordinate shown at the top left corner of the buffer.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Set how many buffer pixels a world pixel takes up, values below `1.0` zoom out.
+    ///
+    /// Negative values are treated as `0.0`.
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom.max(0.0);
+        self.clamp();
+    }
+
+    /// Get how many buffer pixels a world pixel takes up.
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    /// Turn the camera clockwise around the center of the view, the world appears to turn the
+    /// other way.
+    ///
+    /// The positions of the sprites are rotated and their rotated versions are picked to match,
+    /// so only sprites loaded with rotations turn themselves. Tilemaps and backgrounds aren't
+    /// rotated. This needs the size of the buffer the camera is rendered to, which the
+    /// [`RenderSystem`](crate::RenderSystem) sets.
+    ///
+    /// ```rust
+    /// use specs_blit::Camera;
+    ///
+    /// let mut camera = Camera::new(0, 0);
+    /// camera.set_viewport(10, 10);
+    /// camera.set_rotation(90.0);
+    ///
+    /// // What's to the right of the center is shown above it
+    /// assert_eq!(camera.to_screen((8, 5)), (5, 2));
+    /// ```
+    ///
+    /// Sprites are turned around their centers, so a sprite in the top left quarter of the view
+    /// ends up in the bottom left quarter when the camera is turned a quarter:
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs::prelude::*;
+    /// use specs_blit::{load, testing, Camera, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut world = World::new();
+    /// world.register::<Sprite>();
+    ///
+    /// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 100], 10, 0xFF_00_FF))?;
+    /// world.create_entity().with(Sprite::new(sprite_ref)).build();
+    ///
+    /// let drawn = |world: &mut World, rotation: f64| {
+    ///     let mut camera = Camera::new(0, 0);
+    ///     camera.set_rotation(rotation);
+    ///     world.insert(camera);
+    ///
+    ///     let buffer = testing::render(world, 20, 20);
+    ///     // Count the drawn pixels in every quarter, from the top left to the bottom right
+    ///     let mut quarters = [0; 4];
+    ///     for (index, pixel) in buffer.pixels().iter().enumerate() {
+    ///         if *pixel != 0 {
+    ///             let (x, y) = (index % 20, index / 20);
+    ///             quarters[x / 10 + y / 10 * 2] += 1;
+    ///         }
+    ///     }
+    ///
+    ///     quarters
+    /// };
+    ///
+    /// assert_eq!(drawn(&mut world, 0.0), [100, 0, 0, 0]);
+    /// assert_eq!(drawn(&mut world, 90.0), [0, 0, 100, 0]);
+    /// assert_eq!(drawn(&mut world, 180.0), [0, 0, 0, 100]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_rotation(&mut self, degrees: f64) {
+        self.rotation = degrees.rem_euclid(360.0);
+    }
+
+    /// Get the clockwise rotation in degrees of the camera, between `0.0` and `360.0`.
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    /// Keep the view inside of the rectangle in world coordinates, e.g. the size of the level.
+    ///
+    /// The view is centered on the rectangle along the axes where it's smaller than the view.
+    /// This needs the size of the buffer the camera is rendered to, set with
+    /// [`set_viewport`](Camera::set_viewport).
+    ///
+    /// ```rust
+    /// use specs_blit::Camera;
+    ///
+    /// let mut camera = Camera::new(0, 0);
+    /// camera.set_viewport(100, 50);
+    /// // The level is wider but less high than the view
+    /// camera.set_bounds(0, 0, 400, 30);
+    ///
+    /// camera.set_pos(350, 0);
+    /// assert_eq!(camera.pos(), (300, -10));
+    ///
+    /// // Zooming in shows less of the world so the camera can move further
+    /// camera.set_zoom(2.0);
+    /// camera.set_pos(350, 10);
+    /// assert_eq!(camera.pos(), (350, 5));
+    /// ```
+    pub fn set_bounds(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        self.bounds = Some((x, y, width, height));
+        self.clamp();
+    }
+
+    /// Let the view go anywhere again.
+    pub fn clear_bounds(&mut self) {
+        self.bounds = None;
+    }
+
+    /// Get the rectangle in world coordinates the view stays inside of.
+    pub fn bounds(&self) -> Option<(i32, i32, u32, u32)> {
+        self.bounds
+    }
+
+    /// Set the size in pixels of the buffer the camera is rendered to, used for the bounds.
+    ///
+    /// The [`CameraFollowSystem`] sets it to the size of the [`PixelBuffer`] every frame.
+    pub fn set_viewport(&mut self, width: usize, height: usize) {
+        self.viewport = (width, height);
+        self.clamp();
+    }
+
+    /// Get the size in pixels of the buffer the camera is rendered to.
+    pub fn viewport(&self) -> (usize, usize) {
+        self.viewport
+    }
+
+    /// Move the position so the view stays inside of the bounds.
+    fn clamp(&mut self) {
+        let (x, y, width, height) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let zoom = if self.zoom > 0.0 { self.zoom } else { 1.0 };
+        let axis = |pos: i32, start: i32, size: u32, viewport: usize| {
+            // Amount of world pixels the view is larger than the bounds
+            let overflow = viewport as f64 / zoom - f64::from(size);
+            if overflow >= 0.0 {
+                start - (overflow / 2.0).round() as i32
+            } else {
+                pos.max(start).min(start + (-overflow).floor() as i32)
+            }
+        };
+
+        self.pos = (
+            axis(self.pos.0, x, width, self.viewport.0),
+            axis(self.pos.1, y, height, self.viewport.1),
+        );
+    }
+
+    /// Convert a world coordinate to a buffer coordinate.
+    pub fn to_screen(&self, pos: (i32, i32)) -> (i32, i32) {
+        let (x, y) = self.turn(self.to_view(pos, false));
+
+        (x.round() as i32, y.round() as i32)
+    }
+
+    /// Convert a world coordinate to a buffer coordinate, rounding the zoomed coordinate and the
+    /// zoomed camera position to the pixel grid separately.
+    ///
+    /// When zoomed, the distance between positions doesn't change while the camera moves.
+    ///
+    /// ```rust
+    /// use specs_blit::Camera;
+    ///
+    /// let mut camera = Camera::new(0, 0);
+    /// camera.set_zoom(1.5);
+    ///
+    /// for x in 0..4 {
+    ///     camera.set_pos(x, 0);
+    ///     assert_eq!(camera.to_screen_snapped((2, 0)).0 - camera.to_screen_snapped((1, 0)).0, 1);
+    /// }
+    /// ```
+    pub fn to_screen_snapped(&self, pos: (i32, i32)) -> (i32, i32) {
+        let (x, y) = self.turn(self.to_view(pos, true));
+
+        (x.round() as i32, y.round() as i32)
+    }
+
+    /// Get the top left corner on the buffer of an image drawn at the world position.
+    ///
+    /// The offset and size are of the image drawn without the rotation of the camera. The
+    /// center of that image is turned around the center of the view, and the image drawn with
+    /// the rotation of the camera, which has the rotated size, is centered on it.
+    pub(crate) fn image_to_screen(
+        &self,
+        pos: (i32, i32),
+        snapped: bool,
+        (offset, size): ((i32, i32), (usize, usize)),
+        rotated_size: (usize, usize),
+    ) -> (i32, i32) {
+        let (x, y) = self.to_view(pos, snapped);
+        if self.rotation == 0.0 {
+            return (x.round() as i32 + offset.0, y.round() as i32 + offset.1);
+        }
+
+        let (x, y) = self.turn((
+            x + f64::from(offset.0) + size.0 as f64 / 2.0,
+            y + f64::from(offset.1) + size.1 as f64 / 2.0,
+        ));
+
+        (
+            (x - rotated_size.0 as f64 / 2.0).round() as i32,
+            (y - rotated_size.1 as f64 / 2.0).round() as i32,
+        )
+    }
+
+    /// Convert a world coordinate to a buffer coordinate without turning it.
+    fn to_view(&self, pos: (i32, i32), snapped: bool) -> (f64, f64) {
+        if snapped {
+            let snap = |pos: i32, camera: i32| {
+                (f64::from(pos) * self.zoom).round() - (f64::from(camera) * self.zoom).round()
+            };
+
+            (snap(pos.0, self.pos.0), snap(pos.1, self.pos.1))
+        } else {
+            (
+                f64::from(pos.0 - self.pos.0) * self.zoom,
+                f64::from(pos.1 - self.pos.1) * self.zoom,
+            )
+        }
+    }
+
+    /// Turn a buffer coordinate around the center of the view.
+    fn turn(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        if self.rotation == 0.0 {
+            return (x, y);
+        }
+
+        // The world turns the other way around the center of the view
+        let center = (self.viewport.0 as f64 / 2.0, self.viewport.1 as f64 / 2.0);
+        let (sin, cos) = (-self.rotation).to_radians().sin_cos();
+        let (dx, dy) = (x - center.0, y - center.1);
+
+        (
+            center.0 + dx * cos - dy * sin,
+            center.1 + dx * sin + dy * cos,
+        )
+    }
+
+    /// Rotation in degrees a sprite is drawn with when it's rotated in the world.
+    pub(crate) fn screen_rotation(&self, rot: i16) -> i16 {
+        if self.rotation == 0.0 {
+            rot
+        } else {
+            (f64::from(rot) - self.rotation).round().rem_euclid(360.0) as i16
+        }
+    }
+}
+
+/// Make the [`Camera`] follow the [`Sprite`] of this entity with the [`CameraFollowSystem`].
+///
+/// The camera keeps the sprite position in the center of the view. It doesn't move while the
+/// sprite is inside of the deadzone rectangle around the center, catches up gradually with
+/// smoothing and never moves faster than the maximum speed. When more entities have this
+/// component only one of them is followed.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Camera, CameraFollow, CameraFollowSystem, PixelBuffer, Sprite, Time};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(100, 100));
+/// world.insert(Camera::new(0, 0));
+/// world.insert(Time::default());
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(CameraFollowSystem::default(), "camera_follow", &[])
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?);
+/// sprite.set_pos(60, 50);
+/// let player = world
+///     .create_entity()
+///     .with(sprite)
+///     .with(CameraFollow::new().with_deadzone(20, 20))
+///     .build();
+///
+/// // The player is inside of the deadzone so the camera doesn't move
+/// world.write_resource::<Time>().advance(Duration::from_millis(16));
+/// dispatcher.dispatch(&world);
+/// assert_eq!(world.read_resource::<Camera>().pos(), (0, 0));
+///
+/// // The camera moves just enough to keep the player at the edge of the deadzone
+/// world.write_storage::<Sprite>().get_mut(player).unwrap().set_pos(80, 50);
+/// world.write_resource::<Time>().advance(Duration::from_millis(16));
+/// dispatcher.dispatch(&world);
+/// assert_eq!(world.read_resource::<Camera>().pos(), (20, 0));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CameraFollow {
+    /// Size of the rectangle around the center of the view in which the target can move freely.
+    deadzone: (u32, u32),
+    /// How quickly the camera catches up, `None` snaps to the target.
+    smoothing: Option<f64>,
+    /// Maximum speed of the camera in world pixels per second.
+    max_speed: Option<f64>,
+}
+
+impl CameraFollow {
+    /// Follow the entity by keeping it exactly in the center of the view.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only move the camera when the target leaves the rectangle of this size in world pixels
+    /// around the center of the view.
+    pub fn with_deadzone(mut self, width: u32, height: u32) -> Self {
+        self.deadzone = (width, height);
+
+        self
+    }
+
+    /// Catch up with the target gradually instead of snapping to it.
+    ///
+    /// Every second the camera covers all but `exp(-rate)` of the distance it still has to go, so
+    /// higher rates follow more closely. The movement doesn't depend on the frame rate.
+    pub fn with_smoothing(mut self, rate: f64) -> Self {
+        self.smoothing = Some(rate.max(0.0));
+
+        self
+    }
+
+    /// Never move the camera faster than this amount of world pixels per second.
+    pub fn with_max_speed(mut self, max_speed: f64) -> Self {
+        self.max_speed = Some(max_speed.max(0.0));
+
+        self
+    }
+
+    /// Size of the deadzone rectangle in world pixels.
+    pub fn deadzone(&self) -> (u32, u32) {
+        self.deadzone
+    }
+
+    /// How quickly the camera catches up, `None` when it snaps to the target.
+    pub fn smoothing(&self) -> Option<f64> {
+        self.smoothing
+    }
+
+    /// Maximum speed of the camera in world pixels per second.
+    pub fn max_speed(&self) -> Option<f64> {
+        self.max_speed
+    }
+
+    /// Where the center of the view should move to this frame.
+    fn step(&self, center: (f64, f64), target: (f64, f64), seconds: f64) -> (f64, f64) {
+        // Only the part of the target outside of the deadzone pulls the camera
+        let axis = |center: f64, target: f64, deadzone: u32| {
+            let half = f64::from(deadzone) / 2.0;
+            (target - half).max(center).min(target + half) - center
+        };
+        let mut offset = (
+            axis(center.0, target.0, self.deadzone.0),
+            axis(center.1, target.1, self.deadzone.1),
+        );
+
+        if let Some(rate) = self.smoothing {
+            let factor = 1.0 - (-rate * seconds).exp();
+            offset = (offset.0 * factor, offset.1 * factor);
+        }
+
+        if let Some(max_speed) = self.max_speed {
+            let distance = offset.0.hypot(offset.1);
+            let max_distance = max_speed * seconds;
+            if distance > max_distance {
+                let factor = max_distance / distance;
+                offset = (offset.0 * factor, offset.1 * factor);
+            }
+        }
+
+        (center.0 + offset.0, center.1 + offset.1)
+    }
+}
+
+impl Component for CameraFollow {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system moving the [`Camera`] resource to the entity with a [`CameraFollow`].
+///
+/// The view size is taken from the [`PixelBuffer`] and the zoom of the camera, smoothing and the
+/// maximum speed use the [`Time`] resource. The camera doesn't follow the target outside of the
+/// [bounds](Camera::set_bounds) of the camera. Run it after the systems moving the target and
+/// before the [`RenderSystem`](crate::RenderSystem).
+#[derive(Debug, Default)]
+pub struct CameraFollowSystem {
+    /// Center of the view with sub-pixel precision and the camera position it was rounded to.
+    center: Option<((f64, f64), (i32, i32))>,
+}
+
+impl<'a> System<'a> for CameraFollowSystem {
+    type SystemData = (
+        Read<'a, PixelBuffer>,
+        Read<'a, Time>,
+        Write<'a, Camera>,
+        ReadStorage<'a, Sprite>,
+        ReadStorage<'a, CameraFollow>,
+    );
+
+    fn run(&mut self, (buffer, time, mut camera, sprites, follows): Self::SystemData) {
+        let (follow, sprite) = match (&follows, &sprites).join().next() {
+            Some(found) => found,
+            None => return,
+        };
+
+        camera.set_viewport(buffer.width(), buffer.height());
+
+        let zoom = if camera.zoom() > 0.0 {
+            camera.zoom()
+        } else {
+            1.0
+        };
+        let half_view = (
+            buffer.width() as f64 / zoom / 2.0,
+            buffer.height() as f64 / zoom / 2.0,
+        );
+
+        // Start from the camera position again when something else moved it
+        let center = match self.center {
+            Some((center, pos)) if pos == camera.pos() => center,
+            _ => {
+                let (x, y) = camera.pos();
+                (f64::from(x) + half_view.0, f64::from(y) + half_view.1)
+            }
+        };
+
+        let (x, y) = sprite.pos();
+        let center = follow.step(
+            center,
+            (f64::from(x), f64::from(y)),
+            time.delta().as_secs_f64(),
+        );
+
+        // Rounding the position of the camera and not of every sprite keeps the scene from
+        // jittering
+        let pos = (
+            (center.0 - half_view.0).round() as i32,
+            (center.1 - half_view.1).round() as i32,
+        );
+        camera.set_pos(pos.0, pos.1);
+        self.center = Some((center, pos));
+    }
+}
+
+/// Resource with the sprite layers whose positions are snapped to the pixel grid.
+///
+/// When the camera is zoomed, a world pixel isn't a whole amount of buffer pixels. The distance
+/// between sprites on the buffer then changes by a pixel while the camera moves, which makes
+/// them swim. Sprites on snapped layers are positioned with
+/// [`Camera::to_screen_snapped`], which rounds the camera and the sprites the same way.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{PixelSnap, RenderSystem};
+///
+/// let mut world = World::new();
+///
+/// // Snap the world on layer 0 but keep the smooth movement of the UI on layer 1
+/// let mut pixel_snap = PixelSnap::new();
+/// pixel_snap.snap(0);
+/// world.insert(pixel_snap);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PixelSnap {
+    /// Layers that are snapped.
+    layers: HashSet<u8>,
+}
+
+impl PixelSnap {
+    /// Create the resource without any snapped layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snap the positions of the sprites on the layer to the pixel grid.
+    pub fn snap(&mut self, layer: u8) {
+        self.layers.insert(layer);
+    }
+
+    /// Stop snapping the positions of the sprites on the layer.
+    pub fn unsnap(&mut self, layer: u8) {
+        self.layers.remove(&layer);
+    }
+
+    /// Whether the positions of the sprites on the layer are snapped to the pixel grid.
+    pub fn is_snapped(&self, layer: u8) -> bool {
+        self.layers.contains(&layer)
+    }
+}
@@ -0,0 +1,276 @@
+//! Clearing the buffer before a frame is drawn.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    PixelBuffer,
+};
+use specs::prelude::*;
+use std::cmp;
+use web_time::Instant;
+
+/// Resource with the color the [`ClearSystem`] fills the buffer with, black by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClearColor(pub u32);
+
+/// Resource with the parts of the buffer the [`RenderSystem`](crate::RenderSystem) drew on, so
+/// the [`ClearSystem`] only clears those instead of the whole buffer.
+///
+/// This is much faster for scenes with a few sprites on an empty buffer. Only the sprites and the
+/// commands of the [`DrawQueue`](crate::DrawQueue) are remembered, call
+/// [`DrawnRegions::invalidate`] after drawing anything else so the next clear covers the whole
+/// buffer. The whole buffer is also cleared the first time, when the regions together are larger
+/// than the buffer and when the [`ClearColor`] or the size of the buffer changed.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     load, ClearColor, ClearSystem, DrawnRegions, PixelBuffer, RenderSystem, Sprite,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(4, 1));
+/// world.insert(DrawnRegions::new());
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(ClearSystem, "clear", &[])
+///     .with(RenderSystem::default(), "render", &["clear"])
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+/// world.create_entity().with(Sprite::new(sprite_ref)).build();
+/// dispatcher.dispatch(&world);
+/// assert_eq!(world.read_resource::<DrawnRegions>().regions(), &[(0, 0, 1, 1)]);
+///
+/// // Something not drawn by the render system isn't cleared
+/// world.write_resource::<PixelBuffer>().pixels_mut()[3] = 0xFF_00_00;
+/// dispatcher.dispatch(&world);
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[3], 0xFF_00_00);
+///
+/// // A new clear color fills the whole buffer again
+/// world.insert(ClearColor(0x33_33_33));
+/// dispatcher.dispatch(&world);
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[3], 0x33_33_33);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrawnRegions {
+    /// Horizontal & vertical position, width and height of the parts that were drawn on.
+    regions: Vec<(usize, usize, usize, usize)>,
+    /// Amount of pixels covered by the regions, overlapping pixels are counted more than once.
+    area: usize,
+    /// Whether the whole buffer must be cleared.
+    full: bool,
+    /// Width and height of the buffer at the last clear.
+    buffer_size: (usize, usize),
+    /// Color of the last clear, `None` before the first one.
+    color: Option<u32>,
+    /// Parts that were filled by the last clear.
+    cleared: Vec<(usize, usize, usize, usize)>,
+    /// Whether the last clear filled the whole buffer.
+    cleared_full: bool,
+}
+
+impl Default for DrawnRegions {
+    fn default() -> Self {
+        Self {
+            regions: Vec::new(),
+            area: 0,
+            // What was drawn before the resource existed isn't known
+            full: true,
+            buffer_size: (0, 0),
+            color: None,
+            cleared: Vec::new(),
+            cleared_full: true,
+        }
+    }
+}
+
+impl DrawnRegions {
+    /// Create the resource, the first clear covers the whole buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the horizontal & vertical position, width and height of the parts that were drawn on
+    /// since the last clear.
+    pub fn regions(&self) -> &[(usize, usize, usize, usize)] {
+        &self.regions
+    }
+
+    /// Get the parts of the buffer that changed since the frame before the last clear, e.g. to
+    /// only send those to a slow display.
+    ///
+    /// These are the parts the last clear filled and the parts that were drawn on after it, they
+    /// can overlap. When the whole buffer could have changed it's the only region.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs::prelude::*;
+    /// use specs_blit::{load, ClearSystem, DrawnRegions, PixelBuffer, RenderSystem, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut world = World::new();
+    /// world.insert(PixelBuffer::new(4, 1));
+    /// world.insert(DrawnRegions::new());
+    ///
+    /// let mut dispatcher = DispatcherBuilder::new()
+    ///     .with(ClearSystem, "clear", &[])
+    ///     .with(RenderSystem::default(), "render", &["clear"])
+    ///     .build();
+    /// dispatcher.setup(&mut world);
+    ///
+    /// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+    /// let entity = world.create_entity().with(Sprite::new(sprite_ref)).build();
+    ///
+    /// // Everything is new in the first frame
+    /// dispatcher.dispatch(&world);
+    /// assert_eq!(world.read_resource::<DrawnRegions>().damaged_regions(), [(0, 0, 4, 1)]);
+    ///
+    /// // Moving the sprite changes where it was and where it is
+    /// world.write_storage::<Sprite>().get_mut(entity).unwrap().set_pos(2, 0);
+    /// dispatcher.dispatch(&world);
+    /// assert_eq!(
+    ///     world.read_resource::<DrawnRegions>().damaged_regions(),
+    ///     [(0, 0, 1, 1), (2, 0, 1, 1)]
+    /// );
+    ///
+    /// // A new buffer of another size is cleared and damaged completely
+    /// world.insert(PixelBuffer::new(2, 1));
+    /// world.write_storage::<Sprite>().get_mut(entity).unwrap().set_pos(5, 0);
+    /// dispatcher.dispatch(&world);
+    /// assert_eq!(world.read_resource::<DrawnRegions>().damaged_regions(), [(0, 0, 2, 1)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn damaged_regions(&self) -> Vec<(usize, usize, usize, usize)> {
+        if self.full || self.cleared_full {
+            return vec![(0, 0, self.buffer_size.0, self.buffer_size.1)];
+        }
+
+        self.cleared.iter().chain(&self.regions).copied().collect()
+    }
+
+    /// Clear the whole buffer the next time, because something was drawn that isn't remembered.
+    pub fn invalidate(&mut self) {
+        self.regions.clear();
+        self.area = 0;
+        self.full = true;
+    }
+
+    /// Remember that the part of the buffer with the size at the position was drawn on.
+    pub(crate) fn add(
+        &mut self,
+        pos: (i32, i32),
+        size: (usize, usize),
+        buffer_size: (usize, usize),
+    ) {
+        if self.full {
+            return;
+        }
+        if buffer_size != self.buffer_size {
+            // The buffer was replaced after the last clear, so it was never cleared
+            self.invalidate();
+            return;
+        }
+
+        // Only the part inside of the buffer has to be cleared
+        let start = (cmp::max(pos.0, 0), cmp::max(pos.1, 0));
+        let end = (
+            cmp::min(pos.0 + size.0 as i32, buffer_size.0 as i32),
+            cmp::min(pos.1 + size.1 as i32, buffer_size.1 as i32),
+        );
+        if start.0 >= end.0 || start.1 >= end.1 {
+            return;
+        }
+
+        let (width, height) = ((end.0 - start.0) as usize, (end.1 - start.1) as usize);
+        self.area += width * height;
+        if self.area >= buffer_size.0 * buffer_size.1 {
+            // Clearing everything at once is faster
+            self.invalidate();
+        } else {
+            self.regions
+                .push((start.0 as usize, start.1 as usize, width, height));
+        }
+    }
+
+    /// Fill the parts that were drawn on with the color and forget them.
+    fn clear(&mut self, buffer: &mut PixelBuffer, color: u32) {
+        // The parts that weren't drawn on still have the old color, and a replaced buffer was
+        // never cleared at all
+        let buffer_size = (buffer.width(), buffer.height());
+        self.cleared_full =
+            self.full || self.buffer_size != buffer_size || self.color != Some(color);
+        if self.cleared_full {
+            buffer.clear(color);
+        } else {
+            let pixels = buffer.pixels_mut();
+            for &(x, y, width, height) in &self.regions {
+                for row in y..y + height {
+                    let start = row * buffer_size.0 + x;
+                    pixels[start..start + width].fill(color);
+                }
+            }
+        }
+
+        // Remember what was cleared for the damaged regions of this frame
+        self.cleared.clear();
+        if !self.cleared_full {
+            self.cleared.append(&mut self.regions);
+        }
+        self.buffer_size = buffer_size;
+        self.color = Some(color);
+        self.area = 0;
+        self.full = false;
+    }
+}
+
+/// Specs system filling the [`PixelBuffer`] with the [`ClearColor`] resource.
+///
+/// Run it before everything else is drawn, otherwise the sprites of the previous frames smear
+/// over the buffer. With a [`DrawnRegions`] resource only the parts that were drawn on are
+/// filled.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{ClearColor, ClearSystem, PixelBuffer, RenderSystem};
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(2, 1));
+/// world.insert(ClearColor(0x33_33_33));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(ClearSystem, "clear", &[])
+///     .with(RenderSystem::default(), "render", &["clear"])
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+///
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels(), &[0x33_33_33; 2]);
+/// ```
+#[derive(Debug, Default)]
+pub struct ClearSystem;
+impl<'a> System<'a> for ClearSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, ClearColor>>,
+        Option<Write<'a, DrawnRegions>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, clear_color, drawn_regions, profiler): Self::SystemData) {
+        let start = Instant::now();
+
+        let color = clear_color.map_or(0, |clear_color| clear_color.0);
+        match drawn_regions {
+            Some(mut drawn_regions) => drawn_regions.clear(&mut buffer, color),
+            None => buffer.clear(color),
+        }
+
+        profiler::record(profiler, RenderPhase::Clear, "ClearSystem", start);
+    }
+}
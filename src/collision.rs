@@ -0,0 +1,114 @@
+//! Solid parts of a tilemap for gameplay code.
+
+/// Grid of solid and empty tiles extracted from a [`Tilemap`](crate::Tilemap).
+///
+/// Which tiles are solid is decided by the sprite they use, see
+/// [`Tilemap::set_solid`](crate::Tilemap::set_solid). The grid is a copy, so changing the tilemap
+/// afterwards doesn't change it.
+///
+/// ```rust
+/// use specs_blit::Tilemap;
+///
+/// // A wall at the top and a block in the bottom right corner
+/// let mut tilemap = Tilemap::new(3, 3, (16, 16), vec![]);
+/// for x in 0..3 {
+///     tilemap.set_tile(x, 0, Some(1));
+/// }
+/// tilemap.set_tile(2, 2, Some(1));
+/// tilemap.set_solid(1, true);
+///
+/// let collision = tilemap.collision_layer();
+/// assert!(collision.is_solid(1, 0));
+/// assert!(!collision.is_solid(1, 1));
+///
+/// // Neighboring solid tiles are merged into as few rectangles as possible
+/// let rects: Vec<_> = collision.rects().collect();
+/// assert_eq!(rects, vec![(0, 0, 3, 1), (2, 2, 1, 1)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionLayer {
+    /// Amount of tiles in a row.
+    width: usize,
+    /// Amount of rows.
+    height: usize,
+    /// Whether every tile is solid.
+    solid: Vec<bool>,
+}
+
+impl CollisionLayer {
+    /// Create a grid from whether every tile is solid, row by row.
+    ///
+    /// # Panics
+    ///
+    /// When the amount of flags doesn't match the size of the grid.
+    pub fn new(width: usize, height: usize, solid: Vec<bool>) -> Self {
+        assert_eq!(
+            solid.len(),
+            width * height,
+            "the collision layer needs a flag for every tile"
+        );
+
+        Self {
+            width,
+            height,
+            solid,
+        }
+    }
+
+    /// Amount of tiles in a row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Amount of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the tile is solid, `false` when it's outside of the grid.
+    pub fn is_solid(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.solid[y * self.width + x]
+    }
+
+    /// Rectangles covering all solid tiles as `(x, y, width, height)` in tiles.
+    ///
+    /// Runs of solid tiles in a row are merged with the rows below them when they have the same
+    /// width, so a rectangular block of tiles results in a single rectangle.
+    pub fn rects(&self) -> impl Iterator<Item = (usize, usize, usize, usize)> {
+        let mut covered = vec![false; self.solid.len()];
+        let mut rects = Vec::new();
+
+        for y in 0..self.height {
+            let mut x = 0;
+            while x < self.width {
+                if !self.is_solid(x, y) || covered[y * self.width + x] {
+                    x += 1;
+                    continue;
+                }
+
+                // Extend to the right as long as the tiles are not part of another rectangle
+                let mut width = 1;
+                while self.is_solid(x + width, y) && !covered[y * self.width + x + width] {
+                    width += 1;
+                }
+
+                // Extend down as long as the whole run is solid
+                let mut height = 1;
+                while (x..x + width).all(|run_x| {
+                    self.is_solid(run_x, y + height) && !covered[(y + height) * self.width + run_x]
+                }) {
+                    height += 1;
+                }
+
+                for rect_y in y..y + height {
+                    covered[rect_y * self.width + x..rect_y * self.width + x + width].fill(true);
+                }
+                rects.push((x, y, width, height));
+
+                x += width;
+            }
+        }
+
+        rects.into_iter()
+    }
+}
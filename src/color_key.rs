@@ -0,0 +1,121 @@
+//! Loading sprites whose transparent color isn't exactly the same everywhere.
+
+use crate::{RotationAlgorithm, SpriteError, SpriteImage, SpriteRef};
+
+/// Colors treated as transparent when loading a sprite with [`load_keyed`].
+///
+/// Images that are compressed or scanned don't have a perfectly uniform background color, a
+/// tolerance makes colors close to the key transparent as well.
+///
+/// ```rust
+/// use specs_blit::ColorKey;
+///
+/// let key = ColorKey::new(0xFF_00_FF).with_color(0x00_FF_00).with_tolerance(2);
+///
+/// assert!(key.matches(0xFE_01_FD));
+/// assert!(key.matches(0x00_FF_00));
+/// assert!(!key.matches(0xFC_00_FF));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorKey {
+    /// Colors in the `0x00RRGGBB` format, the first one is used as the mask color.
+    colors: Vec<u32>,
+    /// Maximum difference of every color channel with a key color.
+    tolerance: u8,
+}
+
+impl ColorKey {
+    /// Treat exactly this color as transparent.
+    pub fn new(color: u32) -> Self {
+        Self {
+            colors: vec![color & 0xFF_FF_FF],
+            tolerance: 0,
+        }
+    }
+
+    /// Treat another color as transparent as well.
+    pub fn with_color(mut self, color: u32) -> Self {
+        self.colors.push(color & 0xFF_FF_FF);
+
+        self
+    }
+
+    /// Treat colors as transparent when none of their red, green and blue channels differ more
+    /// than the tolerance from a key color.
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+
+        self
+    }
+
+    /// Get the colors treated as transparent.
+    pub fn colors(&self) -> &[u32] {
+        &self.colors
+    }
+
+    /// Get the maximum difference of every color channel with a key color.
+    pub fn tolerance(&self) -> u8 {
+        self.tolerance
+    }
+
+    /// Whether the color is treated as transparent, the alpha channel is ignored.
+    pub fn matches(&self, color: u32) -> bool {
+        self.colors.iter().any(|&key| {
+            [16, 8, 0].iter().all(|&shift| {
+                let (a, b) = ((color >> shift) & 0xFF, (key >> shift) & 0xFF);
+
+                a.max(b) - a.min(b) <= u32::from(self.tolerance)
+            })
+        })
+    }
+
+    /// Color all pixels that are treated as transparent with the first key color.
+    fn apply(&self, pixels: &[u32]) -> Vec<u32> {
+        pixels
+            .iter()
+            .map(|&pixel| {
+                if self.matches(pixel) {
+                    self.colors[0]
+                } else {
+                    pixel & 0xFF_FF_FF
+                }
+            })
+            .collect()
+    }
+}
+
+/// Load pixels in the `0x00RRGGBB` format whose colors matching the key are transparent, and
+/// place them onto the heap with a set amount of rotations.
+///
+/// Returns an index that can be used in sprite components.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{load_keyed, testing, ColorKey, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // The background of a compressed image is slightly off
+/// let pixels = [0xFF_00_FF, 0xFD_02_FF, 0xFF_FF_FF];
+/// let sprite_ref = load_keyed(&pixels, 3, &ColorKey::new(0xFF_00_FF).with_tolerance(4), 1)?;
+/// world.create_entity().with(Sprite::new(sprite_ref)).build();
+///
+/// let buffer = testing::render(&mut world, 3, 1);
+/// assert_eq!(buffer.pixels()[0], 0);
+/// assert_eq!(buffer.pixels()[1], 0);
+/// assert_eq!(buffer.pixels()[2] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_keyed(
+    pixels: &[u32],
+    width: usize,
+    key: &ColorKey,
+    rotations: u16,
+) -> Result<SpriteRef, SpriteError> {
+    let image = SpriteImage::from_buffer(&key.apply(pixels), width, key.colors[0]);
+
+    crate::load_image(image, rotations, (0, 360), &RotationAlgorithm::default())
+}
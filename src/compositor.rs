@@ -0,0 +1,411 @@
+//! Merging the named buffers into the final buffer.
+
+use crate::{
+    dither,
+    profiler::{self, RenderPhase, RenderProfiler},
+    Palette, Palettes, PixelBuffer, PixelBuffers, RenderWarning,
+};
+use specs::{prelude::*, shrev::EventChannel};
+use std::collections::HashMap;
+use web_time::Instant;
+
+/// How the pixels of a [`CompositeLayer`] are combined with the pixels below them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Replace the pixels below.
+    #[default]
+    Normal,
+    /// Add the colors, channels that would overflow become `255`.
+    Additive,
+    /// Multiply the colors, making the result darker.
+    Multiply,
+    /// Multiply the inverse of the colors, making the result lighter.
+    Screen,
+}
+
+impl BlendMode {
+    /// Combine the color of the layer with the color below it.
+    pub(crate) fn blend(self, src: u32, dst: u32) -> u32 {
+        let channel = |shift: u32| {
+            let src = (src >> shift) & 0xFF;
+            let dst = (dst >> shift) & 0xFF;
+
+            let value = match self {
+                BlendMode::Normal => src,
+                BlendMode::Additive => (src + dst).min(0xFF),
+                BlendMode::Multiply => (src * dst + 0x7F) / 0xFF,
+                BlendMode::Screen => 0xFF - ((0xFF - src) * (0xFF - dst) + 0x7F) / 0xFF,
+            };
+
+            value << shift
+        };
+
+        match self {
+            BlendMode::Normal => src,
+            _ => 0xFF_00_00_00 | channel(16) | channel(8) | channel(0),
+        }
+    }
+}
+
+/// A buffer from the [`PixelBuffers`] resource restricting where a [`CompositeLayer`] is drawn.
+///
+/// The stencil buffer is placed over the final buffer without an offset, so it can be filled by
+/// a [`RenderSystem`](crate::RenderSystem) targeting it, e.g. with a flashlight cone or the
+/// silhouettes of portal sprites.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     CompositeLayer, CompositorConfig, CompositorSystem, PixelBuffer, PixelBuffers, Stencil,
+/// };
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(2, 1));
+///
+/// let mut world_layer = PixelBuffer::new(2, 1);
+/// world_layer.clear(0xFF_FF_FF);
+/// // Only the right pixel is lit
+/// let mut flashlight = PixelBuffer::new(2, 1);
+/// flashlight.pixels_mut()[1] = 0xFF_FF_00;
+///
+/// let mut buffers = PixelBuffers::new();
+/// buffers.insert("world", world_layer);
+/// buffers.insert("flashlight", flashlight);
+/// world.insert(buffers);
+///
+/// world.insert(CompositorConfig::new(vec![
+///     CompositeLayer::new("world").with_stencil(Stencil::new("flashlight"))
+/// ]));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(CompositorSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+///
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels(), &[0, 0xFF_FF_FF]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stencil {
+    /// Name of the buffer in the [`PixelBuffers`] resource.
+    pub name: String,
+    /// Pixels of the stencil buffer with this color are empty.
+    pub mask_color: u32,
+    /// Draw the layer where the stencil is empty instead of where it's filled.
+    pub inverted: bool,
+}
+
+impl Stencil {
+    /// Only draw the layer where the buffer with the name isn't black.
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            mask_color: 0,
+            inverted: false,
+        }
+    }
+
+    /// Only draw the layer where the buffer with the name is black, cutting holes in it.
+    pub fn inverted<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            inverted: true,
+            ..Self::new(name)
+        }
+    }
+
+    /// Treat the pixels with the color as empty instead of black ones.
+    pub fn with_mask_color(mut self, mask_color: u32) -> Self {
+        self.mask_color = mask_color;
+
+        self
+    }
+
+    /// Whether a layer can be drawn at the position of the final buffer.
+    fn allows(&self, buffer: &PixelBuffer, x: i32, y: i32) -> bool {
+        // Everything outside of the stencil buffer is empty
+        let filled = x < buffer.width as i32
+            && y < buffer.height as i32
+            && buffer.pixels[(y * buffer.width as i32 + x) as usize] & 0xFF_FF_FF
+                != self.mask_color & 0xFF_FF_FF;
+
+        filled != self.inverted
+    }
+}
+
+/// A buffer from the [`PixelBuffers`] resource drawn by the [`CompositorSystem`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeLayer {
+    /// Name of the buffer in the [`PixelBuffers`] resource.
+    pub name: String,
+    /// How the pixels are combined with the layers below.
+    pub blend: BlendMode,
+    /// Dithered opacity where `0` hides the layer and `255` shows all of its pixels.
+    pub opacity: u8,
+    /// Position of the top left corner of the layer in the final buffer.
+    pub offset: (i32, i32),
+    /// Pixels with this color are not drawn, `None` to draw all pixels.
+    pub mask_color: Option<u32>,
+    /// Buffer restricting where the pixels are drawn, `None` to draw everywhere.
+    pub stencil: Option<Stencil>,
+    /// Name of the palette in the [`Palettes`] resource the colors are mapped to, `None` to keep
+    /// the colors as they are.
+    pub palette: Option<String>,
+}
+
+impl CompositeLayer {
+    /// Draw the buffer with the name fully opaque over the layers below.
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            blend: BlendMode::Normal,
+            opacity: u8::MAX,
+            offset: (0, 0),
+            mask_color: None,
+            stencil: None,
+            palette: None,
+        }
+    }
+
+    /// Combine the pixels with the layers below with the blend mode.
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+
+        self
+    }
+
+    /// Only draw part of the pixels with an ordered dither pattern.
+    pub fn with_opacity(mut self, opacity: u8) -> Self {
+        self.opacity = opacity;
+
+        self
+    }
+
+    /// Move the layer in the final buffer.
+    pub fn with_offset(mut self, x: i32, y: i32) -> Self {
+        self.offset = (x, y);
+
+        self
+    }
+
+    /// Don't draw the pixels with the color so the layers below can be seen through them.
+    pub fn with_mask_color(mut self, mask_color: u32) -> Self {
+        self.mask_color = Some(mask_color);
+
+        self
+    }
+
+    /// Only draw the pixels where the stencil allows it.
+    pub fn with_stencil(mut self, stencil: Stencil) -> Self {
+        self.stencil = Some(stencil);
+
+        self
+    }
+
+    /// Map the colors to the nearest colors of the palette with the name before drawing them.
+    ///
+    /// Layers with a palette that's not in the [`Palettes`] resource keep their colors.
+    pub fn with_palette<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.palette = Some(name.into());
+
+        self
+    }
+
+    /// Draw the pixels of the layer buffer on the final buffer, restricted by the buffer of the
+    /// stencil and mapped to the palette when there are those.
+    pub(crate) fn composite(
+        &self,
+        layer: &PixelBuffer,
+        stencil: Option<&PixelBuffer>,
+        palette: Option<&Palette>,
+        buffer: &mut PixelBuffer,
+    ) {
+        if self.opacity == 0 {
+            return;
+        }
+
+        // Only look at the part of the layer that overlaps the buffer
+        let (offset_x, offset_y) = self.offset;
+        let start_x = (-offset_x).max(0);
+        let start_y = (-offset_y).max(0);
+        let end_x = (buffer.width as i32 - offset_x).min(layer.width as i32);
+        let end_y = (buffer.height as i32 - offset_y).min(layer.height as i32);
+
+        let mask_color = self.mask_color.map(|color| color & 0xFF_FF_FF);
+        let mut palette_cache = HashMap::new();
+        for y in start_y..end_y {
+            let buffer_y = y + offset_y;
+            for x in start_x..end_x {
+                let buffer_x = x + offset_x;
+
+                let src = layer.pixels[(y * layer.width as i32 + x) as usize];
+                if mask_color == Some(src & 0xFF_FF_FF)
+                    || !dither::is_visible(buffer_x, buffer_y, self.opacity)
+                {
+                    continue;
+                }
+                if let (Some(stencil), Some(stencil_buffer)) = (&self.stencil, stencil) {
+                    if !stencil.allows(stencil_buffer, buffer_x, buffer_y) {
+                        continue;
+                    }
+                }
+
+                let src = match palette {
+                    Some(palette) => *palette_cache
+                        .entry(src)
+                        .or_insert_with(|| palette.nearest(src)),
+                    None => src,
+                };
+
+                let dst = &mut buffer.pixels[(buffer_y * buffer.width as i32 + buffer_x) as usize];
+                *dst = self.blend.blend(src, *dst);
+            }
+        }
+    }
+}
+
+/// Resource with the layers the [`CompositorSystem`] merges into the [`PixelBuffer`].
+///
+/// The layers are drawn in order, so the last layer ends up on top.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     BlendMode, CompositeLayer, CompositorConfig, CompositorSystem, PixelBuffer, PixelBuffers,
+/// };
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(2, 1));
+///
+/// let mut world_layer = PixelBuffer::new(2, 1);
+/// world_layer.clear(0x20_20_20);
+/// let mut light_layer = PixelBuffer::new(1, 1);
+/// light_layer.clear(0x40_00_00);
+///
+/// let mut buffers = PixelBuffers::new();
+/// buffers.insert("world", world_layer);
+/// buffers.insert("lights", light_layer);
+/// world.insert(buffers);
+///
+/// world.insert(CompositorConfig::new(vec![
+///     CompositeLayer::new("world"),
+///     // Brighten the right pixel
+///     CompositeLayer::new("lights")
+///         .with_blend(BlendMode::Additive)
+///         .with_offset(1, 0),
+/// ]));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(CompositorSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+///
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[0] & 0xFF_FF_FF, 0x20_20_20);
+/// assert_eq!(buffer.pixels()[1] & 0xFF_FF_FF, 0x60_20_20);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompositorConfig {
+    /// Layers from bottom to top.
+    pub layers: Vec<CompositeLayer>,
+    /// Color the buffer is cleared with before drawing the layers, `None` to draw over what's
+    /// already in it.
+    pub clear_color: Option<u32>,
+}
+
+impl CompositorConfig {
+    /// Draw the layers from bottom to top on a black buffer.
+    pub fn new(layers: Vec<CompositeLayer>) -> Self {
+        Self {
+            layers,
+            clear_color: Some(0),
+        }
+    }
+
+    /// Get the layer with the name.
+    pub fn layer(&self, name: &str) -> Option<&CompositeLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Get the layer with the name so it can be changed, e.g. to fade it out.
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut CompositeLayer> {
+        self.layers.iter_mut().find(|layer| layer.name == name)
+    }
+}
+
+/// Specs system merging the buffers of the [`CompositorConfig`] layers into the [`PixelBuffer`].
+///
+/// Run it with `.with_thread_local()` after all systems drawing into the
+/// [`PixelBuffers`](crate::PixelBuffers). Layers without a buffer or without the buffer of their
+/// [`Stencil`] are skipped and reported as a [`RenderWarning::MissingBuffer`].
+#[derive(Debug, Default)]
+pub struct CompositorSystem;
+impl<'a> System<'a> for CompositorSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Read<'a, PixelBuffers>,
+        Read<'a, CompositorConfig>,
+        Option<Read<'a, Palettes>>,
+        Option<Write<'a, EventChannel<RenderWarning>>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(
+        &mut self,
+        (mut buffer, buffers, config, palettes, mut warnings, profiler): Self::SystemData,
+    ) {
+        let start = Instant::now();
+
+        if let Some(color) = config.clear_color {
+            buffer.clear(color);
+        }
+
+        let mut missing = |name: &String| {
+            if let Some(warnings) = warnings.as_mut() {
+                warnings.single_write(RenderWarning::MissingBuffer { name: name.clone() });
+            }
+        };
+        for layer in config.layers.iter() {
+            let layer_buffer = match buffers.get(&layer.name) {
+                Some(layer_buffer) => layer_buffer,
+                None => {
+                    missing(&layer.name);
+                    continue;
+                }
+            };
+
+            // Without its stencil the layer would be drawn where it shouldn't, so it's skipped
+            let stencil_buffer = match &layer.stencil {
+                Some(stencil) => match buffers.get(&stencil.name) {
+                    Some(stencil_buffer) => Some(stencil_buffer),
+                    None => {
+                        missing(&stencil.name);
+                        continue;
+                    }
+                },
+                None => None,
+            };
+
+            let palette = match (&layer.palette, palettes.as_ref()) {
+                (Some(name), Some(palettes)) => palettes.get(name),
+                _ => None,
+            };
+
+            layer.composite(layer_buffer, stencil_buffer, palette, &mut buffer);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "CompositorSystem", start);
+    }
+}
@@ -0,0 +1,258 @@
+//! Log lines shown over the frame, for targets where the standard output isn't visible.
+
+use crate::{
+    fog,
+    profiler::{self, RenderPhase, RenderProfiler},
+    Font, PixelBuffer,
+};
+use specs::prelude::*;
+use std::collections::VecDeque;
+use web_time::Instant;
+
+/// Resource with the lines shown by the [`ConsoleSystem`].
+///
+/// The oldest lines are forgotten when there are more lines than the capacity. It's inserted when
+/// the system is set up, so other systems can write to it without knowing about the console.
+///
+/// ```rust
+/// use specs_blit::ConsoleLog;
+///
+/// let mut log = ConsoleLog::new(2);
+/// log.push("loading");
+/// log.push("level 1\nplayer spawned");
+///
+/// assert_eq!(log.lines().collect::<Vec<_>>(), ["level 1", "player spawned"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleLog {
+    /// Lines from the oldest to the newest.
+    lines: VecDeque<String>,
+    /// Maximum amount of lines that are kept.
+    capacity: usize,
+    /// Amount of lines the view is moved up from the newest line.
+    scroll: usize,
+}
+
+impl Default for ConsoleLog {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl ConsoleLog {
+    /// Create an empty log keeping at most the amount of lines.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+            scroll: 0,
+        }
+    }
+
+    /// Add the text at the end, every newline starts a new line.
+    ///
+    /// When the view is scrolled up it keeps showing the same lines.
+    pub fn push<S>(&mut self, text: S)
+    where
+        S: Into<String>,
+    {
+        for line in text.into().lines() {
+            if self.lines.len() == self.capacity {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line.to_string());
+
+            if self.scroll > 0 {
+                self.scroll += 1;
+            }
+        }
+
+        self.scroll = self.scroll.min(self.lines.len());
+    }
+
+    /// Get the lines from the oldest to the newest.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// Remove all lines.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = 0;
+    }
+
+    /// Move the view up by the amount of lines, towards the older lines.
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.lines.len());
+    }
+
+    /// Move the view down by the amount of lines, towards the newest line.
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// Get the amount of lines the view is moved up from the newest line.
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Get at most the amount of lines ending at the view.
+    fn visible(&self, rows: usize) -> impl Iterator<Item = &str> {
+        let end = self.lines.len() - self.scroll;
+
+        self.lines
+            .range(end.saturating_sub(rows)..end)
+            .map(String::as_str)
+    }
+}
+
+/// Resource configuring the overlay drawn by the [`ConsoleSystem`].
+///
+/// It's hidden until it's toggled, e.g. when a key is pressed.
+#[derive(Debug, Clone)]
+pub struct Console {
+    /// Font the lines are drawn with.
+    font: Font,
+    /// Whether the overlay is drawn.
+    visible: bool,
+    /// Amount of lines that fit in the overlay.
+    rows: usize,
+    /// Color of the text.
+    color: u32,
+    /// How bright the frame behind the overlay is, from `0.0` for black to `1.0` for not dimmed.
+    brightness: f32,
+}
+
+impl Console {
+    /// Create a hidden overlay of 8 lines with white text drawn with the font.
+    pub fn new(font: Font) -> Self {
+        Self {
+            font,
+            visible: false,
+            rows: 8,
+            color: 0xFF_FF_FF,
+            brightness: 0.3,
+        }
+    }
+
+    /// Set the amount of lines that fit in the overlay, the height is cut off at the bottom of the
+    /// buffer.
+    pub fn with_rows(mut self, rows: usize) -> Self {
+        self.rows = rows;
+
+        self
+    }
+
+    /// Set the color of the text.
+    pub fn with_color(mut self, color: u32) -> Self {
+        self.color = color;
+
+        self
+    }
+
+    /// Set how bright the frame behind the overlay is, from `0.0` for black to `1.0` for not
+    /// dimmed.
+    pub fn with_brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness.clamp(0.0, 1.0);
+
+        self
+    }
+
+    /// Show the overlay when it's hidden and hide it when it's shown.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Show or hide the overlay.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Whether the overlay is drawn.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Dim the top of the buffer and draw the lines of the log ending at its view over it.
+    fn draw(&self, buffer: &mut PixelBuffer, log: &ConsoleLog) {
+        let glyph_height = self.font.glyph_size().1;
+        let width = buffer.width();
+        let height = (self.rows * glyph_height).min(buffer.height());
+        for pixel in &mut buffer.pixels_mut()[..width * height] {
+            *pixel = fog::darken(*pixel, self.brightness);
+        }
+
+        // The newest line is at the bottom of the overlay
+        let lines: Vec<&str> = log.visible(self.rows).collect();
+        let top = (self.rows - lines.len()) * glyph_height;
+        for (row, line) in lines.into_iter().enumerate() {
+            let y = (top + row * glyph_height) as i32;
+            self.font.draw(buffer, line, (0, y), Some(self.color));
+        }
+    }
+}
+
+/// Specs system drawing the [`ConsoleLog`] over the top of the buffer when the [`Console`]
+/// resource is visible.
+///
+/// Does nothing when the [`Console`] resource doesn't exist. It has to run after everything else
+/// is drawn, so add it last with `.with_thread_local()` or make it depend on all drawing systems.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{Console, ConsoleLog, ConsoleSystem, Font, PixelBuffer};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(4, 2));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(ConsoleSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// // A font with a single glyph of 1x1 pixels for the character 'a'
+/// let font = Font::load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF), (1, 1), 'a')?;
+/// world.insert(Console::new(font).with_rows(1).with_brightness(0.5));
+///
+/// world.write_resource::<PixelBuffer>().clear(0x88_88_88);
+/// world.write_resource::<ConsoleLog>().push("aa");
+/// world.write_resource::<Console>().toggle();
+/// dispatcher.dispatch(&world);
+///
+/// // The text is drawn on the dimmed first row, the second row is untouched
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// let colors: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+/// assert_eq!(
+///     colors,
+///     [
+///         0xFF_FF_FF, 0xFF_FF_FF, 0x44_44_44, 0x44_44_44,
+///         0x88_88_88, 0x88_88_88, 0x88_88_88, 0x88_88_88,
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ConsoleSystem;
+impl<'a> System<'a> for ConsoleSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Console>>,
+        Read<'a, ConsoleLog>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, console, log, profiler): Self::SystemData) {
+        let start = Instant::now();
+
+        if let Some(console) = console {
+            if console.is_visible() {
+                console.draw(&mut buffer, &log);
+            }
+        }
+
+        profiler::record(profiler, RenderPhase::PostProcess, "ConsoleSystem", start);
+    }
+}
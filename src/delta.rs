@@ -0,0 +1,274 @@
+//! Changes between consecutive frames, for streaming them to a remote viewer.
+
+use crate::{PixelBuffer, SnapshotError};
+use std::convert::TryInto;
+
+/// Bytes before the runs of a serialized delta, the width, height and amount of runs as
+/// little-endian `u32`s.
+const HEADER_SIZE: usize = 12;
+
+/// Bytes before the pixels of every run, the start and length as little-endian `u32`s.
+const RUN_HEADER_SIZE: usize = 8;
+
+/// Unchanged pixels that are sent anyway to join two runs, because that's cheaper than the header
+/// of a new run.
+const MAX_GAP: usize = RUN_HEADER_SIZE / 4;
+
+/// Pixels that changed between two frames, grouped in runs of consecutive pixels.
+///
+/// Apply it to the previous frame to get the next one. When the size of the frames differs the
+/// delta contains the whole next frame.
+///
+/// ```rust
+/// use specs_blit::{FrameDelta, PixelBuffer};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let previous = PixelBuffer::new(4, 4);
+/// let mut next = PixelBuffer::new(4, 4);
+/// next.pixels_mut()[5] = 0xFF_00_00;
+/// next.pixels_mut()[6] = 0x00_FF_00;
+///
+/// let delta = FrameDelta::between(&previous, &next);
+/// assert_eq!(delta.changed_pixels(), 2);
+///
+/// // Send it over the network and apply it on the other side
+/// let bytes = delta.to_bytes();
+/// let mut remote = PixelBuffer::new(4, 4);
+/// FrameDelta::from_bytes(&bytes)?.apply(&mut remote);
+/// assert_eq!(remote.pixels(), next.pixels());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameDelta {
+    /// Width and height in pixels of the next frame.
+    size: (usize, usize),
+    /// Index of the first pixel and the new colors of every run.
+    runs: Vec<(usize, Vec<u32>)>,
+}
+
+impl FrameDelta {
+    /// Find the pixels that changed from the previous frame to the next frame.
+    pub fn between(previous: &PixelBuffer, next: &PixelBuffer) -> Self {
+        let size = (next.width, next.height);
+        if (previous.width, previous.height) != size {
+            return Self::full(next);
+        }
+
+        let mut runs: Vec<(usize, Vec<u32>)> = Vec::new();
+        let mut run_end = 0;
+        for (index, (&old, &new)) in previous.pixels.iter().zip(&next.pixels).enumerate() {
+            if old == new {
+                continue;
+            }
+
+            match runs.last_mut() {
+                // Join the run when the gap is small, including the unchanged pixels in between
+                Some((_, pixels)) if index - run_end <= MAX_GAP => {
+                    pixels.extend_from_slice(&next.pixels[run_end..=index]);
+                }
+                _ => runs.push((index, vec![new])),
+            }
+            run_end = index + 1;
+        }
+
+        Self { size, runs }
+    }
+
+    /// Create a delta containing the whole frame, e.g. for the first frame that's sent.
+    pub fn full(frame: &PixelBuffer) -> Self {
+        let runs = if frame.pixels.is_empty() {
+            Vec::new()
+        } else {
+            vec![(0, frame.pixels.clone())]
+        };
+
+        Self {
+            size: (frame.width, frame.height),
+            runs,
+        }
+    }
+
+    /// Whether no pixels changed.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Amount of pixels that are sent, including the unchanged pixels joining runs.
+    pub fn changed_pixels(&self) -> usize {
+        self.runs.iter().map(|(_, pixels)| pixels.len()).sum()
+    }
+
+    /// Get the width and height in pixels of the next frame.
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Turn the previous frame into the next frame.
+    ///
+    /// The buffer is resized when its size is different, the pixels that aren't in the delta are
+    /// black then.
+    pub fn apply(&self, buffer: &mut PixelBuffer) {
+        if (buffer.width, buffer.height) != self.size {
+            *buffer = PixelBuffer::new(self.size.0, self.size.1);
+        }
+
+        for (start, pixels) in &self.runs {
+            buffer.pixels[*start..*start + pixels.len()].copy_from_slice(pixels);
+        }
+    }
+
+    /// Serialize the delta so it can be sent.
+    ///
+    /// The width, height and amount of runs come first, followed by the start, length and pixels
+    /// of every run, all as little-endian `u32`s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            HEADER_SIZE + self.runs.len() * RUN_HEADER_SIZE + self.changed_pixels() * 4,
+        );
+        let mut push = |value: u32| bytes.extend_from_slice(&value.to_le_bytes());
+
+        push(self.size.0 as u32);
+        push(self.size.1 as u32);
+        push(self.runs.len() as u32);
+        for (start, pixels) in &self.runs {
+            push(*start as u32);
+            push(pixels.len() as u32);
+            for &pixel in pixels {
+                push(pixel);
+            }
+        }
+
+        bytes
+    }
+
+    /// Largest amount of pixels in the frame of a delta read with [`FrameDelta::from_bytes`],
+    /// enough for 16384x16384 pixels.
+    pub const MAX_PIXELS: usize = 1 << 28;
+
+    /// Read a delta serialized with [`FrameDelta::to_bytes`].
+    ///
+    /// The bytes can come from anywhere, so frames larger than [`FrameDelta::MAX_PIXELS`] are
+    /// rejected instead of allocating the buffer for them when the delta is applied.
+    ///
+    /// ```rust
+    /// use specs_blit::{FrameDelta, SnapshotError};
+    ///
+    /// // A header of a frame of 65536x65536 pixels without any runs
+    /// let mut bytes = Vec::new();
+    /// for value in &[65536u32, 65536, 0] {
+    ///     bytes.extend_from_slice(&value.to_le_bytes());
+    /// }
+    ///
+    /// assert_eq!(
+    ///     FrameDelta::from_bytes(&bytes),
+    ///     Err(SnapshotError::FrameTooLarge {
+    ///         width: 65536,
+    ///         height: 65536
+    ///     })
+    /// );
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut values = bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()) as usize);
+        let truncated = || SnapshotError::Truncated(bytes.len());
+
+        if bytes.len() < HEADER_SIZE {
+            return Err(SnapshotError::MissingHeader(bytes.len()));
+        }
+        let size = (values.next().unwrap(), values.next().unwrap());
+        let amount = values.next().unwrap();
+        match size.0.checked_mul(size.1) {
+            Some(pixels) if pixels <= Self::MAX_PIXELS => (),
+            _ => {
+                return Err(SnapshotError::FrameTooLarge {
+                    width: size.0,
+                    height: size.1,
+                })
+            }
+        }
+
+        let mut runs = Vec::new();
+        for _ in 0..amount {
+            let start = values.next().ok_or_else(truncated)?;
+            let len = values.next().ok_or_else(truncated)?;
+
+            // A run outside of the frame can't be applied
+            if start.saturating_add(len) > size.0 * size.1 {
+                return Err(SnapshotError::RunOutOfBounds { start, len });
+            }
+
+            let pixels: Vec<u32> = values
+                .by_ref()
+                .take(len)
+                .map(|pixel| pixel as u32)
+                .collect();
+            if pixels.len() != len {
+                return Err(truncated());
+            }
+            runs.push((start, pixels));
+        }
+
+        Ok(Self { size, runs })
+    }
+}
+
+/// Remembers the last frame to create a [`FrameDelta`] for every next frame.
+///
+/// ```rust
+/// use specs_blit::{FrameDeltaEncoder, PixelBuffer};
+///
+/// let mut encoder = FrameDeltaEncoder::new();
+/// let mut frame = PixelBuffer::new(8, 8);
+///
+/// // The first frame is sent completely
+/// assert_eq!(encoder.encode(&frame).changed_pixels(), 64);
+///
+/// frame.pixels_mut()[0] = 0xFF_FF_FF;
+/// assert_eq!(encoder.encode(&frame).changed_pixels(), 1);
+/// assert!(encoder.encode(&frame).is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct FrameDeltaEncoder {
+    /// The frame that was encoded last.
+    previous: Option<PixelBuffer>,
+}
+
+impl FrameDeltaEncoder {
+    /// Create an encoder that sends the whole first frame.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the changes from the frame that was encoded last to the frame.
+    pub fn encode(&mut self, frame: &PixelBuffer) -> FrameDelta {
+        let delta = match &self.previous {
+            Some(previous) => FrameDelta::between(previous, frame),
+            None => FrameDelta::full(frame),
+        };
+
+        // Keep the allocation of the previous frame
+        match &mut self.previous {
+            Some(previous) if previous.pixels.len() == frame.pixels.len() => {
+                previous.pixels.copy_from_slice(&frame.pixels);
+                previous.width = frame.width;
+                previous.height = frame.height;
+            }
+            previous => {
+                *previous = Some(PixelBuffer {
+                    pixels: frame.pixels.clone(),
+                    width: frame.width,
+                    height: frame.height,
+                })
+            }
+        }
+
+        delta
+    }
+
+    /// Send the whole next frame, e.g. when a new viewer connects.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+}
@@ -0,0 +1,94 @@
+//! Hiding parts of sprites behind the columns of a pseudo-3D scene.
+
+use specs::prelude::*;
+
+/// Resource with the distance to the closest thing drawn in every column of the buffer.
+///
+/// Sprites with a [`Depth`] component are only drawn in the columns where they are closer than
+/// the distance in this buffer, so they can disappear behind walls drawn before them. It's filled
+/// by the raycaster of the `raycaster` feature but can also be filled manually.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Depth, DepthBuffer, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Depth>();
+///
+/// // Something is drawn at a distance of 2 in the first column
+/// let mut depth_buffer = DepthBuffer::new(2);
+/// depth_buffer.set(0, 2.0);
+/// world.insert(depth_buffer);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(Depth(5.0))
+///     .build();
+///
+/// // The sprite is hidden in the first column
+/// let frame = testing::render(&mut world, 2, 1);
+/// assert_eq!(frame.pixels()[0], 0);
+/// assert_eq!(frame.pixels()[1] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DepthBuffer {
+    /// Distance per column.
+    columns: Vec<f32>,
+}
+
+impl DepthBuffer {
+    /// Create a buffer for the amount of columns where nothing is drawn yet.
+    pub fn new(width: usize) -> Self {
+        Self {
+            columns: vec![f32::INFINITY; width],
+        }
+    }
+
+    /// Change the amount of columns and mark all of them as empty.
+    pub fn reset(&mut self, width: usize) {
+        self.columns.clear();
+        self.columns.resize(width, f32::INFINITY);
+    }
+
+    /// Amount of columns.
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Set the distance of the column, ignored when it's outside of the buffer.
+    pub fn set(&mut self, x: usize, depth: f32) {
+        if let Some(column) = self.columns.get_mut(x) {
+            *column = depth;
+        }
+    }
+
+    /// Distance of the column, infinite when nothing is drawn or it's outside of the buffer.
+    pub fn get(&self, x: usize) -> f32 {
+        self.columns.get(x).copied().unwrap_or(f32::INFINITY)
+    }
+
+    /// Whether something at the distance is visible in the column.
+    ///
+    /// Negative distances are behind the viewer and never visible.
+    pub fn is_visible(&self, x: i32, depth: f32) -> bool {
+        depth >= 0.0 && (x < 0 || depth < self.get(x as usize))
+    }
+}
+
+/// Distance of a sprite to the viewer, used to hide it behind the columns of the
+/// [`DepthBuffer`] resource.
+///
+/// Has no effect when the resource doesn't exist.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Depth(pub f32);
+
+impl Component for Depth {
+    type Storage = DenseVecStorage<Self>;
+}
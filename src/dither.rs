@@ -0,0 +1,12 @@
+//! Ordered dithering for faking transparency without blending.
+
+/// 4x4 Bayer matrix, every value is the order in which that pixel disappears.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Whether the pixel at the coordinates is drawn for the opacity, where `0` hides all pixels and
+/// `255` shows all pixels.
+pub(crate) fn is_visible(x: i32, y: i32, opacity: u8) -> bool {
+    let threshold = BAYER[(y & 3) as usize][(x & 3) as usize] * 16 + 8;
+
+    opacity > threshold
+}
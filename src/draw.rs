@@ -0,0 +1,419 @@
+//! Drawing directly on a buffer without any entities.
+
+use crate::{
+    effect::Effects, Camera, Font, Instances, MaskedBlitter, MultiSprite, PixelBuffer, Sprite,
+    SpriteEffects, SpriteRef, SPRITES,
+};
+
+/// A sprite with the components it's drawn with, for drawing sprites that aren't stored in specs.
+///
+/// The sprite is placed the same way the [`RenderSystem`](crate::RenderSystem) places an entity
+/// with these components, see [`PixelBuffer::draw_sprite_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct SpriteDraw<'a> {
+    /// Sprite that's drawn.
+    sprite: &'a Sprite,
+    /// Effects applied to all images of the sprite.
+    effects: Option<&'a SpriteEffects>,
+    /// Copies drawn instead of the sprite itself.
+    instances: Option<&'a Instances>,
+    /// Parts drawn around the sprite.
+    multi_sprite: Option<&'a MultiSprite>,
+    /// Whether the position is snapped to the pixel grid.
+    snapped: bool,
+}
+
+impl<'a> SpriteDraw<'a> {
+    /// Draw the sprite without effects, instances or parts.
+    pub fn new(sprite: &'a Sprite) -> Self {
+        Self {
+            sprite,
+            effects: None,
+            instances: None,
+            multi_sprite: None,
+            snapped: false,
+        }
+    }
+
+    /// Apply the effects, like the effect components do for the render system.
+    pub fn with_effects(mut self, effects: Option<&'a SpriteEffects>) -> Self {
+        self.effects = effects;
+
+        self
+    }
+
+    /// Draw the sprite once for every instance, like the [`Instances`] component does.
+    pub fn with_instances(mut self, instances: Option<&'a Instances>) -> Self {
+        self.instances = instances;
+
+        self
+    }
+
+    /// Draw the parts around the sprite, like the [`MultiSprite`] component does.
+    pub fn with_multi_sprite(mut self, multi_sprite: Option<&'a MultiSprite>) -> Self {
+        self.multi_sprite = multi_sprite;
+
+        self
+    }
+
+    /// Position the sprite with [`Camera::to_screen_snapped`], like the layers of the
+    /// [`PixelSnap`](crate::PixelSnap) resource.
+    pub fn with_snap(mut self, snapped: bool) -> Self {
+        self.snapped = snapped;
+
+        self
+    }
+
+    /// Get the sprite that's drawn.
+    pub fn sprite(&self) -> &'a Sprite {
+        self.sprite
+    }
+}
+
+/// Immediate drawing functions, useful for tools, loading screens and debug drawing outside of
+/// the dispatcher.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::{load, PixelBuffer};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut buffer = PixelBuffer::new(16, 16);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00; 4], 2, 0xFF_00_FF))?;
+/// buffer.blit_sprite(&sprite_ref, 1, 1, 0);
+///
+/// buffer.draw_line((0, 15), (15, 0), 0x00_FF_00);
+/// buffer.draw_rect((4, 4), (8, 8), 0x00_00_FF);
+///
+/// assert_eq!(buffer.pixels()[16 + 1] & 0xFF_FF_FF, 0xFF_00_00);
+/// assert_eq!(buffer.pixels()[15 * 16], 0x00_FF_00);
+/// assert_eq!(buffer.pixels()[4 * 16 + 8], 0x00_00_FF);
+/// assert_eq!(buffer.pixels()[6 * 16 + 6], 0);
+/// # Ok(())
+/// # }
+/// ```
+impl PixelBuffer {
+    /// Get the color of a single pixel, `None` when it's outside of the buffer.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(4, 4);
+    /// buffer.set_pixel(2, 3, 0xFF_00_00);
+    ///
+    /// assert_eq!(buffer.get_pixel(2, 3), Some(0xFF_00_00));
+    /// assert_eq!(buffer.get_pixel(4, 0), None);
+    /// assert_eq!(buffer.get_pixel(0, 4), None);
+    /// ```
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<u32> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    /// Set the color of a single pixel, ignored when it's outside of the buffer.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u32) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// Get the color of a single pixel without checking whether it's inside of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The position must be inside of the buffer.
+    pub unsafe fn get_pixel_unchecked(&self, x: usize, y: usize) -> u32 {
+        debug_assert!(x < self.width && y < self.height);
+
+        *self.pixels.get_unchecked(y * self.width + x)
+    }
+
+    /// Set the color of a single pixel without checking whether it's inside of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// The position must be inside of the buffer.
+    pub unsafe fn set_pixel_unchecked(&mut self, x: usize, y: usize, color: u32) {
+        debug_assert!(x < self.width && y < self.height);
+
+        *self.pixels.get_unchecked_mut(y * self.width + x) = color;
+    }
+
+    /// Index of the pixel in the array, `None` when it's outside of the buffer.
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Draw the sprite with its top left corner at the position, rotated by the degrees.
+    ///
+    /// The sprite is drawn the same way as the [`RenderSystem`](crate::RenderSystem) draws a
+    /// [`Sprite`] without any effects.
+    pub fn blit_sprite(&mut self, sprite: &SpriteRef, x: i32, y: i32, rot: i16) {
+        let mut sprite = Sprite::new(sprite.clone());
+        sprite.set_rot(rot);
+
+        let render_info = sprite.render_info(1.0);
+        let sprites = SPRITES.read().unwrap();
+        if let Some(image) = sprites.get(render_info.index) {
+            let pos = (x + render_info.offset.0, y + render_info.offset.1);
+            let size = render_info.size(image);
+            Effects::none().blit(&MaskedBlitter, image, self, pos, size);
+        }
+    }
+
+    /// Draw the sprite through the camera, with its position, rotation, scale, pivot and anchor.
+    ///
+    /// The sprite is drawn the same way as the [`RenderSystem`](crate::RenderSystem) draws it
+    /// without any effects. The [`Sprite`] doesn't need to be stored in specs, so it can be used
+    /// from the systems of another ECS or without one.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{load, Camera, PixelBuffer, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut buffer = PixelBuffer::new(8, 8);
+    /// let camera = Camera::new(2, 2);
+    ///
+    /// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?);
+    /// sprite.set_pos(5, 5);
+    /// buffer.draw_sprite(&sprite, &camera);
+    ///
+    /// assert_eq!(buffer.pixels()[3 * 8 + 3] & 0xFF_FF_FF, 0xFF_00_00);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_sprite(&mut self, sprite: &Sprite, camera: &Camera) {
+        self.draw_sprite_with(SpriteDraw::new(sprite), camera);
+    }
+
+    /// Draw the sprite through the camera with the components it's drawn with.
+    ///
+    /// The images are placed by the same code as the [`RenderSystem`](crate::RenderSystem)
+    /// uses, including the rotation of the camera, snapping, instances and the parts of a
+    /// multi-sprite. Nothing is drawn when the sprite isn't loaded anymore.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{load, Camera, Instances, PixelBuffer, Sprite, SpriteDraw};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut buffer = PixelBuffer::new(4, 1);
+    ///
+    /// let sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?);
+    /// let mut instances = Instances::new();
+    /// instances.push(1, 0);
+    /// instances.push(3, 0);
+    ///
+    /// let draw = SpriteDraw::new(&sprite).with_instances(Some(&instances));
+    /// buffer.draw_sprite_with(draw, &Camera::default());
+    ///
+    /// let drawn: Vec<bool> = buffer.pixels().iter().map(|pixel| *pixel != 0).collect();
+    /// assert_eq!(drawn, [false, true, false, true]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_sprite_with(&mut self, draw: SpriteDraw, camera: &Camera) {
+        // A turned camera rotates around the center of the buffer
+        let mut camera = camera.clone();
+        camera.set_viewport(self.width, self.height);
+
+        let no_effects = SpriteEffects::default();
+        let effects = Effects::from_sprite_effects(draw.effects.unwrap_or(&no_effects))
+            .with_mask_color(draw.sprite.mask_color());
+
+        let images = SPRITES.read().unwrap();
+        let placements = draw.sprite.placements(
+            (draw.instances, draw.multi_sprite),
+            &camera,
+            draw.snapped,
+            &images,
+        );
+        for placement in placements {
+            // The render system also skips the rest of a sprite when an image isn't loaded
+            let placement = match placement {
+                Ok(placement) => placement,
+                Err(_) => return,
+            };
+
+            effects.blit(
+                &MaskedBlitter,
+                &images[placement.index],
+                self,
+                placement.pos,
+                placement.size,
+            );
+        }
+    }
+
+    /// Draw all sprites through the camera sorted by their layer and z-order, sprites with the
+    /// same values are drawn in the order of the iterator.
+    ///
+    /// This is what the [`RenderSystem`](crate::RenderSystem) does without any effects, for
+    /// sprites queried from another ECS like `hecs` or `legion`:
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{load, Camera, PixelBuffer, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut buffer = PixelBuffer::new(1, 1);
+    ///
+    /// let mut front = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?);
+    /// front.set_z(1);
+    /// let back = Sprite::new(load(BlitBuffer::from_buffer(&[0x00_FF_00], 1, 0xFF_00_FF))?);
+    ///
+    /// let sprites = vec![front, back];
+    /// buffer.draw_sprites(&sprites, &Camera::default());
+    ///
+    /// assert_eq!(buffer.pixels()[0] & 0xFF_FF_FF, 0xFF_00_00);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_sprites<'a, I>(&mut self, sprites: I, camera: &Camera)
+    where
+        I: IntoIterator<Item = &'a Sprite>,
+    {
+        self.draw_sprites_with(sprites.into_iter().map(SpriteDraw::new), camera);
+    }
+
+    /// Draw all sprites with their components through the camera sorted by their layer and
+    /// z-order, sprites with the same values are drawn in the order of the iterator.
+    ///
+    /// See [`PixelBuffer::draw_sprite_with`] for how every sprite is drawn.
+    pub fn draw_sprites_with<'a, I>(&mut self, draws: I, camera: &Camera)
+    where
+        I: IntoIterator<Item = SpriteDraw<'a>>,
+    {
+        let mut draws: Vec<SpriteDraw> = draws.into_iter().collect();
+        draws.sort_by_key(|draw| (draw.sprite.layer(), draw.sprite.z()));
+
+        for draw in draws {
+            self.draw_sprite_with(draw, camera);
+        }
+    }
+
+    /// Draw a line of single pixels between both positions, both ends included.
+    ///
+    /// Only the pixels inside of the buffer are visited, so the ends can be far outside of it.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(4, 4);
+    /// buffer.draw_line((i32::MIN, 1), (i32::MAX, 1), 0xFF_FF_FF);
+    /// assert_eq!(buffer.pixels()[4..8], [0xFF_FF_FF; 4]);
+    /// ```
+    pub fn draw_line(&mut self, from: (i32, i32), to: (i32, i32), color: u32) {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let (dx, dy) = (
+            i64::from(to.0) - i64::from(from.0),
+            i64::from(to.1) - i64::from(from.1),
+        );
+
+        // Step one pixel at a time along the axis the line is longest on, the position on the
+        // other axis is rounded to the nearest pixel
+        let steep = dy.abs() > dx.abs();
+        let (major, minor, major_delta, minor_delta, major_size, minor_size) = if steep {
+            (from.1, from.0, dy, dx, height, width)
+        } else {
+            (from.0, from.1, dx, dy, width, height)
+        };
+        let (major, minor) = (i64::from(major), i64::from(minor));
+        let steps = major_delta.abs();
+        let minor_at = |step: i64| {
+            // Both factors can be close to 2^32, so the product doesn't fit in an i64
+            let offset = (2 * i128::from(step) * i128::from(minor_delta.abs()) + i128::from(steps))
+                / (2 * i128::from(steps.max(1)));
+            minor + minor_delta.signum() * offset as i64
+        };
+
+        // Clip the steps to the buffer on the major axis
+        let (first, last) = match major_delta.signum() {
+            -1 => (major - (major_size - 1), major),
+            _ => (-major, major_size - 1 - major),
+        };
+        let (first, last) = (first.max(0), last.min(steps));
+        if first > last {
+            return;
+        }
+
+        // The minor axis only moves in one direction, so the steps inside of the buffer on it
+        // are a single range that's found with binary searches
+        let before = |step: i64| match minor_delta.signum() {
+            -1 => minor_at(step) >= minor_size,
+            _ => minor_at(step) < 0,
+        };
+        let inside = |step: i64| (0..minor_size).contains(&minor_at(step));
+        let first = partition_point(first, last + 1, before);
+        let end = partition_point(first, last + 1, inside);
+
+        for step in first..end {
+            let major = (major + major_delta.signum() * step) as usize;
+            let minor = minor_at(step) as usize;
+            let (x, y) = if steep {
+                (minor, major)
+            } else {
+                (major, minor)
+            };
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    /// Draw the outline of a rectangle with the top left corner at the position.
+    pub fn draw_rect(&mut self, pos: (i32, i32), size: (usize, usize), color: u32) {
+        if size.0 == 0 || size.1 == 0 {
+            return;
+        }
+
+        let end = (pos.0 + size.0 as i32 - 1, pos.1 + size.1 as i32 - 1);
+        self.draw_line(pos, (end.0, pos.1), color);
+        self.draw_line((pos.0, end.1), end, color);
+        self.draw_line(pos, (pos.0, end.1), color);
+        self.draw_line((end.0, pos.1), end, color);
+    }
+
+    /// Fill a rectangle with the top left corner at the position.
+    pub fn fill_rect(&mut self, pos: (i32, i32), size: (usize, usize), color: u32) {
+        // Only fill the part that's inside of the buffer
+        let start = (pos.0.max(0) as usize, pos.1.max(0) as usize);
+        let end = (
+            (pos.0 + size.0 as i32).clamp(0, self.width as i32) as usize,
+            (pos.1 + size.1 as i32).clamp(0, self.height as i32) as usize,
+        );
+        if start.0 >= end.0 {
+            return;
+        }
+
+        for y in start.1..end.1 {
+            self.pixels[y * self.width + start.0..y * self.width + end.0].fill(color);
+        }
+    }
+
+    /// Draw the text with the font, see [`Font::draw`].
+    pub fn draw_text(&mut self, font: &Font, text: &str, pos: (i32, i32), color: Option<u32>) {
+        font.draw(self, text, pos, color);
+    }
+}
+
+/// Get the first value in the range for which the check fails, the check must pass for all values
+/// before it and fail for all values after it.
+fn partition_point<F>(mut start: i64, mut end: i64, check: F) -> i64
+where
+    F: Fn(i64) -> bool,
+{
+    while start < end {
+        let middle = start + (end - start) / 2;
+        if check(middle) {
+            start = middle + 1;
+        } else {
+            end = middle;
+        }
+    }
+
+    start
+}
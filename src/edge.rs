@@ -0,0 +1,165 @@
+//! How sprites behave at the edges of the buffer.
+
+use std::collections::HashMap;
+
+/// What happens with the part of a sprite that falls outside of the buffer.
+///
+/// Set it per sprite layer with the [`EdgeModes`] resource.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, EdgeMode, EdgeModes, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // A red, green and blue sprite with two pixels past the left edge
+/// let pixels = [0xFF_00_00, 0x00_FF_00, 0x00_00_FF];
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&pixels, 3, 0xFF_00_FF))?);
+/// sprite.set_pos(-2, 0);
+/// world.create_entity().with(sprite).build();
+///
+/// let mut edge_modes = EdgeModes::new();
+/// edge_modes.set(0, EdgeMode::Mirror);
+/// world.insert(edge_modes);
+///
+/// // The part past the edge is reflected back
+/// let buffer = testing::render(&mut world, 4, 1);
+/// let colors: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+/// assert_eq!(colors, [0x00_FF_00, 0xFF_00_00, 0, 0]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum EdgeMode {
+    /// The part outside of the buffer isn't drawn.
+    #[default]
+    Clip,
+    /// The buffer wraps around, the part leaving one edge is drawn at the opposite edge.
+    ///
+    /// Positions are wrapped as well, so sprites anywhere in the world are drawn on the buffer.
+    Wrap,
+    /// The edges are mirrors, the part leaving an edge is drawn reflected back into the buffer.
+    Mirror,
+}
+
+impl EdgeMode {
+    /// Positions to draw a sprite of the size at on a buffer of the size, with whether the
+    /// sprite is mirrored horizontally and vertically at that position.
+    pub(crate) fn positions(
+        self,
+        pos: (i32, i32),
+        size: (usize, usize),
+        buffer_size: (i32, i32),
+    ) -> impl Iterator<Item = ((i32, i32), (bool, bool))> {
+        let (width, height) = buffer_size;
+        let (size_x, size_y) = (size.0 as i32, size.1 as i32);
+
+        // Positions on a single axis with whether the sprite is mirrored on it
+        let axes = match self {
+            EdgeMode::Wrap if width > 0 && height > 0 => {
+                let pos = (pos.0.rem_euclid(width), pos.1.rem_euclid(height));
+
+                // A sprite crossing the right or bottom edge also enters at the opposite edge
+                [
+                    [
+                        Some((pos.0, false)),
+                        Some((pos.0 - width, false)).filter(|_| pos.0 + size_x > width),
+                        None,
+                    ],
+                    [
+                        Some((pos.1, false)),
+                        Some((pos.1 - height, false)).filter(|_| pos.1 + size_y > height),
+                        None,
+                    ],
+                ]
+            }
+            EdgeMode::Mirror => {
+                // Reflect the sprite around the edges it crosses
+                let mirrored = |pos: i32, size: i32, length: i32| {
+                    [
+                        Some((pos, false)),
+                        Some((-pos - size, true)).filter(|_| pos < 0),
+                        Some((2 * length - pos - size, true)).filter(|_| pos + size > length),
+                    ]
+                };
+
+                [
+                    mirrored(pos.0, size_x, width),
+                    mirrored(pos.1, size_y, height),
+                ]
+            }
+            _ => [
+                [Some((pos.0, false)), None, None],
+                [Some((pos.1, false)), None, None],
+            ],
+        };
+
+        let [xs, ys] = axes;
+        IntoIterator::into_iter(xs)
+            .flatten()
+            .flat_map(move |(x, flip_x)| {
+                IntoIterator::into_iter(ys)
+                    .flatten()
+                    .map(move |(y, flip_y)| ((x, y), (flip_x, flip_y)))
+            })
+    }
+}
+
+/// Resource with the [`EdgeMode`] of every sprite layer, used by the
+/// [`RenderSystem`](crate::RenderSystem).
+///
+/// Layers without a mode are clipped.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, EdgeMode, EdgeModes, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // An asteroid leaving the bottom right corner of the screen
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?);
+/// sprite.set_pos(3, 3);
+/// world.create_entity().with(sprite).build();
+///
+/// let mut edge_modes = EdgeModes::new();
+/// edge_modes.set(0, EdgeMode::Wrap);
+/// world.insert(edge_modes);
+///
+/// // The sprite is drawn in all four corners
+/// let buffer = testing::render(&mut world, 4, 4);
+/// for &index in &[0, 3, 12, 15] {
+///     assert_eq!(buffer.pixels()[index] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// }
+/// assert_eq!(buffer.pixels()[5], 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EdgeModes {
+    /// Modes of the layers that aren't clipped.
+    layers: HashMap<u8, EdgeMode>,
+}
+
+impl EdgeModes {
+    /// Create the resource with all layers clipped.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set what happens with the parts of the sprites on the layer outside of the buffer.
+    pub fn set(&mut self, layer: u8, mode: EdgeMode) {
+        self.layers.insert(layer, mode);
+    }
+
+    /// Get what happens with the parts of the sprites on the layer outside of the buffer.
+    pub fn get(&self, layer: u8) -> EdgeMode {
+        self.layers.get(&layer).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,1102 @@
+//! Components changing how the pixels of a sprite are drawn.
+
+use crate::{
+    dither,
+    sprite_image::Composite,
+    time::{self, PausedLayers},
+    Blitter, Depth, DepthBuffer, PixelBuffer, Sprite, SpriteEffects, SpriteImage, Time,
+};
+use specs::prelude::*;
+use std::{fmt, time::Duration};
+
+/// Function called for every pixel that's drawn of the sprite of the same entity.
+///
+/// It receives the color of the sprite pixel, the color of the buffer pixel it's drawn on and the
+/// coordinates in the buffer, the returned color is written to the buffer. The
+/// [`Blitter`](crate::Blitter) of the render system is not used for these sprites.
+///
+/// This is a lot slower than the normal drawing so it's best suited for one-off effects.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, PixelFn, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<PixelFn>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     // Only draw the pixels on the even columns
+///     .with(PixelFn::new(|src, dst, x, _y| if x % 2 == 0 { src } else { dst }))
+///     .build();
+///
+/// let frame = testing::render(&mut world, 2, 1);
+/// assert_eq!(frame.pixels()[0] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// assert_eq!(frame.pixels()[1], 0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct PixelFn(Box<dyn Fn(u32, u32, i32, i32) -> u32 + Send + Sync>);
+
+impl PixelFn {
+    /// Wrap a function taking the sprite color, the buffer color and the x & y coordinates.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(u32, u32, i32, i32) -> u32 + Send + Sync + 'static,
+    {
+        Self(Box::new(f))
+    }
+}
+
+impl Component for PixelFn {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl fmt::Debug for PixelFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PixelFn").finish()
+    }
+}
+
+/// Change the colors of a sprite when it's drawn by multiplying them with a matrix.
+///
+/// The different adjustments can be chained with [`ColorAdjust::then`].
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, ColorAdjust, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<ColorAdjust>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     // Completely desaturate the sprite and make it darker
+///     .with(ColorAdjust::saturation(0.0).then(ColorAdjust::brightness(0.5)))
+///     .build();
+///
+/// let frame = testing::render(&mut world, 1, 1);
+/// assert_eq!(frame.pixels()[0] & 0xFF_FF_FF, 0x1B_1B_1B);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ColorAdjust {
+    /// Rows of the matrix that calculate the red, green & blue channels respectively.
+    matrix: [[f32; 3]; 3],
+    /// Added to the red, green & blue channels after the multiplication, in the range `0..255`.
+    offset: [f32; 3],
+}
+
+impl ColorAdjust {
+    /// Weights of the red, green & blue channels for calculating the luminance of a color.
+    const LUMINANCE: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+    /// Adjustment that keeps all colors the same.
+    pub fn identity() -> Self {
+        Self::from_matrix([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Use a custom matrix, where every row calculates a channel from the red, green & blue values
+    /// of the original color.
+    pub fn from_matrix(matrix: [[f32; 3]; 3]) -> Self {
+        Self {
+            matrix,
+            offset: [0.0; 3],
+        }
+    }
+
+    /// Multiply all channels, `1.0` keeps the colors the same and `0.0` makes them black.
+    pub fn brightness(factor: f32) -> Self {
+        Self::from_matrix([[factor, 0.0, 0.0], [0.0, factor, 0.0], [0.0, 0.0, factor]])
+    }
+
+    /// Scale the distance of all channels to the middle gray, `1.0` keeps the colors the same and
+    /// `0.0` makes them gray.
+    pub fn contrast(factor: f32) -> Self {
+        Self {
+            offset: [(1.0 - factor) * 127.5; 3],
+            ..Self::brightness(factor)
+        }
+    }
+
+    /// Scale the saturation, `1.0` keeps the colors the same and `0.0` turns them into grayscale.
+    pub fn saturation(factor: f32) -> Self {
+        let mut matrix = [[0.0; 3]; 3];
+        for (channel, row) in matrix.iter_mut().enumerate() {
+            for (weight_channel, weight) in row.iter_mut().enumerate() {
+                *weight = Self::LUMINANCE[weight_channel] * (1.0 - factor);
+            }
+            row[channel] += factor;
+        }
+
+        Self::from_matrix(matrix)
+    }
+
+    /// Apply another adjustment after this one.
+    pub fn then(self, next: Self) -> Self {
+        let mut matrix = [[0.0; 3]; 3];
+        let mut offset = next.offset;
+        for ((row, next_row), offset) in matrix.iter_mut().zip(next.matrix.iter()).zip(&mut offset)
+        {
+            for (column, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|i| next_row[i] * self.matrix[i][column]).sum();
+            }
+            *offset += (0..3).map(|i| next_row[i] * self.offset[i]).sum::<f32>();
+        }
+
+        Self { matrix, offset }
+    }
+
+    /// Convert the matrix to fixed point integers with 8 bits for the fraction so it can be applied
+    /// quickly to every pixel.
+    fn to_fixed(self) -> FixedColorAdjust {
+        let fixed = |value: f32| (value * 256.0).round() as i32;
+
+        FixedColorAdjust {
+            matrix: [
+                [
+                    fixed(self.matrix[0][0]),
+                    fixed(self.matrix[0][1]),
+                    fixed(self.matrix[0][2]),
+                ],
+                [
+                    fixed(self.matrix[1][0]),
+                    fixed(self.matrix[1][1]),
+                    fixed(self.matrix[1][2]),
+                ],
+                [
+                    fixed(self.matrix[2][0]),
+                    fixed(self.matrix[2][1]),
+                    fixed(self.matrix[2][2]),
+                ],
+            ],
+            offset: [
+                fixed(self.offset[0]),
+                fixed(self.offset[1]),
+                fixed(self.offset[2]),
+            ],
+        }
+    }
+}
+
+impl Default for ColorAdjust {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Component for ColorAdjust {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A [`ColorAdjust`] in fixed point representation.
+#[derive(Clone)]
+struct FixedColorAdjust {
+    matrix: [[i32; 3]; 3],
+    offset: [i32; 3],
+}
+
+impl FixedColorAdjust {
+    /// Adjust a single color.
+    fn apply(&self, color: u32) -> u32 {
+        let channels = [
+            ((color >> 16) & 0xFF) as i32,
+            ((color >> 8) & 0xFF) as i32,
+            (color & 0xFF) as i32,
+        ];
+
+        let mut result = color & 0xFF_00_00_00;
+        for (row, (weights, offset)) in self.matrix.iter().zip(self.offset.iter()).enumerate() {
+            let value = weights[0] * channels[0]
+                + weights[1] * channels[1]
+                + weights[2] * channels[2]
+                + offset;
+
+            result |= (((value + 0x80) >> 8).clamp(0, 0xFF) as u32) << (16 - row * 8);
+        }
+
+        result
+    }
+}
+
+/// Draw the sprite as a silhouette of a single color for a couple of frames after it's
+/// triggered.
+///
+/// The [`FlashSystem`] counts down the frames.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Flash, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Flash>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// let entity = world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(Flash::new(0xFF_FF_FF, 3))
+///     .build();
+///
+/// // Flash white when the entity gets hit
+/// world.write_storage::<Flash>().get_mut(entity).unwrap().trigger();
+///
+/// let frame = testing::render(&mut world, 1, 1);
+/// assert_eq!(frame.pixels()[0] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Flash {
+    /// Color of the silhouette.
+    pub color: u32,
+    /// Amount of frames the flash lasts after it's triggered.
+    pub duration: u32,
+    /// Amount of frames the silhouette will still be drawn.
+    frames_left: u32,
+}
+
+impl Flash {
+    /// Create a flash that's not triggered yet.
+    pub fn new(color: u32, duration: u32) -> Self {
+        Self {
+            color,
+            duration,
+            frames_left: 0,
+        }
+    }
+
+    /// Start drawing the silhouette, restarting it when it's already flashing.
+    pub fn trigger(&mut self) {
+        self.frames_left = self.duration;
+    }
+
+    /// Whether the silhouette is currently drawn.
+    pub fn is_active(&self) -> bool {
+        self.frames_left > 0
+    }
+}
+
+impl Component for Flash {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system counting down the frames of every triggered [`Flash`].
+///
+/// Every time this system runs a frame is subtracted.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{FlashSystem, RenderSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(FlashSystem, "flash", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct FlashSystem;
+impl<'a> System<'a> for FlashSystem {
+    type SystemData = WriteStorage<'a, Flash>;
+
+    fn run(&mut self, mut flashes: Self::SystemData) {
+        for flash in (&mut flashes).join() {
+            flash.frames_left = flash.frames_left.saturating_sub(1);
+        }
+    }
+}
+
+/// Repeatedly show and hide the sprite.
+///
+/// The [`BlinkSystem`] advances the timers with the [`Time`](crate::Time) resource, every blink
+/// starts visible when the component is added.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Blink, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Blink>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     // Invincibility frames
+///     .with(Blink::new(100, 50))
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Blink {
+    /// Milliseconds the sprite is shown every cycle.
+    pub on_ms: u32,
+    /// Milliseconds the sprite is hidden every cycle.
+    pub off_ms: u32,
+    /// Time passed since the component was added.
+    elapsed: Duration,
+}
+
+impl Blink {
+    /// Create a blink that starts with showing the sprite.
+    pub fn new(on_ms: u32, off_ms: u32) -> Self {
+        Self {
+            on_ms,
+            off_ms,
+            elapsed: Duration::default(),
+        }
+    }
+
+    /// Whether the sprite is shown at this moment.
+    pub fn is_visible(&self) -> bool {
+        let cycle = u128::from(self.on_ms) + u128::from(self.off_ms);
+        if cycle == 0 {
+            return true;
+        }
+
+        self.elapsed.as_millis() % cycle < u128::from(self.on_ms)
+    }
+}
+
+impl Component for Blink {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system advancing the timers of every [`Blink`] with the [`Time`](crate::Time) resource.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{BlinkSystem, RenderSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(BlinkSystem, "blink", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct BlinkSystem;
+impl<'a> System<'a> for BlinkSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        Option<Read<'a, PausedLayers>>,
+        ReadStorage<'a, Sprite>,
+        WriteStorage<'a, Blink>,
+    );
+
+    fn run(&mut self, (time, paused_layers, sprites, mut blinks): Self::SystemData) {
+        for (blink, sprite) in (&mut blinks, sprites.maybe()).join() {
+            blink.elapsed += time::layer_delta(&time, paused_layers.as_deref(), sprite);
+        }
+    }
+}
+
+/// Delete the entity after a set time.
+///
+/// The [`LifetimeSystem`] counts down the time with the [`Time`](crate::Time) resource, the last
+/// part of the lifetime the sprite can be faded out by dithering.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Lifetime, Sprite};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Lifetime>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     // Show an explosion for a second, fading out the last half
+///     .with(Lifetime::new(Duration::from_secs(1)).with_fade_out(Duration::from_millis(500)))
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lifetime {
+    /// Time left before the entity is deleted.
+    remaining: Duration,
+    /// Time before the end where the sprite starts fading out.
+    fade_out: Duration,
+}
+
+impl Lifetime {
+    /// Delete the entity after the duration.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            remaining: duration,
+            fade_out: Duration::default(),
+        }
+    }
+
+    /// Fade out the sprite with dithering during the last part of the lifetime.
+    pub fn with_fade_out(mut self, fade_out: Duration) -> Self {
+        self.fade_out = fade_out;
+
+        self
+    }
+
+    /// Time left before the entity is deleted.
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /// Opacity of the sprite where `255` is fully visible.
+    pub(crate) fn opacity(&self) -> u8 {
+        if self.remaining >= self.fade_out {
+            return u8::MAX;
+        }
+
+        (self.remaining.as_secs_f32() / self.fade_out.as_secs_f32() * 255.0) as u8
+    }
+}
+
+impl Component for Lifetime {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system deleting every entity whose [`Lifetime`] ran out.
+///
+/// The entities are deleted the next time `world.maintain()` is called.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{LifetimeSystem, RenderSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(LifetimeSystem, "lifetime", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct LifetimeSystem;
+impl<'a> System<'a> for LifetimeSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Option<Read<'a, PausedLayers>>,
+        ReadStorage<'a, Sprite>,
+        WriteStorage<'a, Lifetime>,
+    );
+
+    fn run(&mut self, (entities, time, paused_layers, sprites, mut lifetimes): Self::SystemData) {
+        for (entity, lifetime, sprite) in (&entities, &mut lifetimes, sprites.maybe()).join() {
+            let delta = time::layer_delta(&time, paused_layers.as_deref(), sprite);
+            lifetime.remaining = lifetime.remaining.saturating_sub(delta);
+
+            if lifetime.remaining == Duration::default() {
+                // Deleting can only fail when the entity is already deleted
+                let _ = entities.delete(entity);
+            }
+        }
+    }
+}
+
+/// Only draw the pixels of the sprite that match a repeating pattern, a cheap retro way to make
+/// it look transparent.
+///
+/// The pattern is aligned with the buffer so it doesn't crawl when the sprite moves.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Sprite, Stipple};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Stipple>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00; 4], 2, 0xFF_00_FF))?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     // A ghost
+///     .with(Stipple::checkerboard())
+///     .build();
+///
+/// let frame = testing::render(&mut world, 2, 2);
+/// assert_eq!(frame.pixels()[0] & 0xFF_FF_FF, 0xFF_00_00);
+/// assert_eq!(frame.pixels()[1], 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stipple {
+    /// Pattern of 4x4 pixels, bit `y * 4 + x` is set when the pixel is drawn.
+    pub pattern: u16,
+}
+
+impl Stipple {
+    /// Draw the pixels where the bits of the 4x4 pattern are set, bit `y * 4 + x` is the pixel
+    /// at (x, y).
+    pub fn new(pattern: u16) -> Self {
+        Self { pattern }
+    }
+
+    /// Draw every other pixel.
+    pub fn checkerboard() -> Self {
+        Self::new(0b1010_0101_1010_0101)
+    }
+
+    /// Draw every other row.
+    pub fn scanlines() -> Self {
+        Self::new(0b0000_1111_0000_1111)
+    }
+
+    /// Whether the pixel at the buffer position is drawn.
+    pub fn is_visible(&self, x: i32, y: i32) -> bool {
+        let bit = (y & 3) * 4 + (x & 3);
+
+        self.pattern & (1 << bit) != 0
+    }
+}
+
+impl Component for Stipple {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Skip an increasing part of the pixels of the sprite, picked randomly but the same every frame,
+/// to make it disintegrate.
+///
+/// The pattern moves with the sprite, the same seed always skips the same pixels.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Dissolve, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Dissolve>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00; 64], 8, 0xFF_00_FF))?;
+/// let entity = world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(Dissolve::new(42))
+///     .build();
+///
+/// // Disintegrate half of the sprite
+/// world.write_storage::<Dissolve>().get_mut(entity).unwrap().progress = 0.5;
+///
+/// let frame = testing::render(&mut world, 8, 8);
+/// let drawn = frame.pixels().iter().filter(|&&pixel| pixel != 0).count();
+/// assert!(drawn > 16 && drawn < 48);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dissolve {
+    /// Part of the pixels that are skipped, from `0.0` for none to `1.0` for all.
+    pub progress: f32,
+    /// Determines which pixels are skipped first.
+    pub seed: u64,
+}
+
+impl Dissolve {
+    /// Create a dissolve effect that hasn't started yet.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            progress: 0.0,
+            seed,
+        }
+    }
+
+    /// Whether the pixel at the position relative to the sprite is still drawn.
+    pub fn is_visible(&self, x: i32, y: i32) -> bool {
+        let threshold = (self.progress.clamp(0.0, 1.0) * 65536.0) as u64;
+
+        // Hash the position with the seed, based on SplitMix64
+        let mut hash = self.seed ^ ((x as u32 as u64) << 32 | y as u32 as u64);
+        hash = hash.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+
+        hash & 0xFFFF >= threshold
+    }
+}
+
+impl Component for Dissolve {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Direction in which the rows or columns of a [`Wave`] are moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaveDirection {
+    /// Move every row left and right, like a flag or heat shimmer.
+    #[default]
+    Horizontal,
+    /// Move every column up and down, like something underwater.
+    Vertical,
+}
+
+/// Move every row or column of the sprite by a sine wave that changes over time.
+///
+/// The [`WaveSystem`] advances the waves with the [`Time`](crate::Time) resource.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Sprite, Wave, WaveDirection};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Wave>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00; 8 * 8], 8, 0xFF_00_FF))?;
+/// let mut sprite = Sprite::new(sprite_ref);
+/// sprite.set_pos(2, 2);
+/// world
+///     .create_entity()
+///     .with(sprite)
+///     // Move the rows up to 2 pixels left and right
+///     .with(Wave::new(2.0, 8.0, 1.0))
+///     .build();
+///
+/// // The third row is moved 2 pixels to the right
+/// let frame = testing::render(&mut world, 12, 12);
+/// assert_eq!(frame.pixels()[4 * 12 + 3], 0);
+/// assert_eq!(frame.pixels()[4 * 12 + 11] & 0xFF_FF_FF, 0xFF_00_00);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Wave {
+    /// Maximum distance in pixels a row or column is moved.
+    pub amplitude: f32,
+    /// Length in pixels of a single wave over the sprite.
+    pub wavelength: f32,
+    /// Amount of waves passing every second.
+    pub speed: f32,
+    /// Whether rows or columns are moved.
+    pub direction: WaveDirection,
+    /// Time passed since the component was added.
+    elapsed: Duration,
+}
+
+impl Wave {
+    /// Create a wave moving the rows of the sprite horizontally.
+    pub fn new(amplitude: f32, wavelength: f32, speed: f32) -> Self {
+        Self {
+            amplitude,
+            wavelength,
+            speed,
+            direction: WaveDirection::Horizontal,
+            elapsed: Duration::default(),
+        }
+    }
+
+    /// Set whether rows or columns are moved.
+    pub fn with_direction(mut self, direction: WaveDirection) -> Self {
+        self.direction = direction;
+
+        self
+    }
+
+    /// Distance in pixels the row or column at the position relative to the sprite is moved at
+    /// this moment.
+    pub fn offset(&self, line: i32) -> i32 {
+        if self.wavelength == 0.0 {
+            return 0;
+        }
+
+        let phase = line as f32 / self.wavelength + self.elapsed.as_secs_f32() * self.speed;
+
+        (self.amplitude * (phase * std::f32::consts::TAU).sin()).round() as i32
+    }
+}
+
+impl Component for Wave {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system advancing the time of every [`Wave`] with the [`Time`](crate::Time) resource.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{RenderSystem, WaveSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(WaveSystem, "wave", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct WaveSystem;
+impl<'a> System<'a> for WaveSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        Option<Read<'a, PausedLayers>>,
+        ReadStorage<'a, Sprite>,
+        WriteStorage<'a, Wave>,
+    );
+
+    fn run(&mut self, (time, paused_layers, sprites, mut waves): Self::SystemData) {
+        for (wave, sprite) in (&mut waves, sprites.maybe()).join() {
+            wave.elapsed += time::layer_delta(&time, paused_layers.as_deref(), sprite);
+        }
+    }
+}
+
+/// Storages of all effect components.
+pub(crate) type EffectStorages<'a> = (
+    ReadStorage<'a, PixelFn>,
+    ReadStorage<'a, ColorAdjust>,
+    ReadStorage<'a, Flash>,
+    ReadStorage<'a, Blink>,
+    ReadStorage<'a, Lifetime>,
+    ReadStorage<'a, Stipple>,
+    ReadStorage<'a, Dissolve>,
+    ReadStorage<'a, Wave>,
+    ReadStorage<'a, Depth>,
+);
+
+/// All effect components of a single entity.
+#[derive(Clone)]
+pub(crate) struct Effects<'e> {
+    pixel_fn: Option<&'e PixelFn>,
+    color_adjust: Option<FixedColorAdjust>,
+    /// Color of the silhouette when flashing.
+    flash: Option<u32>,
+    /// Whether the sprite shouldn't be drawn at all.
+    hidden: bool,
+    /// Dithered opacity where `255` is fully visible.
+    opacity: u8,
+    /// Extra color that's treated as transparent.
+    mask_color: Option<u32>,
+    /// Pattern of the pixels that are drawn.
+    stipple: Option<Stipple>,
+    /// Which pixels relative to the sprite are skipped.
+    dissolve: Option<Dissolve>,
+    /// Displacement of the rows or columns.
+    wave: Option<&'e Wave>,
+    /// Distance to the viewer and the distances of the columns it's hidden behind.
+    depth: Option<(f32, &'e DepthBuffer)>,
+    /// Whether the image is mirrored horizontally and vertically.
+    flip: (bool, bool),
+}
+
+impl<'e> Effects<'e> {
+    /// Get the effect components of the entity.
+    pub(crate) fn get(
+        storages: &'e EffectStorages<'_>,
+        depth_buffer: Option<&'e DepthBuffer>,
+        entity: Entity,
+    ) -> Self {
+        let (
+            pixel_fns,
+            color_adjusts,
+            flashes,
+            blinks,
+            lifetimes,
+            stipples,
+            dissolves,
+            waves,
+            depths,
+        ) = storages;
+
+        Self {
+            pixel_fn: pixel_fns.get(entity),
+            color_adjust: color_adjusts.get(entity).map(|adjust| adjust.to_fixed()),
+            flash: flashes
+                .get(entity)
+                .filter(|flash| flash.is_active())
+                .map(|flash| flash.color | 0xFF_00_00_00),
+            hidden: blinks.get(entity).is_some_and(|blink| !blink.is_visible()),
+            opacity: lifetimes.get(entity).map_or(u8::MAX, Lifetime::opacity),
+            mask_color: None,
+            stipple: stipples.get(entity).copied(),
+            dissolve: dissolves
+                .get(entity)
+                .filter(|dissolve| dissolve.progress > 0.0)
+                .copied(),
+            wave: waves.get(entity),
+            depth: depth_buffer
+                .and_then(|depth_buffer| Some((depths.get(entity)?.0, depth_buffer))),
+            flip: (false, false),
+        }
+    }
+
+    /// No effects at all, the image is drawn as is.
+    pub(crate) fn none() -> Self {
+        Self {
+            pixel_fn: None,
+            color_adjust: None,
+            flash: None,
+            hidden: false,
+            opacity: u8::MAX,
+            mask_color: None,
+            stipple: None,
+            dissolve: None,
+            wave: None,
+            depth: None,
+            flip: (false, false),
+        }
+    }
+
+    /// The effects of a sprite that isn't stored on an entity.
+    pub(crate) fn from_sprite_effects(effects: &'e SpriteEffects) -> Self {
+        Self {
+            color_adjust: effects.color_adjust.map(ColorAdjust::to_fixed),
+            flash: effects.flash.map(|color| color | 0xFF_00_00_00),
+            opacity: effects.opacity,
+            stipple: effects.stipple,
+            dissolve: effects.dissolve.filter(|dissolve| dissolve.progress > 0.0),
+            wave: effects.wave.as_ref(),
+            flip: effects.flip,
+            ..Self::none()
+        }
+    }
+
+    /// Also treat pixels with this color as transparent.
+    pub(crate) fn with_mask_color(mut self, mask_color: Option<u32>) -> Self {
+        self.mask_color = mask_color.map(|color| color | 0xFF_00_00_00);
+
+        self
+    }
+
+    /// Mirror the image horizontally and vertically when the flags are set.
+    pub(crate) fn with_flip(mut self, flip: (bool, bool)) -> Self {
+        self.flip = flip;
+
+        self
+    }
+
+    /// Whether the image is mirrored horizontally and vertically.
+    pub(crate) fn flip(&self) -> (bool, bool) {
+        self.flip
+    }
+
+    /// Pixels the effects can move the image outside of its bounds.
+    pub(crate) fn overhang(&self) -> i32 {
+        self.wave
+            .map_or(0, |wave| wave.amplitude.abs().ceil() as i32)
+    }
+
+    /// Whether there are no effects, so the image can be drawn with just the blitter.
+    fn is_empty(&self) -> bool {
+        self.pixel_fn.is_none()
+            && self.color_adjust.is_none()
+            && self.flash.is_none()
+            && self.opacity == u8::MAX
+            && self.mask_color.is_none()
+            && self.stipple.is_none()
+            && self.dissolve.is_none()
+            && self.wave.is_none()
+            && self.depth.is_none()
+            && self.flip == (false, false)
+    }
+
+    /// Whether the pixel of the image at the buffer position isn't skipped by any of the effects.
+    fn is_drawn(&self, src: u32, x: i32, y: i32, pos: (i32, i32)) -> bool {
+        Some(src) != self.mask_color
+            && dither::is_visible(x, y, self.opacity)
+            && self.stipple.is_none_or(|stipple| stipple.is_visible(x, y))
+            && self
+                .dissolve
+                .is_none_or(|dissolve| dissolve.is_visible(x - pos.0, y - pos.1))
+            && self
+                .depth
+                .is_none_or(|(depth, depth_buffer)| depth_buffer.is_visible(x, depth))
+    }
+
+    /// Draw the image stretched to the size with all effects applied.
+    pub(crate) fn blit<B>(
+        &self,
+        blitter: &B,
+        image: &SpriteImage,
+        buffer: &mut PixelBuffer,
+        pos: (i32, i32),
+        size: (usize, usize),
+    ) where
+        B: Blitter,
+    {
+        if self.hidden {
+            return;
+        }
+
+        let scaled = size != (image.width(), image.height());
+        if self.is_empty() && !scaled && image.composite() == Composite::Mask {
+            // Without any effects the blitter can use its fastest path
+            blitter.blit(image, buffer, pos);
+
+            return;
+        }
+
+        let composite = image.composite();
+        let mut draw = |src: u32, dst: &mut u32, x, y| {
+            // Split the alpha channel off so the effects only see opaque colors
+            let alpha = match composite {
+                Composite::Mask => 0xFF,
+                Composite::Alpha | Composite::Premultiplied | Composite::Additive => src >> 24,
+            };
+            let src = src | 0xFF_00_00_00;
+
+            if !self.is_drawn(src, x, y, pos) {
+                return;
+            }
+
+            let src = match (self.flash, &self.color_adjust) {
+                (Some(flash), _) => flash,
+                (None, Some(color_adjust)) => color_adjust.apply(src),
+                (None, None) => src,
+            };
+
+            let color = match self.pixel_fn {
+                Some(pixel_fn) => (pixel_fn.0)(src, *dst, x, y),
+                None => blitter.blend(src, *dst),
+            };
+
+            *dst = match composite {
+                Composite::Additive => add_saturating(scale(color, alpha), *dst),
+                _ if alpha == 0xFF => color,
+                Composite::Premultiplied => blend_premultiplied(color, *dst, alpha),
+                _ => blend_alpha(color, *dst, alpha),
+            };
+        };
+
+        if let Some(wave) = self.wave {
+            blit_wave(image, buffer, pos, size, wave, self.flip, &mut draw);
+        } else if scaled || self.flip != (false, false) {
+            image.blit_sampled_with(buffer, pos, size, self.flip, &mut draw);
+        } else {
+            image.blit_with(buffer, pos, &mut draw);
+        }
+    }
+}
+
+/// Draw the image stretched to the size with the rows or columns moved by the wave.
+fn blit_wave<F>(
+    image: &SpriteImage,
+    buffer: &mut PixelBuffer,
+    pos: (i32, i32),
+    size: (usize, usize),
+    wave: &Wave,
+    flip: (bool, bool),
+    mut f: F,
+) where
+    F: FnMut(u32, &mut u32, i32, i32),
+{
+    if size.0 == 0 || size.1 == 0 {
+        return;
+    }
+
+    // The offset only depends on the row or column, so the sine is calculated once for each
+    let offsets: Vec<i32> = match wave.direction {
+        WaveDirection::Horizontal => (0..size.1 as i32).map(|row| wave.offset(row)).collect(),
+        WaveDirection::Vertical => (0..size.0 as i32)
+            .map(|column| wave.offset(column))
+            .collect(),
+    };
+
+    let (buffer_width, buffer_height) = (buffer.width() as i32, buffer.height() as i32);
+    for local_y in 0..size.1 as i32 {
+        for local_x in 0..size.0 as i32 {
+            let (x, y) = match wave.direction {
+                WaveDirection::Horizontal => {
+                    (pos.0 + local_x + offsets[local_y as usize], pos.1 + local_y)
+                }
+                WaveDirection::Vertical => {
+                    (pos.0 + local_x, pos.1 + local_y + offsets[local_x as usize])
+                }
+            };
+            if x < 0 || y < 0 || x >= buffer_width || y >= buffer_height {
+                continue;
+            }
+
+            // Find the nearest pixel of the image
+            let (local_x, local_y) = (local_x as usize, local_y as usize);
+            let local_x = if flip.0 {
+                size.0 - 1 - local_x
+            } else {
+                local_x
+            };
+            let local_y = if flip.1 {
+                size.1 - 1 - local_y
+            } else {
+                local_y
+            };
+            let src_x = local_x * image.width() / size.0;
+            let src_y = local_y * image.height() / size.1;
+            let src = image.pixels()[src_y * image.width() + src_x];
+            if image.is_visible(src) {
+                f(
+                    src,
+                    &mut buffer.pixels_mut()[(y * buffer_width + x) as usize],
+                    x,
+                    y,
+                );
+            }
+        }
+    }
+}
+
+/// Mix the source color over the destination color, an alpha of `255` results in the source.
+pub(crate) fn blend_alpha(src: u32, dst: u32, alpha: u32) -> u32 {
+    let inverse = 0xFF - alpha;
+    let channel = |shift: u32| {
+        let src = (src >> shift) & 0xFF;
+        let dst = (dst >> shift) & 0xFF;
+
+        ((src * alpha + dst * inverse + 0x7F) / 0xFF) << shift
+    };
+
+    0xFF_00_00_00 | channel(16) | channel(8) | channel(0)
+}
+
+/// Add the premultiplied source color to the destination color scaled by the inverse alpha.
+fn blend_premultiplied(src: u32, dst: u32, alpha: u32) -> u32 {
+    // Effects can make the colors brighter than the alpha, so clamp overflowing channels
+    add_saturating(src, scale(dst, 0xFF - alpha))
+}
+
+/// Multiply all color channels by the factor where `255` keeps the color the same.
+///
+/// The red and blue channels are calculated at once, dividing by 255 is done with shifts.
+fn scale(color: u32, factor: u32) -> u32 {
+    if factor == 0xFF {
+        return color;
+    }
+
+    let red_blue = (color & 0xFF_00_FF) * factor + 0x80_00_80;
+    let red_blue = ((red_blue + ((red_blue >> 8) & 0xFF_00_FF)) >> 8) & 0xFF_00_FF;
+    let green = (color & 0x00_FF_00) * factor + 0x00_80_00;
+    let green = ((green + ((green >> 8) & 0x00_FF_00)) >> 8) & 0x00_FF_00;
+
+    red_blue | green
+}
+
+/// Add the color channels together, channels that would overflow become `255`.
+fn add_saturating(src: u32, dst: u32) -> u32 {
+    let red_blue = (src & 0xFF_00_FF) + (dst & 0xFF_00_FF);
+    let red_blue = (red_blue | (0x01_00_01_00 - ((red_blue >> 8) & 0x00_01_00_01))) & 0xFF_00_FF;
+    let green = ((src & 0x00_FF_00) + (dst & 0x00_FF_00)).min(0x00_FF_00);
+
+    0xFF_00_00_00 | red_blue | green
+}
@@ -0,0 +1,75 @@
+//! Errors that can occur when loading sprites.
+
+use thiserror::Error;
+
+/// Error returned when loading a sprite or font fails.
+///
+/// It implements `std::error::Error`, so it can be converted to other error types like
+/// `anyhow::Error` with `?`.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::{load, SpriteError};
+///
+/// let sprite = BlitBuffer::from_buffer(&[], 1, 0xFF00FF);
+///
+/// match load(sprite) {
+///     Err(SpriteError::Empty { width, height }) => println!("sprite is {}x{}", width, height),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SpriteError {
+    /// The sprite doesn't contain any pixels.
+    #[error("sprite of {width}x{height} pixels is empty")]
+    Empty {
+        /// Width of the sprite in pixels.
+        width: usize,
+        /// Height of the sprite in pixels.
+        height: usize,
+    },
+    /// The end of the rotation range is not after the start.
+    #[error("rotation range from {start} to {end} degrees is empty")]
+    InvalidRotationRange {
+        /// Start of the range in degrees.
+        start: i16,
+        /// End of the range in degrees.
+        end: i16,
+    },
+    /// The font data could not be parsed, returned by [`Font::from_ttf`](crate::Font::from_ttf).
+    #[cfg(feature = "fontdue")]
+    #[error("parsing the font failed: {0}")]
+    InvalidFont(&'static str),
+    /// Baking a rotation of the sprite failed.
+    #[cfg(feature = "rotsprite")]
+    #[error("rotating the sprite failed")]
+    Rotation(#[from] rotsprite::Error),
+    /// The sprite doesn't fit in the buffer, returned by
+    /// [`SpriteRef::validate_against`](crate::SpriteRef::validate_against).
+    #[error(
+        "sprite of {width}x{height} pixels is larger than the buffer of \
+         {buffer_width}x{buffer_height} pixels"
+    )]
+    LargerThanBuffer {
+        /// Width of the sprite in pixels.
+        width: usize,
+        /// Height of the sprite in pixels.
+        height: usize,
+        /// Width of the buffer in pixels.
+        buffer_width: usize,
+        /// Height of the buffer in pixels.
+        buffer_height: usize,
+    },
+    /// Another thread panicked while it was accessing the sprites.
+    #[error("the sprite store is poisoned because a thread panicked while accessing it")]
+    StorePoisoned,
+    /// All slots of the sprite store are used, see [`set_max_sprites`](crate::set_max_sprites).
+    #[error("no room for {requested} more sprites, the maximum is {max}")]
+    TooManySprites {
+        /// Amount of rotations and downscaled versions that would be added.
+        requested: usize,
+        /// Maximum amount of sprites in the store.
+        max: usize,
+    },
+}
@@ -0,0 +1,275 @@
+//! Hiding the parts of the world that can't be seen.
+
+use crate::{
+    dither,
+    profiler::{self, RenderPhase, RenderProfiler},
+    Camera, PixelBuffer,
+};
+use specs::prelude::*;
+use web_time::Instant;
+
+/// How much of a cell of the [`FogOfWar`] is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    /// Never seen, covered completely by the fog.
+    #[default]
+    Unseen,
+    /// Seen before but not at the moment, shown darkened.
+    Explored,
+    /// Seen at the moment, shown as is.
+    Visible,
+}
+
+impl Visibility {
+    /// Value used for blending between the states.
+    fn level(self) -> f32 {
+        match self {
+            Visibility::Unseen => 0.0,
+            Visibility::Explored => 1.0,
+            Visibility::Visible => 2.0,
+        }
+    }
+
+    /// State of the value, rounded down.
+    fn from_level(level: usize) -> Self {
+        match level {
+            0 => Visibility::Unseen,
+            1 => Visibility::Explored,
+            _ => Visibility::Visible,
+        }
+    }
+}
+
+/// Resource with a grid of visibility states laid over the world, drawn over the rendered frame
+/// by the [`FogOfWarSystem`].
+///
+/// Unseen cells are filled with the fog color and explored cells are darkened. The edges between
+/// cells of different states are blended with ordered dithering, so no alpha blending is needed.
+/// Everything outside of the grid is unseen.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{FogOfWar, FogOfWarSystem, PixelBuffer, Visibility};
+///
+/// let mut world = World::new();
+///
+/// // Everything is white before the fog is applied
+/// let mut buffer = PixelBuffer::new(24, 24);
+/// buffer.clear(0xFF_FF_FF);
+/// world.insert(buffer);
+///
+/// // Only the center of a grid of 3x3 cells of 8x8 pixels is visible
+/// let mut fog = FogOfWar::new(3, 3, (8, 8));
+/// fog.set_visibility(1, 1, Visibility::Visible);
+/// world.insert(fog);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     // Must run after the sprites are rendered
+///     .with_thread_local(FogOfWarSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+///
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[12 * 24 + 12] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// assert_eq!(buffer.pixels()[0] & 0xFF_FF_FF, 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FogOfWar {
+    /// Amount of cells in a row.
+    width: usize,
+    /// Amount of rows.
+    height: usize,
+    /// State of every cell.
+    cells: Vec<Visibility>,
+    /// Size of a cell in world pixels.
+    cell_size: (i32, i32),
+    /// World position of the top left corner of the first cell.
+    pos: (i32, i32),
+    /// Color of the unseen parts.
+    pub color: u32,
+    /// How bright the explored parts are, from `0.0` for black to `1.0` for not darkened.
+    pub explored_brightness: f32,
+}
+
+impl FogOfWar {
+    /// Create a grid of unseen cells with the size of a single cell in world pixels.
+    pub fn new(width: usize, height: usize, cell_size: (i32, i32)) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Visibility::Unseen; width * height],
+            cell_size,
+            pos: (0, 0),
+            color: 0,
+            explored_brightness: 0.5,
+        }
+    }
+
+    /// Amount of cells in a row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Amount of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size of a cell in world pixels.
+    pub fn cell_size(&self) -> (i32, i32) {
+        self.cell_size
+    }
+
+    /// Set the world position of the top left corner of the first cell.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the world position of the top left corner of the first cell.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Set the state of the cell, ignored when it's outside of the grid.
+    pub fn set_visibility(&mut self, x: usize, y: usize, visibility: Visibility) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = visibility;
+        }
+    }
+
+    /// Get the state of the cell, unseen when it's outside of the grid.
+    pub fn visibility(&self, x: i32, y: i32) -> Visibility {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.cells[y as usize * self.width + x as usize]
+        } else {
+            Visibility::Unseen
+        }
+    }
+
+    /// Make all cells within the radius in cells around the cell visible.
+    pub fn reveal(&mut self, x: i32, y: i32, radius: i32) {
+        for cell_y in (y - radius).max(0)..=(y + radius).min(self.height as i32 - 1) {
+            for cell_x in (x - radius).max(0)..=(x + radius).min(self.width as i32 - 1) {
+                let (dx, dy) = (cell_x - x, cell_y - y);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.cells[cell_y as usize * self.width + cell_x as usize] =
+                        Visibility::Visible;
+                }
+            }
+        }
+    }
+
+    /// Turn all visible cells into explored cells, call it before revealing what's seen in the
+    /// current frame.
+    pub fn hide_visible(&mut self) {
+        for cell in self.cells.iter_mut() {
+            if *cell == Visibility::Visible {
+                *cell = Visibility::Explored;
+            }
+        }
+    }
+
+    /// Convert a world position to the coordinate of the cell it's in, which can be outside of
+    /// the grid.
+    pub fn world_to_cell(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            (x - self.pos.0).div_euclid(self.cell_size.0.max(1)),
+            (y - self.pos.1).div_euclid(self.cell_size.1.max(1)),
+        )
+    }
+
+    /// Blended state at the world position, between the centers of the surrounding cells.
+    fn level(&self, x: f32, y: f32) -> f32 {
+        let (width, height) = (
+            self.cell_size.0.max(1) as f32,
+            self.cell_size.1.max(1) as f32,
+        );
+
+        // Relative to the center of the first cell
+        let cell_x = (x - self.pos.0 as f32) / width - 0.5;
+        let cell_y = (y - self.pos.1 as f32) / height - 0.5;
+        let (left, top) = (cell_x.floor(), cell_y.floor());
+        let (fraction_x, fraction_y) = (cell_x - left, cell_y - top);
+        let (left, top) = (left as i32, top as i32);
+
+        let level = |x, y| self.visibility(x, y).level();
+        let top_level = level(left, top) * (1.0 - fraction_x) + level(left + 1, top) * fraction_x;
+        let bottom_level =
+            level(left, top + 1) * (1.0 - fraction_x) + level(left + 1, top + 1) * fraction_x;
+
+        top_level * (1.0 - fraction_y) + bottom_level * fraction_y
+    }
+
+    /// Cover the buffer with the fog as seen by the camera.
+    pub fn draw(&self, buffer: &mut PixelBuffer, camera: &Camera) {
+        let zoom = camera.zoom() as f32;
+        if zoom <= 0.0 {
+            return;
+        }
+
+        let width = buffer.width();
+        let camera_pos = camera.pos();
+        let brightness = self.explored_brightness.clamp(0.0, 1.0);
+        for (y, row) in buffer
+            .pixels_mut()
+            .chunks_exact_mut(width.max(1))
+            .enumerate()
+        {
+            let world_y = (y as f32 + 0.5) / zoom + camera_pos.1 as f32;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let world_x = (x as f32 + 0.5) / zoom + camera_pos.0 as f32;
+
+                // Dither between the two states the position is in between
+                let level = self.level(world_x, world_y);
+                let lower = level.floor();
+                let opacity = ((level - lower) * 255.0) as u8;
+                let state = if dither::is_visible(x as i32, y as i32, opacity) {
+                    Visibility::from_level(lower as usize + 1)
+                } else {
+                    Visibility::from_level(lower as usize)
+                };
+
+                match state {
+                    Visibility::Visible => (),
+                    Visibility::Explored => *pixel = darken(*pixel, brightness),
+                    Visibility::Unseen => *pixel = self.color,
+                }
+            }
+        }
+    }
+}
+
+/// Multiply the color channels by the factor, keeping the alpha channel.
+pub(crate) fn darken(color: u32, factor: f32) -> u32 {
+    let channel = |shift: u32| (((color >> shift & 0xFF) as f32 * factor) as u32) << shift;
+
+    color & 0xFF_00_00_00 | channel(16) | channel(8) | channel(0)
+}
+
+/// Specs system covering the buffer with the [`FogOfWar`] resource through the [`Camera`]
+/// resource.
+///
+/// Does nothing when the resource doesn't exist. It has to run after the
+/// [`RenderSystem`](crate::RenderSystem), e.g. by passing the name of the render system as a
+/// dependency to `.with()`.
+#[derive(Debug, Default)]
+pub struct FogOfWarSystem;
+impl<'a> System<'a> for FogOfWarSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Camera>>,
+        Option<Read<'a, FogOfWar>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, camera, fog, profiler): Self::SystemData) {
+        let start = Instant::now();
+        if let Some(fog) = fog {
+            let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+            fog.draw(&mut buffer, &camera);
+        }
+
+        profiler::record(profiler, RenderPhase::PostProcess, "FogOfWarSystem", start);
+    }
+}
@@ -0,0 +1,219 @@
+//! Conversion of the rendered pixels to the formats expected by presentation backends.
+
+use crate::PixelBuffer;
+
+/// Byte layout of a pixel when copying a [`PixelBuffer`] to another buffer.
+///
+/// The pixels in a [`PixelBuffer`] are stored as `0xXXRRGGBB` `u32` values, most backends expect
+/// raw bytes in a specific order instead. The highest byte is ignored when converting: pixels
+/// drawn from sprites have it set to `0xFF`, cleared pixels have the value passed to
+/// [`PixelBuffer::clear`].
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::{load, PixelBuffer, PixelFormat};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut buffer = PixelBuffer::new(2, 1);
+/// buffer.clear(0x11_22_33);
+/// buffer.blit_sprite(&load(BlitBuffer::from_buffer(&[0x11_22_33], 1, 0xFF_00_FF))?, 1, 0, 0);
+///
+/// // The sprite sets the highest byte, but both pixels are converted the same
+/// assert_eq!(buffer.pixels()[..], [0x00_11_22_33, 0xFF_11_22_33]);
+/// let mut out = vec![0; 2 * PixelFormat::Rgb8.bytes_per_pixel()];
+/// buffer.copy_to_format(PixelFormat::Rgb8, &mut out);
+/// assert_eq!(out, [0x11, 0x22, 0x33, 0x11, 0x22, 0x33]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PixelFormat {
+    /// 4 bytes per pixel in the order red, green, blue, alpha, e.g. for WebGL & `ImageData`.
+    Rgba8,
+    /// 4 bytes per pixel in the order blue, green, red, alpha, e.g. for most swapchains.
+    Bgra8,
+    /// 3 bytes per pixel in the order red, green, blue.
+    Rgb8,
+    /// 2 bytes per pixel as a little-endian `u16` with 5 bits red, 6 bits green & 5 bits blue,
+    /// e.g. for embedded displays.
+    Rgb565,
+}
+
+impl PixelFormat {
+    /// Amount of bytes a single pixel takes in this format.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgba8 | PixelFormat::Bgra8 => 4,
+            PixelFormat::Rgb8 => 3,
+            PixelFormat::Rgb565 => 2,
+        }
+    }
+}
+
+impl PixelBuffer {
+    /// Copy all pixels to a byte buffer in the target format.
+    ///
+    /// The alpha channel is always written as fully opaque.
+    ///
+    /// # Panics
+    ///
+    /// When `out` is smaller than `width * height * format.bytes_per_pixel()`.
+    ///
+    /// ```rust
+    /// use specs_blit::{PixelBuffer, PixelFormat};
+    ///
+    /// let mut buffer = PixelBuffer::new(2, 1);
+    /// buffer.clear(0x11_22_33);
+    ///
+    /// let mut out = vec![0; 2 * PixelFormat::Rgba8.bytes_per_pixel()];
+    /// buffer.copy_to_format(PixelFormat::Rgba8, &mut out);
+    ///
+    /// assert_eq!(out, [0x11, 0x22, 0x33, 0xFF, 0x11, 0x22, 0x33, 0xFF]);
+    /// ```
+    pub fn copy_to_format(&self, format: PixelFormat, out: &mut [u8]) {
+        self.copy_to_format_with_stride(format, out, self.width * format.bytes_per_pixel());
+    }
+
+    /// Copy all pixels to a byte buffer in the target format where every row starts
+    /// `bytes_per_row` bytes after the previous one.
+    ///
+    /// This is needed for graphics APIs that require rows to be padded to a certain alignment,
+    /// the padding bytes are left untouched.
+    ///
+    /// # Panics
+    ///
+    /// When `bytes_per_row` can't hold a single row or `out` can't hold all rows.
+    ///
+    /// ```rust
+    /// use specs_blit::{PixelBuffer, PixelFormat};
+    ///
+    /// let mut buffer = PixelBuffer::new(1, 2);
+    /// buffer.clear(0x11_22_33);
+    ///
+    /// // Pad every row to 4 bytes
+    /// let mut out = vec![0; 8];
+    /// buffer.copy_to_format_with_stride(PixelFormat::Rgb8, &mut out, 4);
+    ///
+    /// assert_eq!(out, [0x11, 0x22, 0x33, 0, 0x11, 0x22, 0x33, 0]);
+    /// ```
+    pub fn copy_to_format_with_stride(
+        &self,
+        format: PixelFormat,
+        out: &mut [u8],
+        bytes_per_row: usize,
+    ) {
+        let row_bytes = self.width * format.bytes_per_pixel();
+        assert!(
+            bytes_per_row >= row_bytes,
+            "row stride of {} bytes is too small, {} bytes are needed",
+            bytes_per_row,
+            row_bytes
+        );
+        let bytes = if self.height == 0 {
+            0
+        } else {
+            (self.height - 1) * bytes_per_row + row_bytes
+        };
+        assert!(
+            out.len() >= bytes,
+            "output buffer of {} bytes is too small, {} bytes are needed",
+            out.len(),
+            bytes
+        );
+
+        if self.width == 0 {
+            return;
+        }
+
+        for (out, pixels) in out
+            .chunks_mut(bytes_per_row)
+            .zip(self.pixels.chunks_exact(self.width))
+        {
+            convert_row(format, pixels, &mut out[..row_bytes]);
+        }
+    }
+
+    /// Copy the pixels into an image so it can be processed or saved with the `image` crate.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(4, 3);
+    /// buffer.clear(0xFF_00_00);
+    ///
+    /// let image = buffer.to_rgba_image();
+    /// assert_eq!(image.dimensions(), (4, 3));
+    /// assert_eq!(image.get_pixel(0, 0).0, [0xFF, 0x00, 0x00, 0xFF]);
+    /// ```
+    #[cfg(feature = "image")]
+    pub fn to_rgba_image(&self) -> image::RgbaImage {
+        let mut raw = vec![0; self.pixels.len() * PixelFormat::Rgba8.bytes_per_pixel()];
+        self.copy_to_format(PixelFormat::Rgba8, &mut raw);
+
+        image::RgbaImage::from_raw(self.width as u32, self.height as u32, raw)
+            .expect("buffer size doesn't match the dimensions")
+    }
+
+    /// Create a buffer from an image, the alpha channel is discarded and the pixels are stored as
+    /// `0x00RRGGBB`.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let image = image::RgbaImage::from_pixel(2, 2, image::Rgba([0x11, 0x22, 0x33, 0xFF]));
+    ///
+    /// let buffer = PixelBuffer::from_rgba_image(&image);
+    /// assert_eq!(buffer.pixels()[0], 0x11_22_33);
+    /// ```
+    #[cfg(feature = "image")]
+    pub fn from_rgba_image(image: &image::RgbaImage) -> Self {
+        Self {
+            pixels: image
+                .pixels()
+                .map(|pixel| {
+                    let [r, g, b, _] = pixel.0;
+
+                    u32::from_be_bytes([0, r, g, b])
+                })
+                .collect(),
+            width: image.width() as usize,
+            height: image.height() as usize,
+        }
+    }
+}
+
+/// Convert a single row of pixels, `out` must have exactly enough room for all pixels.
+fn convert_row(format: PixelFormat, pixels: &[u32], out: &mut [u8]) {
+    // Every arm is a simple zipped loop without bounds checks so it can be vectorized
+    match format {
+        PixelFormat::Rgba8 => {
+            for (dst, &pixel) in out.chunks_exact_mut(4).zip(pixels.iter()) {
+                dst.copy_from_slice(&(pixel << 8 | 0xFF).to_be_bytes());
+            }
+        }
+        PixelFormat::Bgra8 => {
+            for (dst, &pixel) in out.chunks_exact_mut(4).zip(pixels.iter()) {
+                dst.copy_from_slice(&(pixel | 0xFF_00_00_00).to_le_bytes());
+            }
+        }
+        PixelFormat::Rgb8 => {
+            for (dst, &pixel) in out.chunks_exact_mut(3).zip(pixels.iter()) {
+                dst.copy_from_slice(&pixel.to_be_bytes()[1..]);
+            }
+        }
+        PixelFormat::Rgb565 => {
+            for (dst, &pixel) in out.chunks_exact_mut(2).zip(pixels.iter()) {
+                dst.copy_from_slice(&rgb565(pixel).to_le_bytes());
+            }
+        }
+    }
+}
+
+/// Pack a `0xXXRRGGBB` color into 16 bits by dropping the lowest bits of every channel.
+fn rgb565(pixel: u32) -> u16 {
+    let r = (pixel >> 8) & 0xF8_00;
+    let g = (pixel >> 5) & 0x07_E0;
+    let b = (pixel >> 3) & 0x00_1F;
+
+    (r | g | b) as u16
+}
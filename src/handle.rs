@@ -0,0 +1,205 @@
+//! Loading sprites on another thread.
+
+use crate::{load_rotations, Sprite, SpriteError, SpriteRef};
+use blit::BlitBuffer;
+use specs::prelude::*;
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+/// How far a [`SpriteHandle`] is.
+#[derive(Debug)]
+enum HandleState {
+    /// The rotations are still being baked.
+    Loading,
+    /// The sprite is in the store.
+    Loaded(SpriteRef),
+    /// Loading failed, the error is taken out once it's read.
+    Failed(Option<SpriteError>),
+}
+
+/// Reference to a sprite that's loaded on another thread, returned by [`load_async`].
+///
+/// Cloning the handle doesn't load the sprite again, all clones resolve at the same time.
+#[derive(Debug, Clone)]
+pub struct SpriteHandle(Arc<(Mutex<HandleState>, Condvar)>);
+
+impl SpriteHandle {
+    /// Create a handle that's still loading.
+    fn new() -> Self {
+        Self(Arc::new((Mutex::new(HandleState::Loading), Condvar::new())))
+    }
+
+    /// Store the result of loading and wake up everything that's waiting for it.
+    fn resolve(&self, result: Result<SpriteRef, SpriteError>) {
+        let (state, loaded) = &*self.0;
+        *state.lock().unwrap() = match result {
+            Ok(sprite_ref) => HandleState::Loaded(sprite_ref),
+            Err(err) => HandleState::Failed(Some(err)),
+        };
+
+        loaded.notify_all();
+    }
+
+    /// The loaded sprite, `None` when it's still loading or loading failed.
+    pub fn get(&self) -> Option<SpriteRef> {
+        match &*self.0 .0.lock().unwrap() {
+            HandleState::Loaded(sprite_ref) => Some(sprite_ref.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether the sprite is still being loaded.
+    pub fn is_loading(&self) -> bool {
+        matches!(*self.0 .0.lock().unwrap(), HandleState::Loading)
+    }
+
+    /// Whether loading the sprite failed.
+    pub fn is_failed(&self) -> bool {
+        matches!(*self.0 .0.lock().unwrap(), HandleState::Failed(_))
+    }
+
+    /// Take the error when loading failed, it's only returned the first time.
+    pub fn take_error(&self) -> Option<SpriteError> {
+        match &mut *self.0 .0.lock().unwrap() {
+            HandleState::Failed(err) => err.take(),
+            _ => None,
+        }
+    }
+
+    /// Block the current thread until the sprite is loaded, `None` when loading failed.
+    pub fn wait(&self) -> Option<SpriteRef> {
+        let (state, loaded) = &*self.0;
+        let state = loaded
+            .wait_while(state.lock().unwrap(), |state| {
+                matches!(state, HandleState::Loading)
+            })
+            .unwrap();
+
+        match &*state {
+            HandleState::Loaded(sprite_ref) => Some(sprite_ref.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Load a sprite buffer with a set amount of rotations on another thread.
+///
+/// The rotations are baked in the background, so loading many sprites while the game is running
+/// doesn't cause hitches. Use the handle with a [`LoadingSprite`] component to show the sprite
+/// once it's loaded.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::load_async;
+///
+/// let handle = load_async(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF), 16);
+///
+/// // Do something else while the sprite is loaded
+///
+/// let sprite_ref = handle.wait().expect("loading failed");
+/// ```
+pub fn load_async(sprite: BlitBuffer, rotations: u16) -> SpriteHandle {
+    let handle = SpriteHandle::new();
+
+    let worker_handle = handle.clone();
+    thread::spawn(move || worker_handle.resolve(load_rotations(sprite, rotations)));
+
+    handle
+}
+
+/// Specs component giving the entity the sprite of the [`SpriteHandle`] once it's loaded by the
+/// [`SpriteLoadSystem`].
+///
+/// When the entity already has a [`Sprite`] it's shown as a placeholder, only the image is
+/// replaced so its position and other settings are kept. Otherwise nothing is drawn until a
+/// sprite is added with the loaded image. The component is removed when loading is done, also
+/// when it failed.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, load_async, LoadingSprite, Sprite, SpriteLoadSystem};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(SpriteLoadSystem, "sprite_load", &[])
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// // Show a single pixel while the big sprite is loading
+/// let placeholder = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+/// let handle = load_async(BlitBuffer::from_buffer(&[0xFF_00_00; 64], 8, 0xFF_00_FF), 32);
+/// let entity = world
+///     .create_entity()
+///     .with(Sprite::new(placeholder))
+///     .with(LoadingSprite::new(handle.clone()))
+///     .build();
+///
+/// handle.wait();
+/// dispatcher.dispatch(&world);
+/// world.maintain();
+///
+/// assert!(world.read_storage::<LoadingSprite>().get(entity).is_none());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadingSprite {
+    /// Sprite that's being loaded.
+    handle: SpriteHandle,
+}
+
+impl Component for LoadingSprite {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl LoadingSprite {
+    /// Create the component for the handle.
+    pub fn new(handle: SpriteHandle) -> Self {
+        Self { handle }
+    }
+
+    /// Handle of the sprite that's being loaded.
+    pub fn handle(&self) -> &SpriteHandle {
+        &self.handle
+    }
+}
+
+/// Specs system setting the [`Sprite`] of every [`LoadingSprite`] whose sprite is loaded.
+#[derive(Debug, Default)]
+pub struct SpriteLoadSystem;
+impl<'a> System<'a> for SpriteLoadSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, LoadingSprite>,
+        WriteStorage<'a, Sprite>,
+    );
+
+    fn run(&mut self, (entities, mut loading_sprites, mut sprites): Self::SystemData) {
+        let done: Vec<(Entity, Option<SpriteRef>)> = (&entities, &loading_sprites)
+            .join()
+            .filter(|(_, loading)| !loading.handle.is_loading())
+            .map(|(entity, loading)| (entity, loading.handle.get()))
+            .collect();
+
+        for (entity, sprite_ref) in done {
+            loading_sprites.remove(entity);
+
+            let sprite_ref = match sprite_ref {
+                Some(sprite_ref) => sprite_ref,
+                None => continue,
+            };
+            match sprites.get_mut(entity) {
+                Some(sprite) => sprite.reference = sprite_ref,
+                None => {
+                    // Can only fail when the entity is dead, which is fine to ignore
+                    let _ = sprites.insert(entity, Sprite::new(sprite_ref));
+                }
+            }
+        }
+    }
+}
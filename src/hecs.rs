@@ -0,0 +1,63 @@
+//! Drawing sprites stored in a [hecs](https://docs.rs/hecs) world.
+//!
+//! The [`Sprite`] component is used the same way as in specs, together with the [`Instances`],
+//! [`MultiSprite`] and [`SpriteEffects`] components of the same entity. The sprites share the
+//! sprite store and are drawn by the same code as the [`RenderSystem`](crate::RenderSystem).
+//!
+//! ```rust
+//! use blit::BlitBuffer;
+//! use specs_blit::{load, Camera, Instances, PixelBuffer, Sprite};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut world = hecs::World::new();
+//!
+//! let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+//! let mut instances = Instances::new();
+//! instances.push(0, 0);
+//! instances.push(2, 0);
+//! world.spawn((Sprite::new(sprite_ref), instances));
+//!
+//! let mut buffer = PixelBuffer::new(3, 1);
+//! specs_blit::hecs::render(&world, &mut buffer, &Camera::default(), None);
+//!
+//! let drawn: Vec<bool> = buffer.pixels().iter().map(|pixel| *pixel != 0).collect();
+//! assert_eq!(drawn, [true, false, true]);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    Camera, Instances, MultiSprite, PixelBuffer, PixelSnap, Sprite, SpriteDraw, SpriteEffects,
+};
+use hecs::World;
+
+/// Draw the sprites of all entities in the world through the camera.
+///
+/// The sprites are drawn sorted by their layer and z-order, the positions of the sprites on the
+/// layers of the [`PixelSnap`] are snapped to the pixel grid.
+pub fn render(
+    world: &World,
+    buffer: &mut PixelBuffer,
+    camera: &Camera,
+    pixel_snap: Option<&PixelSnap>,
+) {
+    let mut query = world.query::<(
+        &Sprite,
+        Option<&SpriteEffects>,
+        Option<&Instances>,
+        Option<&MultiSprite>,
+    )>();
+    let draws = query
+        .iter()
+        .map(|(_, (sprite, effects, instances, multi_sprite))| {
+            SpriteDraw::new(sprite)
+                .with_effects(effects)
+                .with_instances(instances)
+                .with_multi_sprite(multi_sprite)
+                .with_snap(
+                    pixel_snap.is_some_and(|pixel_snap| pixel_snap.is_snapped(sprite.layer())),
+                )
+        });
+
+    buffer.draw_sprites_with(draws, camera);
+}
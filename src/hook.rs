@@ -0,0 +1,105 @@
+//! Custom drawing around the sprites of single entities.
+
+use crate::PixelBuffer;
+use specs::prelude::*;
+use std::fmt;
+
+/// Function receiving the buffer with the position and size the sprite is drawn at.
+type HookFn = Box<dyn Fn(&mut PixelBuffer, (i32, i32), (usize, usize)) + Send + Sync>;
+
+/// Component with functions the [`RenderSystem`](crate::RenderSystem) calls right before and
+/// right after the sprite of the entity is drawn.
+///
+/// The functions receive the buffer with the position and size in pixels the sprite is drawn at
+/// on it, so they can decorate the sprite without replacing the blitter. They are called for
+/// every time the sprite is drawn, e.g. for every instance, and not for sprites that are culled.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, RenderHook, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<RenderHook>();
+///
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?);
+/// sprite.set_pos(1, 0);
+/// world
+///     .create_entity()
+///     .with(sprite)
+///     // Draw a red pixel left of the sprite
+///     .with(RenderHook::new().with_after(|buffer, pos, _size| {
+///         let index = (pos.1 * buffer.width() as i32 + pos.0 - 1) as usize;
+///         buffer.pixels_mut()[index] = 0xFF_00_00;
+///     }))
+///     .build();
+///
+/// let buffer = testing::render(&mut world, 2, 1);
+/// assert_eq!(buffer.pixels()[0], 0xFF_00_00);
+/// assert_eq!(buffer.pixels()[1] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct RenderHook {
+    /// Called before the sprite is drawn.
+    before: Option<HookFn>,
+    /// Called after the sprite is drawn.
+    after: Option<HookFn>,
+}
+
+impl RenderHook {
+    /// Create a hook without any functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call the function right before the sprite is drawn, what it draws is below the sprite.
+    pub fn with_before<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&mut PixelBuffer, (i32, i32), (usize, usize)) + Send + Sync + 'static,
+    {
+        self.before = Some(Box::new(f));
+
+        self
+    }
+
+    /// Call the function right after the sprite is drawn, what it draws is above the sprite.
+    pub fn with_after<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&mut PixelBuffer, (i32, i32), (usize, usize)) + Send + Sync + 'static,
+    {
+        self.after = Some(Box::new(f));
+
+        self
+    }
+
+    /// Call the function that runs before the sprite is drawn.
+    pub(crate) fn before(&self, buffer: &mut PixelBuffer, pos: (i32, i32), size: (usize, usize)) {
+        if let Some(before) = &self.before {
+            before(buffer, pos, size);
+        }
+    }
+
+    /// Call the function that runs after the sprite is drawn.
+    pub(crate) fn after(&self, buffer: &mut PixelBuffer, pos: (i32, i32), size: (usize, usize)) {
+        if let Some(after) = &self.after {
+            after(buffer, pos, size);
+        }
+    }
+}
+
+impl Component for RenderHook {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl fmt::Debug for RenderHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderHook")
+            .field("before", &self.before.is_some())
+            .field("after", &self.after.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,187 @@
+//! Arrows pointing at sprites that are outside of the view.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    Anchor, Camera, PixelBuffer, Sprite, SpriteImage, SpriteRef, SPRITES,
+};
+use specs::prelude::*;
+use web_time::Instant;
+
+/// Specs component drawing an arrow at the edge of the buffer pointing at the [`Sprite`] of the
+/// same entity when it's outside of the view of the [`Camera`].
+///
+/// The arrow sprite must point to the right, it's rotated towards the sprite so it should be
+/// loaded with enough rotations, for example with
+/// [`load_rotations`](crate::load_rotations).
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     load, load_rotations, OffscreenIndicator, OffscreenIndicatorSystem, PixelBuffer,
+///     RenderSystem, Sprite,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(16, 16));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     // Draw the arrows over the sprites
+///     .with_thread_local(OffscreenIndicatorSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let arrow = load_rotations(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF), 8)?;
+///
+/// // An enemy far to the right of the view
+/// let mut enemy = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?);
+/// enemy.set_pos(100, 8);
+/// world
+///     .create_entity()
+///     .with(enemy)
+///     .with(OffscreenIndicator::new(arrow))
+///     .build();
+///
+/// dispatcher.dispatch(&world);
+///
+/// // The arrow is drawn at the right edge
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[8 * 16 + 15] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// assert_eq!(buffer.pixels()[8 * 16] & 0xFF_FF_FF, 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OffscreenIndicator {
+    /// Sprite pointing to the right.
+    arrow: SpriteRef,
+    /// Pixels between the arrow and the edge of the buffer.
+    margin: i32,
+}
+
+impl Component for OffscreenIndicator {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl OffscreenIndicator {
+    /// Create an indicator with an arrow sprite pointing to the right, touching the edge.
+    pub fn new(arrow: SpriteRef) -> Self {
+        Self { arrow, margin: 0 }
+    }
+
+    /// Set the amount of pixels between the arrow and the edge of the buffer.
+    pub fn set_margin(&mut self, margin: i32) {
+        self.margin = margin;
+    }
+
+    /// Get the amount of pixels between the arrow and the edge of the buffer.
+    pub fn margin(&self) -> i32 {
+        self.margin
+    }
+
+    /// Draw the arrow at the edge of the buffer pointing at the center of the rectangle in buffer
+    /// coordinates, nothing is drawn when the rectangle is visible in the buffer.
+    pub fn draw(&self, buffer: &mut PixelBuffer, target_pos: (i32, i32), target_size: (i32, i32)) {
+        self.draw_with(buffer, target_pos, target_size, &SPRITES.read().unwrap());
+    }
+
+    /// Draw the arrow like [`OffscreenIndicator::draw`], the images are the locked sprite store.
+    fn draw_with(
+        &self,
+        buffer: &mut PixelBuffer,
+        target_pos: (i32, i32),
+        target_size: (i32, i32),
+        sprite_images: &[SpriteImage],
+    ) {
+        let (width, height) = (buffer.width() as i32, buffer.height() as i32);
+        if target_pos.0 < width
+            && target_pos.1 < height
+            && target_pos.0 + target_size.0 > 0
+            && target_pos.1 + target_size.1 > 0
+        {
+            return;
+        }
+
+        // Direction from the center of the buffer to the center of the target
+        let center = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+        let dx = f64::from(target_pos.0) + f64::from(target_size.0) / 2.0 - center.0;
+        let dy = f64::from(target_pos.1) + f64::from(target_size.1) / 2.0 - center.1;
+        if dx == 0.0 && dy == 0.0 {
+            return;
+        }
+
+        // Keep the whole arrow inside of the buffer
+        let (arrow_width, arrow_height) = (self.arrow.size.0, self.arrow.size.1);
+        let half_width = (center.0 - f64::from(self.margin + (arrow_width + 1) / 2)).max(0.0);
+        let half_height = (center.1 - f64::from(self.margin + (arrow_height + 1) / 2)).max(0.0);
+
+        // Move from the center towards the target until an edge is hit
+        let scale = (half_width / dx.abs()).min(half_height / dy.abs());
+        let pos = (
+            (center.0 + dx * scale).floor() as i32,
+            (center.1 + dy * scale).floor() as i32,
+        );
+
+        let mut arrow = Sprite::new(self.arrow.clone());
+        arrow.set_anchor(Anchor::Center);
+        arrow.set_rot((dy.atan2(dx).to_degrees().round() as i16).rem_euclid(360));
+
+        let render_info = arrow.render_info(1.0);
+        if let Some(image) = sprite_images.get(render_info.index) {
+            let pos = (pos.0 + render_info.offset.0, pos.1 + render_info.offset.1);
+            let size = render_info.size(image);
+            image.blit_scaled_with(buffer, pos, size, |src, dst, _, _| *dst = src);
+        }
+    }
+}
+
+/// Specs system drawing the arrow of every [`OffscreenIndicator`] whose [`Sprite`] is outside of
+/// the view of the [`Camera`] resource.
+///
+/// It has to run after the [`RenderSystem`](crate::RenderSystem), so the arrows are drawn over
+/// the sprites.
+#[derive(Debug, Default)]
+pub struct OffscreenIndicatorSystem;
+impl<'a> System<'a> for OffscreenIndicatorSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Camera>>,
+        ReadStorage<'a, Sprite>,
+        ReadStorage<'a, OffscreenIndicator>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, camera, sprites, indicators, profiler): Self::SystemData) {
+        let start = Instant::now();
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+
+        // Lock the store once for all indicators
+        let sprite_images = SPRITES.read().unwrap();
+        for (sprite, indicator) in (&sprites, &indicators).join() {
+            // Calculate where the sprite is drawn the same way as the render system
+            let render_info = sprite.render_info(camera.zoom());
+            let size = match sprite_images.get(render_info.index) {
+                Some(image) => render_info.size(image),
+                None => continue,
+            };
+
+            let pos = camera.to_screen(sprite.pos());
+            let pos = (pos.0 + render_info.offset.0, pos.1 + render_info.offset.1);
+            indicator.draw_with(
+                &mut buffer,
+                pos,
+                (size.0 as i32, size.1 as i32),
+                &sprite_images,
+            );
+        }
+
+        profiler::record(
+            profiler,
+            RenderPhase::Blit,
+            "OffscreenIndicatorSystem",
+            start,
+        );
+    }
+}
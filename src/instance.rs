@@ -0,0 +1,126 @@
+//! Drawing the same sprite many times from a single entity.
+
+use specs::prelude::*;
+use std::iter::FromIterator;
+
+/// A single copy of the sprite of an [`Instances`] component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instance {
+    /// Position relative to the position of the sprite.
+    pub offset: (i32, i32),
+    /// Rotation in degrees used instead of the rotation of the sprite, `None` to use the rotation
+    /// of the sprite.
+    pub rot: Option<i16>,
+}
+
+/// Specs component drawing the [`Sprite`](crate::Sprite) of the same entity once for every
+/// instance instead of once at its position.
+///
+/// All instances share the layer, z-order and effects of the sprite, but are culled
+/// separately. This is much cheaper than an entity per copy for things like grass, bullets and
+/// decorations.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Instances, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<Instances>();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+///
+/// // Draw a row of grass with a single entity
+/// let mut grass = Instances::new();
+/// for x in 0..4 {
+///     grass.push(x * 2, 0);
+/// }
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(grass)
+///     .build();
+///
+/// let frame = testing::render(&mut world, 8, 1);
+/// assert_eq!(frame.pixels()[6] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// assert_eq!(frame.pixels()[7], 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Instances {
+    /// Copies that are drawn.
+    instances: Vec<Instance>,
+}
+
+impl Component for Instances {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Instances {
+    /// Create the component without any instances, so nothing is drawn.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a copy at the offset from the position of the sprite.
+    pub fn push(&mut self, x: i32, y: i32) {
+        self.instances.push(Instance {
+            offset: (x, y),
+            rot: None,
+        });
+    }
+
+    /// Add a copy at the offset from the position of the sprite with its own rotation in degrees.
+    ///
+    /// The rotation will attempt to match the nearest degrees of rotation divisor.
+    pub fn push_rotated(&mut self, x: i32, y: i32, rotation: i16) {
+        self.instances.push(Instance {
+            offset: (x, y),
+            rot: Some(rotation.rem_euclid(360)),
+        });
+    }
+
+    /// Remove all copies.
+    pub fn clear(&mut self) {
+        self.instances.clear();
+    }
+
+    /// Amount of copies.
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Whether there are no copies.
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// All copies in the order they are drawn as a slice.
+    pub(crate) fn as_slice(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// All copies in the order they are drawn.
+    pub fn iter(&self) -> impl Iterator<Item = &Instance> {
+        self.instances.iter()
+    }
+
+    /// All copies in the order they are drawn, to change them.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Instance> {
+        self.instances.iter_mut()
+    }
+}
+
+impl FromIterator<Instance> for Instances {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Instance>,
+    {
+        Self {
+            instances: iter.into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Rotating sprites only when a rotation is used.
+
+use crate::{
+    reclaim::{self, SlotGuard},
+    Camera, Instances, MultiSprite, RotationAlgorithm, Sprite, SpriteError, SpriteImage, SpriteRef,
+    SPRITES,
+};
+use blit::BlitBuffer;
+use lazy_static::lazy_static;
+use specs::prelude::*;
+use std::{
+    collections::HashMap,
+    iter, mem,
+    sync::{Arc, Mutex},
+};
+
+lazy_static! {
+    /// Rotations that are created when they are used, by their slot in the store.
+    static ref LAZY: Mutex<HashMap<usize, LazyRotation>> = Mutex::new(HashMap::new());
+}
+
+/// A rotation of a sprite that's created when it's used.
+#[derive(Clone)]
+struct LazyRotation {
+    /// Unrotated image shared by all rotations of the sprite.
+    source: Arc<SpriteImage>,
+    /// Clockwise rotation in degrees.
+    degrees: f64,
+    /// Width and height of the square the rotated image is centered in.
+    side: usize,
+    /// How the rotated image is created.
+    algorithm: RotationAlgorithm,
+    /// Whether the rotated image is in the store.
+    baked: bool,
+    /// Memory taken up by the rotated image in the store.
+    bytes: usize,
+    /// Frame of the system in which the rotation was last used.
+    last_used: u64,
+}
+
+impl LazyRotation {
+    /// Create the rotated image, centered in the square.
+    fn bake(&self) -> SpriteImage {
+        // Sprites that can't be rotated stay invisible in this rotation
+        let rotated = match self.algorithm.rotate(&self.source, self.degrees) {
+            Ok(rotated) => rotated,
+            Err(_) => return placeholder(),
+        };
+
+        // Cut off the parts of the rotated image that don't fit in the square
+        let side = self.side as i32;
+        let (width, height) = (rotated.width() as i32, rotated.height() as i32);
+        let (x_offset, y_offset) = ((side - width) / 2, (side - height) / 2);
+        let mut pixels = vec![self.source.mask_color(); self.side * self.side];
+        for y in 0..height {
+            let target_y = y + y_offset;
+            if target_y < 0 || target_y >= side {
+                continue;
+            }
+
+            for x in 0..width {
+                let target_x = x + x_offset;
+                if target_x >= 0 && target_x < side {
+                    pixels[(target_y * side + target_x) as usize] =
+                        rotated.pixels()[(y * width + x) as usize];
+                }
+            }
+        }
+
+        rotated.with_pixels(&pixels, self.side)
+    }
+}
+
+/// Empty image taking the place of a rotation that isn't created yet.
+fn placeholder() -> SpriteImage {
+    SpriteImage::from_buffer(&[], 0, 0)
+}
+
+/// Load a sprite buffer with a set amount of rotations that are only created when a sprite uses
+/// them, with the [`LazyRotationSystem`].
+///
+/// Loading is instant even for sprites with many rotations, and only the rotations that are
+/// used take up memory.
+pub fn load_rotations_lazy(
+    sprite: BlitBuffer,
+    rotations: u16,
+    algorithm: RotationAlgorithm,
+) -> Result<SpriteRef, SpriteError> {
+    let rotations = rotations.max(1);
+    let image = SpriteImage::from_blit_buffer(&sprite);
+
+    crate::validate_load(&image, (0, 360))?;
+
+    let rot_divisor = 360.0 / f64::from(rotations);
+    let (width, height) = (image.width() as i32, image.height() as i32);
+
+    // Every rotation fits in a square with the diagonal as its side, so the offsets are known
+    // before rotating
+    let side = f64::from(width).hypot(f64::from(height)).ceil() as usize;
+    let offset = ((width - side as i32) / 2, (height - side as i32) / 2);
+
+    // The unrotated version is stored right away
+    let source = Arc::new(image.clone());
+    let images = iter::once(image)
+        .chain((1..rotations).map(|_| placeholder()))
+        .collect();
+
+    let mut store = SPRITES.write().map_err(|_| SpriteError::StorePoisoned)?;
+    let slots = reclaim::store(&mut store, images)?;
+    let mut lazy = LAZY.lock().unwrap();
+    for (rotation, &slot) in slots.iter().enumerate().skip(1) {
+        lazy.insert(
+            slot,
+            LazyRotation {
+                source: source.clone(),
+                degrees: rotation as f64 * rot_divisor,
+                side,
+                algorithm: algorithm.clone(),
+                baked: false,
+                bytes: 0,
+                last_used: 0,
+            },
+        );
+    }
+
+    let sprites = slots
+        .iter()
+        .enumerate()
+        .map(|(rotation, &slot)| match rotation {
+            0 => (slot, 0, 0),
+            _ => (slot, offset.0, offset.1),
+        })
+        .collect();
+
+    Ok(SpriteRef {
+        rot_range_start: 0,
+        rot_divisor,
+        size: (width, height),
+        sprites,
+        lods: Vec::new(),
+        sockets: Arc::default(),
+        _slots: Arc::new(SlotGuard::new(slots)),
+    })
+}
+
+/// Memory in bytes taken up by the rotations created by the [`LazyRotationSystem`].
+pub fn lazy_rotation_bytes() -> usize {
+    LAZY.lock()
+        .unwrap()
+        .values()
+        .map(|rotation| rotation.bytes)
+        .sum()
+}
+
+/// Forget the rotations in the slots because they are freed, the store must be locked.
+pub(crate) fn forget(slots: &[usize]) {
+    let mut lazy = LAZY.lock().unwrap();
+    for slot in slots {
+        lazy.remove(slot);
+    }
+}
+
+/// Forget all rotations, called when the store is cleared.
+pub(crate) fn reset() {
+    LAZY.lock().unwrap().clear();
+}
+
+/// Specs system creating the rotations of sprites loaded with [`load_rotations_lazy`] that are
+/// used.
+///
+/// Run it before the [`RenderSystem`](crate::RenderSystem), a rotation that isn't created yet
+/// isn't drawn. With a memory limit the rotations that weren't used for the longest time are
+/// freed again, they are created again when they are used.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     lazy_rotation_bytes, load_rotations_lazy, LazyRotationSystem, PixelBuffer, RenderSystem,
+///     RotationAlgorithm, Sprite,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(16, 16));
+///
+/// // The rotations of a 4x4 sprite are 6x6 pixels, keep at most one in memory
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(LazyRotationSystem::default().with_max_bytes(6 * 6 * 4), "lazy_rotation", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let sprite = BlitBuffer::from_buffer(&[0xFF_FF_FF; 16], 4, 0xFF_00_FF);
+/// let sprite_ref = load_rotations_lazy(sprite, 8, RotationAlgorithm::NearestNeighbor)?;
+/// let mut sprite = Sprite::new(sprite_ref);
+/// sprite.set_pos(8, 8);
+/// sprite.set_rot(45);
+/// let entity = world.create_entity().with(sprite).build();
+/// assert_eq!(lazy_rotation_bytes(), 0);
+///
+/// dispatcher.dispatch(&world);
+/// assert_eq!(lazy_rotation_bytes(), 6 * 6 * 4);
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert!(buffer.pixels().iter().any(|pixel| pixel & 0xFF_FF_FF == 0xFF_FF_FF));
+/// drop(buffer);
+///
+/// // The rotation that isn't used anymore makes room for the new one
+/// world.write_storage::<Sprite>().get_mut(entity).unwrap().set_rot(90);
+/// dispatcher.dispatch(&world);
+/// assert_eq!(lazy_rotation_bytes(), 6 * 6 * 4);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct LazyRotationSystem {
+    /// Maximum memory in bytes taken up by the created rotations.
+    max_bytes: Option<usize>,
+    /// Amount of times the system ran.
+    frame: u64,
+}
+
+impl LazyRotationSystem {
+    /// Free the rotations that weren't used for the longest time when the created rotations take
+    /// up more memory in bytes.
+    ///
+    /// The rotations used in the current frame are always kept.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+
+        self
+    }
+}
+
+impl<'a> System<'a> for LazyRotationSystem {
+    type SystemData = (
+        ReadStorage<'a, Sprite>,
+        ReadStorage<'a, Instances>,
+        ReadStorage<'a, MultiSprite>,
+        Option<Read<'a, Camera>>,
+    );
+
+    fn run(&mut self, (sprites, instances, multi_sprites, camera): Self::SystemData) {
+        self.frame += 1;
+        let frame = self.frame;
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+
+        // Find the rotations that are used, the store isn't locked while rotating
+        let mut missing = HashMap::new();
+        {
+            let mut lazy = LAZY.lock().unwrap();
+            if lazy.is_empty() {
+                return;
+            }
+
+            for (sprite, instances, multi_sprite) in
+                (&sprites, instances.maybe(), multi_sprites.maybe()).join()
+            {
+                let parts = multi_sprite.map_or(&[][..], MultiSprite::parts);
+                let mut used_part = |reference: &SpriteRef, rot: i16| {
+                    let rotation_index = reference.rotation_index(camera.screen_rotation(rot));
+                    let (slot, _, _) = reference.sprites[rotation_index];
+                    if let Some(rotation) = lazy.get_mut(&slot) {
+                        rotation.last_used = frame;
+                        if !rotation.baked {
+                            missing.entry(slot).or_insert_with(|| rotation.clone());
+                        }
+                    }
+                };
+                let mut used = |rot: i16| {
+                    used_part(&sprite.reference, rot);
+                    for part in parts {
+                        used_part(&part.reference, part.world_rot(rot));
+                    }
+                };
+
+                used(sprite.rot);
+                for instance in instances.iter().flat_map(|instances| instances.as_slice()) {
+                    used(instance.rot.unwrap_or(sprite.rot));
+                }
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+
+        let baked: Vec<(usize, SpriteImage)> = missing
+            .into_iter()
+            .map(|(slot, rotation)| (slot, rotation.bake()))
+            .collect();
+
+        let mut store = SPRITES.write().unwrap();
+        let mut lazy = LAZY.lock().unwrap();
+        for (slot, image) in baked {
+            // The sprite can be reclaimed while it was rotated
+            if let Some(rotation) = lazy.get_mut(&slot) {
+                rotation.baked = true;
+                rotation.bytes = mem::size_of_val(image.pixels());
+                store[slot] = image;
+            }
+        }
+
+        let max_bytes = match self.max_bytes {
+            Some(max_bytes) => max_bytes,
+            None => return,
+        };
+        let mut total: usize = lazy.values().map(|rotation| rotation.bytes).sum();
+        if total <= max_bytes {
+            return;
+        }
+
+        // Free the rotations that weren't used for the longest time first
+        let mut evictable: Vec<(u64, usize)> = lazy
+            .iter()
+            .filter(|(_, rotation)| rotation.bytes > 0 && rotation.last_used < frame)
+            .map(|(&slot, rotation)| (rotation.last_used, slot))
+            .collect();
+        evictable.sort_unstable();
+        for (_, slot) in evictable {
+            if total <= max_bytes {
+                break;
+            }
+
+            if let Some(rotation) = lazy.get_mut(&slot) {
+                total -= rotation.bytes;
+                rotation.bytes = 0;
+                rotation.baked = false;
+                store[slot] = placeholder();
+            }
+        }
+    }
+}
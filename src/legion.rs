@@ -0,0 +1,98 @@
+//! Drawing sprites stored in a [legion](https://docs.rs/legion) world.
+//!
+//! The [`Sprite`] component is used the same way as in specs, together with the [`Instances`],
+//! [`MultiSprite`] and [`SpriteEffects`] components of the same entity. The sprites share the
+//! sprite store and are drawn by the same code as the [`RenderSystem`](crate::RenderSystem).
+//!
+//! ```rust
+//! use blit::BlitBuffer;
+//! use legion::{Resources, Schedule, World};
+//! use specs_blit::{load, Camera, PixelBuffer, Sprite, SpriteEffects};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut world = World::default();
+//! let mut resources = Resources::default();
+//! resources.insert(PixelBuffer::new(4, 4));
+//! resources.insert(Camera::new(-1, -1));
+//!
+//! let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+//! world.push((Sprite::new(sprite_ref.clone()),));
+//! let mut sprite = Sprite::new(sprite_ref);
+//! sprite.set_pos(2, 2);
+//! world.push((sprite, SpriteEffects::new().with_flash(0xFF_00_00)));
+//!
+//! let mut schedule = Schedule::builder()
+//!     .add_system(specs_blit::legion::render_system())
+//!     .build();
+//! schedule.execute(&mut world, &mut resources);
+//!
+//! let buffer = resources.get::<PixelBuffer>().unwrap();
+//! assert_eq!(buffer.get_pixel(1, 1), Some(0xFF_FF_FF_FF));
+//! assert_eq!(buffer.get_pixel(3, 3), Some(0xFF_FF_00_00));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    Camera, Instances, MultiSprite, PixelBuffer, PixelSnap, Sprite, SpriteDraw, SpriteEffects,
+};
+use legion::{systems::ParallelRunnable, EntityStore, IntoQuery, SystemBuilder};
+
+/// Components of an entity that are drawn.
+type Components<'a> = (
+    &'a Sprite,
+    Option<&'a SpriteEffects>,
+    Option<&'a Instances>,
+    Option<&'a MultiSprite>,
+);
+
+/// Draw the sprites of all entities in the world through the camera.
+///
+/// The sprites are drawn sorted by their layer and z-order, the positions of the sprites on the
+/// layers of the [`PixelSnap`] are snapped to the pixel grid.
+pub fn render<W>(
+    world: &W,
+    buffer: &mut PixelBuffer,
+    camera: &Camera,
+    pixel_snap: Option<&PixelSnap>,
+) where
+    W: EntityStore,
+{
+    let mut query = <Components>::query();
+    draw(query.iter(world), buffer, camera, pixel_snap);
+}
+
+/// Create a legion system drawing like [`render`] every time it's run, without snapping.
+///
+/// It draws into the [`PixelBuffer`] resource through the [`Camera`] resource, both must be
+/// inserted into the resources. Call [`render`] from a custom system to snap sprites to the
+/// pixel grid.
+pub fn render_system() -> impl ParallelRunnable {
+    SystemBuilder::new("specs_blit::legion::render_system")
+        .read_resource::<Camera>()
+        .write_resource::<PixelBuffer>()
+        .with_query(<Components>::query())
+        .build(|_, world, (camera, buffer), query| {
+            draw(query.iter(world), buffer, camera, None);
+        })
+}
+
+/// Draw the sprites with their components.
+fn draw<'a, I>(
+    components: I,
+    buffer: &mut PixelBuffer,
+    camera: &Camera,
+    pixel_snap: Option<&PixelSnap>,
+) where
+    I: Iterator<Item = Components<'a>>,
+{
+    let draws = components.map(|(sprite, effects, instances, multi_sprite)| {
+        SpriteDraw::new(sprite)
+            .with_effects(effects)
+            .with_instances(instances)
+            .with_multi_sprite(multi_sprite)
+            .with_snap(pixel_snap.is_some_and(|pixel_snap| pixel_snap.is_snapped(sprite.layer())))
+    });
+
+    buffer.draw_sprites_with(draws, camera);
+}
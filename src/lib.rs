@@ -6,12 +6,33 @@
 //! graphics libraries, e.g. minifb.
 //!
 //! All sprites are loaded onto a big array on the heap.
+//!
+//! Rotated versions of sprites are created with the rotsprite algorithm when the default
+//! `rotsprite` feature is enabled, without it they are rotated with nearest-neighbor sampling.
+//!
+//! The `mode7` feature adds a system drawing a big image as a perspective ground plane below the
+//! sprites.
+//!
+//! The `raycaster` feature adds a system drawing a grid of tiles as textured walls from a first
+//! person view, with sprites standing between them.
+//!
+//! The `console` feature adds a system drawing log lines over the frame, for targets where the
+//! standard output isn't visible.
+//!
+//! The `fontdue` feature adds [`Font::from_ttf`] rasterizing a TTF or OTF font at a pixel size,
+//! instead of drawing a bitmap font for every size.
+//!
+//! The `hecs` feature adds the [`hecs`](crate::hecs) module drawing the sprites of a hecs world
+//! with the same sprite store and pipeline.
+//!
+//! The `legion` feature adds the [`legion`](crate::legion) module drawing the sprites of a legion
+//! world with the same sprite store and pipeline.
+//!
 //! ```rust
 //! use anyhow::Result;
 //! use blit::{BlitBuffer, Color};
 //! use specs::prelude::*;
 //! use specs_blit::{load, PixelBuffer, RenderSystem, Sprite};
-//! use rotsprite::rotsprite;
 //!
 //! const WIDTH: usize = 800;
 //! const HEIGHT: usize = 800;
@@ -42,9 +63,12 @@
 //!
 //!     // Setup the dispatcher with the blit system
 //!     let mut dispatcher = DispatcherBuilder::new()
-//!         .with_thread_local(RenderSystem)
+//!         .with_thread_local(RenderSystem::default())
 //!         .build();
 //!
+//!     // Register the components used by the systems
+//!     dispatcher.setup(&mut world);
+//!
 //!     Ok(())
 //! }
 //! ```
@@ -52,18 +76,157 @@
 pub extern crate blit;
 pub extern crate specs;
 
-use anyhow::Result;
+mod anchor;
+mod animation;
+mod atlas;
+mod attach;
+mod background;
+mod blitter;
+mod budget;
+mod buffers;
+mod bundle;
+mod camera;
+mod clear;
+mod collision;
+mod color_key;
+mod compositor;
+#[cfg(feature = "console")]
+mod console;
+mod delta;
+mod depth;
+mod dither;
+mod draw;
+mod edge;
+mod effect;
+mod error;
+mod fog;
+mod format;
+mod handle;
+#[cfg(feature = "hecs")]
+pub mod hecs;
+mod hook;
+mod indicator;
+mod instance;
+mod lazy;
+#[cfg(feature = "legion")]
+pub mod legion;
+mod lighting;
+mod list;
+mod minimap;
+#[cfg(feature = "mode7")]
+mod mode7;
+mod multi;
+mod order;
+mod overdraw;
+mod palette;
+mod pass;
+mod pivot;
+mod post;
+mod profiler;
+mod queue;
+#[cfg(feature = "raycaster")]
+mod raycaster;
+mod reclaim;
+mod rotate;
+mod scale;
+mod snapshot;
+mod spatial;
+mod sprite_image;
+mod squash;
+pub mod testing;
+mod text;
+mod tilemap;
+mod time;
+mod ui;
+mod view;
+mod warning;
+mod weather;
+#[cfg(feature = "wgpu")]
+mod wgpu_texture;
+
+pub use anchor::Anchor;
+pub use animation::{Animation, AnimationSystem};
+pub use atlas::pack_sprites;
+pub use attach::{AttachedTo, AttachmentSystem};
+pub use background::{
+    ScrollingBackground, ScrollingBackgroundSystem, StarLayer, Starfield, StarfieldSystem,
+};
+pub use blitter::{Blitter, MaskedBlitter};
+pub use budget::{RenderBudget, RenderStats};
+pub use buffers::PixelBuffers;
+pub use bundle::{SpriteBuilderExt, SpriteBundleBuilder};
+pub use camera::{Camera, CameraFollow, CameraFollowSystem, PixelSnap};
+pub use clear::{ClearColor, ClearSystem, DrawnRegions};
+pub use collision::CollisionLayer;
+pub use color_key::{load_keyed, ColorKey};
+pub use compositor::{BlendMode, CompositeLayer, CompositorConfig, CompositorSystem, Stencil};
+#[cfg(feature = "console")]
+pub use console::{Console, ConsoleLog, ConsoleSystem};
+pub use delta::{FrameDelta, FrameDeltaEncoder};
+pub use depth::{Depth, DepthBuffer};
+pub use draw::SpriteDraw;
+pub use edge::{EdgeMode, EdgeModes};
+pub use effect::{
+    Blink, BlinkSystem, ColorAdjust, Dissolve, Flash, FlashSystem, Lifetime, LifetimeSystem,
+    PixelFn, Stipple, Wave, WaveDirection, WaveSystem,
+};
+pub use error::SpriteError;
+pub use fog::{FogOfWar, FogOfWarSystem, Visibility};
+pub use format::PixelFormat;
+pub use handle::{load_async, LoadingSprite, SpriteHandle, SpriteLoadSystem};
+pub use hook::RenderHook;
+pub use indicator::{OffscreenIndicator, OffscreenIndicatorSystem};
+pub use instance::{Instance, Instances};
+pub use lazy::{lazy_rotation_bytes, load_rotations_lazy, LazyRotationSystem};
+pub use lighting::{Emissive, Lighting, LightingSystem, NormalBuffer, NormalMap, PointLight};
+pub use list::{SpriteEffects, SpriteList, SpriteListRenderSystem};
+pub use minimap::{Minimap, MinimapMarker, MinimapSystem};
+#[cfg(feature = "mode7")]
+pub use mode7::{Mode7Plane, Mode7System};
+pub use multi::{MultiSprite, SpritePart};
+pub use overdraw::{OverdrawHeatmap, OverdrawHeatmapSystem};
+pub use palette::{Palette, PaletteSystem, Palettes};
+pub use pass::{RenderOrder, RenderPass, RenderPasses};
+pub use pivot::Pivot;
+pub use post::{RowOffsetFn, ScanlineDisplacement, ScanlineDisplacementSystem};
+pub use profiler::{RenderPhase, RenderProfiler};
+pub use queue::{DrawCommand, DrawQueue};
+#[cfg(feature = "raycaster")]
+pub use raycaster::{Billboard, RaycastSystem, Raycaster};
+pub use reclaim::{reclaim_sprites, set_max_sprites, SpriteReclaimSystem};
+pub use rotate::{RotateFn, RotationAlgorithm};
+pub use scale::ScaleAlgorithm;
+pub use snapshot::SnapshotError;
+pub use sprite_image::{AlphaMode, SpriteImage};
+pub use squash::{Easing, SquashStretch, SquashStretchSystem};
+pub use text::{FloatingText, FloatingTextSystem, Font, RasterGlyph, Text, TextRenderSystem};
+pub use tilemap::{HexOrientation, TileProjection, Tilemap, TilemapRenderSystem};
+pub use time::{PausedLayers, Time};
+pub use ui::{Bar, BarFill, Button, ButtonState, ButtonSystem, Cursor, Panel, UiRenderSystem};
+pub use view::PixelBufferView;
+pub use warning::RenderWarning;
+pub use weather::{Weather, WeatherKind, WeatherSystem};
+
 use blit::BlitBuffer;
+use effect::{EffectStorages, Effects};
 use lazy_static::lazy_static;
+use lighting::Surface;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use specs::prelude::*;
-use std::sync::RwLock;
+use reclaim::SlotGuard;
+use spatial::SpatialHash;
+use specs::{hibitset::BitSet, prelude::*, shrev::EventChannel};
+use std::{
+    collections::HashMap,
+    iter,
+    sync::{Arc, RwLock},
+};
+use web_time::Instant;
 
 // The heap allocated array of sprites
 // It's wrapped in a RwLock so all threads can access it
 lazy_static! {
-    static ref SPRITES: RwLock<Vec<BlitBuffer>> = RwLock::new(vec![]);
+    static ref SPRITES: RwLock<Vec<SpriteImage>> = RwLock::new(vec![]);
 }
 
 /// Specs component representing a sprite that can be drawn.
@@ -106,10 +269,24 @@ pub struct Sprite {
     /// The current rotation of the sprite, it will match the nearest rotating divisor of the
     /// loaded version.
     rot: i16,
+    /// Sprites on higher layers are drawn over sprites on lower layers.
+    layer: u8,
+    /// Order of the sprite within its layer, higher values are drawn on top.
+    z: i32,
+    /// The point the sprite rotates around.
+    pivot: Pivot,
+    /// The point of the sprite that is placed at the position.
+    anchor: Anchor,
+    /// The size the sprite is stretched to, if it's not drawn at its original size.
+    size: Option<(usize, usize)>,
+    /// Horizontal and vertical factor applied on top of the size.
+    scale: (f64, f64),
+    /// Color treated as transparent in addition to the mask color of the loaded sprite.
+    mask_color: Option<u32>,
 }
 
 impl Component for Sprite {
-    type Storage = VecStorage<Self>;
+    type Storage = FlaggedStorage<Self, VecStorage<Self>>;
 }
 
 impl Sprite {
@@ -140,10 +317,19 @@ impl Sprite {
             reference: sprite_reference,
             pos: (0, 0),
             rot: 0,
+            layer: 0,
+            z: 0,
+            pivot: Pivot::Center,
+            anchor: Anchor::TopLeft,
+            size: None,
+            scale: (1.0, 1.0),
+            mask_color: None,
         }
     }
 
     /// Set the pixel position of where the sprite needs to be rendered.
+    ///
+    /// This is where the anchor of the sprite will be placed, by default the top left corner.
     pub fn set_pos(&mut self, x: i32, y: i32) {
         self.pos.0 = x;
         self.pos.1 = y;
@@ -173,35 +359,478 @@ impl Sprite {
         self.rot
     }
 
-    /// Get the data needed for rendering this sprite.
-    pub(crate) fn render_info(&self) -> (usize, i32, i32) {
-        self.reference.render_info(self.rot)
+    /// Get the world position of the socket with the name on the sprite as it's drawn, with its
+    /// rotation, pivot, anchor and scale.
+    ///
+    /// See [`SpriteRef::with_socket`] for how to add sockets.
+    pub fn socket_pos(&self, name: &str) -> Option<(i32, i32)> {
+        let (x, y) = self.reference.socket_point(name, self.rot)?;
+
+        let (width, height) = self.reference.size;
+        let degrees = self
+            .reference
+            .degrees(self.reference.rotation_index(self.rot));
+        let (pivot_x, pivot_y) = self.pivot.offset(width, height, degrees);
+        let (anchor_x, anchor_y) = self.anchor.offset(width, height);
+        let (x_scale, y_scale) = self.render_scale();
+
+        Some((
+            self.pos.0 + ((x + f64::from(pivot_x - anchor_x)) * x_scale).floor() as i32,
+            self.pos.1 + ((y + f64::from(pivot_y - anchor_y)) * y_scale).floor() as i32,
+        ))
+    }
+
+    /// Set the layer of the sprite, sprites on higher layers are drawn over sprites on lower
+    /// layers.
+    pub fn set_layer(&mut self, layer: u8) {
+        self.layer = layer;
+    }
+
+    /// Get the layer the sprite is drawn on.
+    pub fn layer(&self) -> u8 {
+        self.layer
+    }
+
+    /// Set the order of the sprite within its layer, higher values are drawn on top.
+    pub fn set_z(&mut self, z: i32) {
+        self.z = z;
+    }
+
+    /// Get the order of the sprite within its layer.
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    /// Set the point the sprite rotates around, by default this is the center of the sprite.
+    pub fn set_pivot(&mut self, pivot: Pivot) {
+        self.pivot = pivot;
+    }
+
+    /// Get the point the sprite rotates around.
+    pub fn pivot(&self) -> Pivot {
+        self.pivot
+    }
+
+    /// Set the point of the sprite that is placed at its position, by default this is the top
+    /// left corner.
+    pub fn set_anchor(&mut self, anchor: Anchor) {
+        self.anchor = anchor;
+    }
+
+    /// Get the point of the sprite that is placed at its position.
+    pub fn anchor(&self) -> Anchor {
+        self.anchor
+    }
+
+    /// Stretch the sprite to the width and height in pixels when rendering it.
+    ///
+    /// The sprite is scaled with nearest-neighbor sampling, rotated versions are scaled by the
+    /// same factors.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs::prelude::*;
+    /// use specs_blit::{load, testing, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut world = World::new();
+    /// world.register::<Sprite>();
+    ///
+    /// // Stretch a single pixel into a bar of 8 by 2 pixels
+    /// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF0000], 1, 0xFF00FF))?);
+    /// sprite.set_size(8, 2);
+    /// world.create_entity().with(sprite).build();
+    ///
+    /// let buffer = testing::render(&mut world, 10, 10);
+    /// assert_eq!(buffer.pixels()[10 + 7] & 0xFFFFFF, 0xFF0000);
+    /// assert_eq!(buffer.pixels()[10 + 8], 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        self.size = Some((width, height));
+    }
+
+    /// Get the width and height in pixels the unrotated sprite is rendered with, before the
+    /// scale is applied.
+    pub fn size(&self) -> (usize, usize) {
+        self.size.unwrap_or((
+            self.reference.size.0 as usize,
+            self.reference.size.1 as usize,
+        ))
+    }
+
+    /// Multiply the width and height the sprite is rendered with by a factor.
+    ///
+    /// This is applied on top of [`Sprite::set_size`], a scale of `(1.0, 1.0)` renders the sprite
+    /// at its size.
+    pub fn set_scale(&mut self, x: f64, y: f64) {
+        self.scale = (x, y);
+    }
+
+    /// Get the horizontal and vertical factor the size is multiplied with.
+    pub fn scale(&self) -> (f64, f64) {
+        self.scale
+    }
+
+    /// Treat pixels with this color as transparent when rendering, in addition to the mask color
+    /// the sprite was loaded with.
+    ///
+    /// This allows reusing a sprite sheet where the transparent color differs per sprite. The
+    /// alpha channel of the color is ignored.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs::prelude::*;
+    /// use specs_blit::{load, testing, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut world = World::new();
+    /// world.register::<Sprite>();
+    ///
+    /// // A sprite with a green background that should not be drawn
+    /// let pixels = [0xFF0000, 0x00FF00];
+    /// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&pixels, 2, 0xFF00FF))?);
+    /// sprite.set_mask_color(Some(0x00FF00));
+    /// world.create_entity().with(sprite).build();
+    ///
+    /// let buffer = testing::render(&mut world, 2, 1);
+    /// assert_eq!(buffer.pixels()[0] & 0xFFFFFF, 0xFF0000);
+    /// assert_eq!(buffer.pixels()[1], 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_mask_color(&mut self, mask_color: Option<u32>) {
+        self.mask_color = mask_color;
+    }
+
+    /// Get the color treated as transparent in addition to the mask color of the loaded sprite.
+    pub fn mask_color(&self) -> Option<u32> {
+        self.mask_color
+    }
+
+    /// Get the horizontal and vertical factor the sprite is stretched with.
+    fn render_scale(&self) -> (f64, f64) {
+        let (width, height) = self.size();
+
+        (
+            width as f64 / f64::from(self.reference.size.0) * self.scale.0,
+            height as f64 / f64::from(self.reference.size.1) * self.scale.1,
+        )
+    }
+
+    /// Get the data needed for rendering this sprite when zoomed by the factor.
+    pub(crate) fn render_info(&self, zoom: f64) -> RenderInfo {
+        self.render_info_rotated(zoom, self.rot)
+    }
+
+    /// Get the data needed for rendering this sprite with another rotation when zoomed by the
+    /// factor.
+    pub(crate) fn render_info_rotated(&self, zoom: f64, rot: i16) -> RenderInfo {
+        self.render_info_of(&self.reference, zoom, rot)
+    }
+
+    /// Get the data needed for rendering another loaded sprite, e.g. a part of a
+    /// [`MultiSprite`], as if it was this sprite.
+    pub(crate) fn render_info_of(&self, full: &SpriteRef, zoom: f64, rot: i16) -> RenderInfo {
+        let (x_scale, y_scale) = self.render_scale();
+        let (x_scale, y_scale) = (x_scale * zoom, y_scale * zoom);
+
+        // Use a smaller version of the sprite when it's drawn much smaller
+        let reference = full.lod(x_scale.max(y_scale));
+        let (x_lod, y_lod) = (
+            f64::from(full.size.0) / f64::from(reference.size.0),
+            f64::from(full.size.1) / f64::from(reference.size.1),
+        );
+
+        let rotation_index = reference.rotation_index(rot);
+        let (index, x_offset, y_offset) = reference.sprites[rotation_index];
+
+        // Calculate the offsets in pixels of the full size sprite
+        let (pivot_x, pivot_y) =
+            self.pivot
+                .offset(full.size.0, full.size.1, reference.degrees(rotation_index));
+        let (anchor_x, anchor_y) = self.anchor.offset(full.size.0, full.size.1);
+        let x_offset = f64::from(x_offset) * x_lod + f64::from(pivot_x - anchor_x);
+        let y_offset = f64::from(y_offset) * y_lod + f64::from(pivot_y - anchor_y);
+
+        RenderInfo {
+            index,
+            offset: (
+                (x_offset * x_scale).round() as i32,
+                (y_offset * y_scale).round() as i32,
+            ),
+            scale: (x_scale * x_lod, y_scale * y_lod),
+        }
+    }
+}
+
+/// What's needed to draw a sprite.
+pub(crate) struct RenderInfo {
+    /// Index of the image in the sprite store.
+    index: usize,
+    /// Position of the image relative to the position of the sprite.
+    offset: (i32, i32),
+    /// Horizontal and vertical factor the image is stretched with.
+    scale: (f64, f64),
+}
+
+impl RenderInfo {
+    /// Get the size the image needs to be drawn with.
+    fn size(&self, image: &SpriteImage) -> (usize, usize) {
+        (
+            (image.width() as f64 * self.scale.0).round().max(0.0) as usize,
+            (image.height() as f64 * self.scale.1).round().max(0.0) as usize,
+        )
+    }
+}
+
+/// Where an image of a sprite is drawn on the buffer.
+pub(crate) struct Placement<'a> {
+    /// Part of the sprite the image belongs to, `None` for the sprite itself.
+    pub(crate) part: Option<&'a SpritePart>,
+    /// Rotation in degrees the image is drawn with on the buffer.
+    pub(crate) rot: i16,
+    /// Index of the image in the sprite store.
+    pub(crate) index: usize,
+    /// Position of the top left corner on the buffer.
+    pub(crate) pos: (i32, i32),
+    /// Size the image is drawn with.
+    pub(crate) size: (usize, usize),
+}
+
+impl Sprite {
+    /// Get where all images of the sprite are drawn through the camera, once for every instance
+    /// with the parts of the multi-sprite below and above it.
+    ///
+    /// The camera must have the size of the buffer as its viewport. The index is returned as an
+    /// error when an image isn't in the store anymore.
+    pub(crate) fn placements<'a>(
+        &'a self,
+        (instances, multi_sprite): (Option<&'a Instances>, Option<&'a MultiSprite>),
+        camera: &'a Camera,
+        snapped: bool,
+        images: &'a [SpriteImage],
+    ) -> impl Iterator<Item = Result<Placement<'a>, usize>> + 'a {
+        // Draw the sprite once at its position when it has no instances
+        let copies = match instances {
+            Some(instances) => instances.as_slice(),
+            None => &[Instance {
+                offset: (0, 0),
+                rot: None,
+            }],
+        };
+        // The parts of a composite are drawn around the sprite itself
+        let (below, above) = multi_sprite.map_or((&[][..], &[][..]), MultiSprite::split);
+
+        copies.iter().flat_map(move |instance| {
+            let instance_rot = instance.rot.unwrap_or(self.rot);
+            below
+                .iter()
+                .map(Some)
+                .chain(iter::once(None))
+                .chain(above.iter().map(Some))
+                .map(move |part| {
+                    let (reference, offset, rot) = match part {
+                        Some(part) => (
+                            &part.reference,
+                            part.world_offset(instance_rot, self.render_scale()),
+                            part.world_rot(instance_rot),
+                        ),
+                        None => (&self.reference, (0, 0), instance_rot),
+                    };
+
+                    let screen_rot = camera.screen_rotation(rot);
+                    let render_info = self.render_info_of(reference, camera.zoom(), screen_rot);
+                    let image = images.get(render_info.index).ok_or(render_info.index)?;
+                    let size = render_info.size(image);
+
+                    // The image is turned by the camera around its center as it's drawn without
+                    // the rotation of the camera
+                    let unrotated = if screen_rot == rot {
+                        (render_info.offset, size)
+                    } else {
+                        let render_info = self.render_info_of(reference, camera.zoom(), rot);
+                        let size = images
+                            .get(render_info.index)
+                            .map_or(size, |image| render_info.size(image));
+
+                        (render_info.offset, size)
+                    };
+                    let world_pos = (
+                        self.pos.0 + instance.offset.0 + offset.0,
+                        self.pos.1 + instance.offset.1 + offset.1,
+                    );
+
+                    Ok(Placement {
+                        part,
+                        rot: screen_rot,
+                        index: render_info.index,
+                        pos: camera.image_to_screen(world_pos, snapped, unrotated, size),
+                        size,
+                    })
+                })
+        })
     }
 }
 
 /// Reference to a heap-allocated sprite.
 /// Contains the index of the vector, only this crate is allowed to access this.
+///
+/// Clones share the sprite, when all of them are dropped its memory can be freed with
+/// [`reclaim_sprites`].
 #[derive(Debug, Clone)]
 pub struct SpriteRef {
     /// Start point of the rotation.
     rot_range_start: i16,
     /// In how many degrees the rotation is divided.
     rot_divisor: f64,
+    /// Width and height of the unrotated sprite.
+    size: (i32, i32),
     /// Array of different rotations sprite references with their position offsets.
     sprites: Vec<(usize, i32, i32)>,
+    /// Versions of half, a quarter, etc. of the size.
+    lods: Vec<SpriteRef>,
+    /// Named points of the unrotated sprite things can be attached to.
+    sockets: Arc<HashMap<String, (i32, i32)>>,
+    /// Releases the rotations when the last clone is dropped.
+    _slots: Arc<SlotGuard>,
 }
 
 impl SpriteRef {
+    /// Check whether the unrotated sprite fits in the buffer.
+    ///
+    /// Sprites that don't fit are still drawn, but only the part inside of the buffer can be
+    /// seen.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{load, PixelBuffer, SpriteError};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 8], 4, 0xFF_00_FF))?;
+    ///
+    /// assert!(sprite_ref.validate_against(&PixelBuffer::new(4, 2)).is_ok());
+    /// assert!(matches!(
+    ///     sprite_ref.validate_against(&PixelBuffer::new(2, 2)),
+    ///     Err(SpriteError::LargerThanBuffer { width: 4, height: 2, .. })
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_against(&self, buffer: &PixelBuffer) -> Result<(), SpriteError> {
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+        if width > buffer.width() || height > buffer.height() {
+            return Err(SpriteError::LargerThanBuffer {
+                width,
+                height,
+                buffer_width: buffer.width(),
+                buffer_height: buffer.height(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Name the pixel at the (x, y) coordinates of the unrotated sprite, so other sprites can be
+    /// attached to it with [`AttachedTo`].
+    ///
+    /// The coordinates may lie outside of the sprite. Clones made before don't get the socket.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{load_rotations, SpriteRef};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let sprite = BlitBuffer::from_buffer(&[0xFF_FF_FF; 8], 4, 0xFF_00_FF);
+    /// let sprite_ref = load_rotations(sprite, 4)?.with_socket("hand", 3, 0);
+    ///
+    /// // The point turns along with the rotated versions of the sprite
+    /// assert_eq!(sprite_ref.socket("hand", 0), Some((3, 0)));
+    /// assert_eq!(sprite_ref.socket("hand", 90), Some((2, 2)));
+    /// assert_eq!(sprite_ref.socket("foot", 0), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_socket<S>(mut self, name: S, x: i32, y: i32) -> Self
+    where
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.sockets).insert(name.into(), (x, y));
+
+        self
+    }
+
+    /// Get the pixel of the socket with the name in the rotated version closest to the rotation,
+    /// relative to the top left corner of the unrotated sprite.
+    pub fn socket(&self, name: &str, rotation: i16) -> Option<(i32, i32)> {
+        self.socket_point(name, rotation)
+            .map(|(x, y)| (x.floor() as i32, y.floor() as i32))
+    }
+
+    /// Get the center of the pixel of the socket in the rotated version closest to the rotation,
+    /// relative to the top left corner of the unrotated sprite.
+    fn socket_point(&self, name: &str, rotation: i16) -> Option<(f64, f64)> {
+        let &(x, y) = self.sockets.get(name)?;
+
+        // The rotated versions are centered on the center of the unrotated sprite
+        let (center_x, center_y) = (f64::from(self.size.0) / 2.0, f64::from(self.size.1) / 2.0);
+        let (dx, dy) = (f64::from(x) + 0.5 - center_x, f64::from(y) + 0.5 - center_y);
+        let degrees = self.degrees(self.rotation_index(rotation));
+        let (sin, cos) = degrees.to_radians().sin_cos();
+
+        Some((
+            center_x + dx * cos - dy * sin,
+            center_y + dx * sin + dy * cos,
+        ))
+    }
+
     // Return the reference index and the offsets of the position.
-    pub(crate) fn render_info(&self, rotation: i16) -> (usize, i32, i32) {
-        let rotation_index = (((rotation - self.rot_range_start) % 360) as f64) / self.rot_divisor;
+    pub(crate) fn render_info(&self, rotation: i16, pivot: Pivot) -> (usize, i32, i32) {
+        let rotation_index = self.rotation_index(rotation);
 
         // Return the proper sprite depending on the rotation
-        *self
-            .sprites
-            .get(rotation_index as usize)
-            // Get the sprite at the index or the first if that's not valid
-            .unwrap_or(&self.sprites[0])
+        let (index, x_offset, y_offset) = self.sprites[rotation_index];
+
+        // Move the sprite so it's rotated around the pivot instead of the center
+        let (pivot_x, pivot_y) =
+            pivot.offset(self.size.0, self.size.1, self.degrees(rotation_index));
+
+        (index, x_offset + pivot_x, y_offset + pivot_y)
+    }
+
+    /// Get the index of the rotated version closest to the rotation.
+    fn rotation_index(&self, rotation: i16) -> usize {
+        let rotation_index =
+            ((((rotation - self.rot_range_start) % 360) as f64) / self.rot_divisor) as usize;
+
+        // Get the sprite at the index or the first if that's not valid
+        if rotation_index < self.sprites.len() {
+            rotation_index
+        } else {
+            0
+        }
+    }
+
+    /// Get the rotation in degrees of a rotated version.
+    fn degrees(&self, rotation_index: usize) -> f64 {
+        f64::from(self.rot_range_start) + rotation_index as f64 * self.rot_divisor
+    }
+
+    /// Get the smallest downscaled version that's still big enough for the scale.
+    fn lod(&self, scale: f64) -> &SpriteRef {
+        let mut reference = self;
+        let mut lod_scale = 1.0;
+        for lod in self.lods.iter() {
+            lod_scale /= 2.0;
+            if scale > lod_scale {
+                break;
+            }
+
+            reference = lod;
+        }
+
+        reference
     }
 }
 
@@ -247,6 +876,56 @@ impl PixelBuffer {
         &mut self.pixels
     }
 
+    /// Iterate over the rows of pixels from top to bottom.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(3, 2);
+    /// for (y, row) in buffer.rows_mut().enumerate() {
+    ///     row.fill(y as u32);
+    /// }
+    ///
+    /// let sums: Vec<u32> = buffer.rows().map(|row| row.iter().sum()).collect();
+    /// assert_eq!(sums, [0, 3]);
+    /// ```
+    pub fn rows(&self) -> impl Iterator<Item = &[u32]> {
+        // An empty buffer has no rows, but chunks can't be empty
+        self.pixels.chunks_exact(self.width.max(1))
+    }
+
+    /// Iterate over the rows of pixels from top to bottom so they can be mutated.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u32]> {
+        self.pixels.chunks_exact_mut(self.width.max(1))
+    }
+
+    /// Iterate over the columns of pixels from left to right, every column from top to bottom.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(2, 3);
+    /// for pixel in buffer.column_mut(1) {
+    ///     *pixel = 1;
+    /// }
+    ///
+    /// let sums: Vec<u32> = buffer.columns().map(|column| column.sum()).collect();
+    /// assert_eq!(sums, [0, 3]);
+    /// ```
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &u32>> {
+        (0..self.width).map(move |x| self.pixels[x..].iter().step_by(self.width))
+    }
+
+    /// Iterate over the pixels of a column from top to bottom so they can be mutated, nothing is
+    /// iterated over when the column is outside of the buffer.
+    ///
+    /// Unlike the rows, the columns can't be mutated all at once because their pixels are
+    /// interleaved.
+    pub fn column_mut(&mut self, x: usize) -> impl Iterator<Item = &mut u32> {
+        let start = if x < self.width { x } else { self.pixels.len() };
+        self.pixels[start..].iter_mut().step_by(self.width.max(1))
+    }
+
     /// Get the width in pixels.
     pub fn width(&self) -> usize {
         self.width
@@ -267,38 +946,572 @@ impl PixelBuffer {
 
 /// Specs system for rendering sprites to a buffer.
 ///
-/// *Note*: This can only be used in conjunction with a `.with_thread_local()`
-/// function in specs and not with a normal `.with()` call.
+/// The sprites are drawn sorted by their layer and z-order, the view is configured with the
+/// [`Camera`] resource. A [`RenderBudget`] resource limits how much is drawn and the
+/// [`RenderStats`] resource is filled with what's drawn. The commands of the [`DrawQueue`]
+/// resource are drawn last.
+///
+/// Optional components like [`PixelFn`] are read by this system as well, call `setup` on the
+/// dispatcher so they are registered.
+///
+/// The system is `Send` when the blitter is, so it can be added with a normal `.with()` call as
+/// well as with `.with_thread_local()`. It writes to the [`PixelBuffer`] resource, so specs never
+/// runs it at the same time as other systems using the buffer, and those have to name it as a
+/// dependency to run after it. The sprite store is only read while it's drawing: loading sprites
+/// in a system that runs at the same time waits until the drawing is done.
 ///
 /// ```rust
 /// use specs::prelude::*;
-/// use specs_blit::RenderSystem;
+/// use specs_blit::{FogOfWarSystem, RenderSystem};
+///
+/// let mut world = World::new();
 ///
 /// let mut dispatcher = DispatcherBuilder::new()
 ///     // Expose the sprite render system to specs
-///     .with_thread_local(RenderSystem)
+///     .with(RenderSystem::default(), "render", &[])
+///     // Cover the drawn sprites afterwards
+///     .with(FogOfWarSystem, "fog", &["render"])
 ///     .build();
+///
+/// // Register all components the render system uses
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+/// ```
+///
+/// Sprites with the same layer and z-order are drawn in the order of their entity ids, not in the
+/// order they were created. The same world always results in the same frame, which is needed for
+/// replays and comparing frames in tests.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// let green = load(BlitBuffer::from_buffer(&[0x00_FF_00], 1, 0xFF_00_FF))?;
+/// let yellow = load(BlitBuffer::from_buffer(&[0xFF_FF_00], 1, 0xFF_00_FF))?;
+///
+/// // The yellow sprite is created last but reuses the lower id of a deleted entity
+/// let deleted = world.create_entity().build();
+/// world.create_entity().with(Sprite::new(green)).build();
+/// world.delete_entity(deleted)?;
+/// world.maintain();
+/// let reused = world.create_entity().with(Sprite::new(yellow)).build();
+/// assert_eq!(reused.id(), deleted.id());
+///
+/// // So the green sprite with the higher id is drawn on top
+/// let frame = testing::render(&mut world, 1, 1);
+/// assert_eq!(frame.pixels()[0] & 0xFF_FF_FF, 0x00_FF_00);
+/// # Ok(())
+/// # }
 /// ```
-pub struct RenderSystem;
-impl<'a> System<'a> for RenderSystem {
-    type SystemData = (Write<'a, PixelBuffer>, ReadStorage<'a, Sprite>);
+#[derive(Debug)]
+pub struct RenderSystem<B = MaskedBlitter> {
+    /// Writes the pixels of the sprites into the buffer.
+    blitter: B,
+    /// Grid of the sprite bounds to only look at the sprites near the view.
+    spatial_hash: Option<SpatialHash>,
+    /// Listens to the changed sprites to keep the grid up to date.
+    reader: Option<ReaderId<ComponentEvent>>,
+    /// Name of the buffer in the [`PixelBuffers`] resource to draw into instead.
+    target: Option<String>,
+    /// Width and height of the [`PixelBuffer`] resource created when setting up the system.
+    buffer_size: Option<(usize, usize)>,
+}
+
+impl Default for RenderSystem {
+    fn default() -> Self {
+        Self::new(MaskedBlitter)
+    }
+}
+
+impl<B: Blitter> RenderSystem<B> {
+    /// Create a render system that draws the sprites with a custom blitter.
+    ///
+    /// See [`Blitter`] for which sprites use [`Blitter::blit`] and which use [`Blitter::blend`].
+    pub fn new(blitter: B) -> Self {
+        Self {
+            blitter,
+            spatial_hash: None,
+            reader: None,
+            target: None,
+            buffer_size: None,
+        }
+    }
+
+    /// Insert a [`PixelBuffer`] resource with the width and height when `setup` is called on the
+    /// system and the world doesn't have one yet.
+    ///
+    /// Without it an empty buffer is inserted, which nothing can be drawn on.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs::prelude::*;
+    /// use specs_blit::{load, PixelBuffer, RenderSystem, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut world = World::new();
+    ///
+    /// let mut dispatcher = DispatcherBuilder::new()
+    ///     .with_thread_local(RenderSystem::default().with_buffer_size(320, 240))
+    ///     .build();
+    /// dispatcher.setup(&mut world);
+    /// assert_eq!(world.read_resource::<PixelBuffer>().width(), 320);
+    ///
+    /// // Sprites can be added right away, the component is registered
+    /// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+    /// world.create_entity().with(Sprite::new(sprite_ref)).build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_buffer_size(mut self, width: usize, height: usize) -> Self {
+        self.buffer_size = Some((width, height));
+
+        self
+    }
+
+    /// Draw into the buffer with the name in the [`PixelBuffers`] resource instead of the
+    /// [`PixelBuffer`] resource.
+    ///
+    /// The sprites are seen through the camera of that buffer when it has one. Only the system
+    /// drawing into the [`PixelBuffer`] resource draws the [`DrawQueue`] and updates the
+    /// [`RenderStats`] and the [`OverdrawHeatmap`]. Nothing is drawn when there's no buffer with
+    /// the name, a [`RenderWarning::MissingBuffer`] is sent instead. See [`PixelBuffers`] for an
+    /// example.
+    pub fn with_target<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.target = Some(name.into());
+
+        self
+    }
+
+    /// Keep the bounds of all sprites in a grid of cells of the size in world pixels, so only the
+    /// sprites near the view are looked at when rendering.
+    ///
+    /// This is useful for big worlds where most sprites are outside of the view. The grid is
+    /// updated when a [`Sprite`] component is changed, which only works when `setup` is called on
+    /// the system. Effects drawing further than a cell outside of the bounds of the sprite can be
+    /// cut off at the edge of the buffer.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs::prelude::*;
+    /// use specs_blit::{load, PixelBuffer, RenderSystem, Sprite};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut world = World::new();
+    /// world.insert(PixelBuffer::new(16, 16));
+    ///
+    /// let mut dispatcher = DispatcherBuilder::new()
+    ///     .with_thread_local(RenderSystem::default().with_spatial_hash(64))
+    ///     .build();
+    /// dispatcher.setup(&mut world);
+    ///
+    /// // Spread a lot of sprites over a big world
+    /// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+    /// for i in 0..10_000 {
+    ///     let mut sprite = Sprite::new(sprite_ref.clone());
+    ///     sprite.set_pos(i % 100 * 100, i / 100 * 100);
+    ///     world.create_entity().with(sprite).build();
+    /// }
+    ///
+    /// dispatcher.dispatch(&world);
+    /// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[0] & 0xFF_FF_FF, 0xFF_FF_FF);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_spatial_hash(mut self, cell_size: i32) -> Self {
+        self.spatial_hash = Some(SpatialHash::new(cell_size));
+
+        self
+    }
+
+    /// Update the grid with the changed sprites and get the ids of the sprites that might be
+    /// visible, `None` when there's no grid.
+    fn query_spatial_hash(
+        &mut self,
+        entities: &Entities,
+        sprites: &ReadStorage<Sprite>,
+        camera: &Camera,
+        buffer_size: (usize, usize),
+        sprite_images: &[SpriteImage],
+    ) -> Option<BitSet> {
+        let (spatial_hash, reader) = match (self.spatial_hash.as_mut(), self.reader.as_mut()) {
+            (Some(spatial_hash), Some(reader)) => (spatial_hash, reader),
+            _ => return None,
+        };
+
+        let mut changed = BitSet::new();
+        for event in sprites.channel().read(reader) {
+            match event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    changed.add(*id);
+                }
+                ComponentEvent::Removed(id) => {
+                    changed.remove(*id);
+                    spatial_hash.remove(*id);
+                }
+            }
+        }
+        for (entity, sprite, _) in (entities, sprites, &changed).join() {
+            spatial_hash.insert_sprite(entity, sprite, sprite_images);
+        }
 
-    fn run(&mut self, (mut buffer, sprites): Self::SystemData) {
-        let width = buffer.width;
+        // The part of the world that's in view, with a margin for effects and rounding
+        let zoom = camera.zoom();
+        if zoom <= 0.0 {
+            return Some(BitSet::new());
+        }
+        let margin = spatial_hash.cell_size();
+        let (x, y) = camera.pos();
+        let (width, height) = (buffer_size.0 as f64 / zoom, buffer_size.1 as f64 / zoom);
+        if camera.rotation() != 0.0 {
+            // A turned view can show everything within the distance of its corners to the center
+            let (center_x, center_y) = (f64::from(x) + width / 2.0, f64::from(y) + height / 2.0);
+            let radius = width.hypot(height) / 2.0;
+
+            return Some(spatial_hash.query((
+                (center_x - radius).floor() as i32 - margin,
+                (center_y - radius).floor() as i32 - margin,
+                (center_x + radius).ceil() as i32 + margin,
+                (center_y + radius).ceil() as i32 + margin,
+            )));
+        }
+        Some(spatial_hash.query((
+            x - margin,
+            y - margin,
+            x + width.ceil() as i32 + margin,
+            y + height.ceil() as i32 + margin,
+        )))
+    }
+}
+
+impl<'a, B: Blitter> System<'a> for RenderSystem<B> {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Entities<'a>,
+        ReadStorage<'a, Sprite>,
+        Option<Read<'a, Camera>>,
+        Option<Read<'a, DepthBuffer>>,
+        EffectStorages<'a>,
+        Option<Write<'a, EventChannel<RenderWarning>>>,
+        Option<Write<'a, RenderProfiler>>,
+        Option<Read<'a, RenderBudget>>,
+        Option<Write<'a, RenderStats>>,
+        Option<Write<'a, OverdrawHeatmap>>,
+        ReadStorage<'a, Instances>,
+        Option<Read<'a, DrawQueue>>,
+        Option<Write<'a, PixelBuffers>>,
+        ReadStorage<'a, NormalMap>,
+        ReadStorage<'a, Emissive>,
+        Option<Write<'a, NormalBuffer>>,
+        Option<Read<'a, PixelSnap>>,
+        Option<Read<'a, EdgeModes>>,
+        ReadStorage<'a, RenderHook>,
+        ReadStorage<'a, MultiSprite>,
+        Option<Write<'a, DrawnRegions>>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        if let Some((width, height)) = self.buffer_size {
+            if !world.has_value::<PixelBuffer>() {
+                world.insert(PixelBuffer::new(width, height));
+            }
+        }
+
+        // Register all components and insert the missing resources
+        Self::SystemData::setup(world);
+
+        if let Some(spatial_hash) = self.spatial_hash.as_mut() {
+            let mut sprites = world.write_storage::<Sprite>();
+            self.reader = Some(sprites.register_reader());
+
+            // Sprites that were created before can't be seen in the events
+            let sprite_images = SPRITES.read().unwrap();
+            for (entity, sprite) in (&world.entities(), &sprites).join() {
+                spatial_hash.insert_sprite(entity, sprite, &sprite_images);
+            }
+        }
+    }
 
-        for sprite_component in sprites.join() {
-            let (index, x_offset, y_offset) = sprite_component.render_info();
+    fn run(
+        &mut self,
+        (
+            mut main_buffer,
+            entities,
+            sprites,
+            camera,
+            depth_buffer,
+            effect_storages,
+            mut warnings,
+            profiler,
+            budget,
+            stats,
+            mut heatmap,
+            instances,
+            draw_queue,
+            mut pixel_buffers,
+            normal_maps,
+            emissives,
+            normal_buffer,
+            pixel_snap,
+            edge_modes,
+            hooks,
+            multi_sprites,
+            drawn_regions,
+        ): Self::SystemData,
+    ) {
+        let start = Instant::now();
 
-            // Get the sprite from the array
-            let sprite = &SPRITES.read().unwrap()[index];
+        // Lock the sprite store once for the whole frame, they don't exist anymore when the
+        // sprites are cleared
+        let sprite_images = SPRITES.read().unwrap();
 
-            let pos = (
-                sprite_component.pos.0 + x_offset,
-                sprite_component.pos.1 + y_offset,
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+
+        // Draw into the named buffer through its own camera when there's a target
+        let (buffer, mut camera) = match self.target.as_deref() {
+            Some(name) => match pixel_buffers
+                .as_mut()
+                .and_then(|buffers| buffers.target_mut(name))
+            {
+                Some((buffer, target_camera)) => (buffer, target_camera.cloned().unwrap_or(camera)),
+                None => {
+                    if let Some(warnings) = warnings.as_mut() {
+                        warnings.single_write(RenderWarning::MissingBuffer {
+                            name: name.to_string(),
+                        });
+                    }
+
+                    return;
+                }
+            },
+            None => (&mut *main_buffer, camera),
+        };
+        let is_main = self.target.is_none();
+
+        // A turned camera rotates around the center of the buffer
+        camera.set_viewport(buffer.width(), buffer.height());
+
+        // The normals are only kept for the main buffer
+        let mut normal_buffer = normal_buffer.filter(|_| is_main);
+        if let Some(normal_buffer) = normal_buffer.as_mut() {
+            normal_buffer.reset(buffer.width(), buffer.height());
+        }
+
+        // Only look at the sprites near the view when they are kept in a grid, the bounds of the
+        // instances aren't known so they are always looked at
+        let candidates = self
+            .query_spatial_hash(
+                &entities,
+                &sprites,
+                &camera,
+                (buffer.width(), buffer.height()),
+                &sprite_images,
+            )
+            .map(|mut candidates| {
+                candidates |= instances.mask();
+                candidates |= multi_sprites.mask();
+
+                candidates
+            });
+        let queried_at = Instant::now();
+
+        let sorted = order::sort(match &candidates {
+            Some(candidates) => (&entities, &sprites, candidates)
+                .join()
+                .map(|(entity, sprite, _)| (entity, sprite))
+                .collect(),
+            None => (&entities, &sprites).join().collect(),
+        });
+        let total = match &candidates {
+            // Counting all sprites is only needed for the statistics
+            Some(_) if stats.is_some() => sprites.join().count(),
+            _ => sorted.len(),
+        };
+        let sorted_at = Instant::now();
+
+        let (buffer_width, buffer_height) = (buffer.width() as i32, buffer.height() as i32);
+        // Sprites that aren't near the view are culled without looking at them
+        let mut culled = total - sorted.len();
+        let mut visible = Vec::with_capacity(sorted.len());
+        'sprites: for (entity, sprite_component) in sorted {
+            let effects = Effects::get(&effect_storages, depth_buffer.as_deref(), entity)
+                .with_mask_color(sprite_component.mask_color);
+            let overhang = effects.overhang();
+            let snapped = pixel_snap
+                .as_ref()
+                .is_some_and(|pixel_snap| pixel_snap.is_snapped(sprite_component.layer));
+
+            let placements = sprite_component.placements(
+                (instances.get(entity), multi_sprites.get(entity)),
+                &camera,
+                snapped,
+                &sprite_images,
             );
+            for placement in placements {
+                let Placement {
+                    part,
+                    rot,
+                    index,
+                    pos,
+                    size,
+                } = match placement {
+                    Ok(placement) => placement,
+                    Err(index) => {
+                        if let Some(warnings) = warnings.as_mut() {
+                            warnings.single_write(RenderWarning::InvalidSprite { entity, index });
+                        }
+
+                        continue 'sprites;
+                    }
+                };
+                let sprite = &sprite_images[index];
+
+                if let Some(warnings) = warnings.as_mut() {
+                    if size.0 == 0 || size.1 == 0 {
+                        warnings.single_write(RenderWarning::EmptySprite { entity, index });
+                    } else if size.0 as i32 > buffer_width || size.1 as i32 > buffer_height {
+                        warnings.single_write(RenderWarning::OversizedSprite {
+                            entity,
+                            size,
+                            buffer_size: (buffer_width as usize, buffer_height as usize),
+                        });
+                    }
+                }
+
+                // The lighting components are only needed when the normals are kept, they belong
+                // to the sprite and not to its parts
+                let surface = normal_buffer.as_ref().map(|_| match part {
+                    Some(_) => Surface::new(None, None, rot, &sprite_images),
+                    None => Surface::new(
+                        normal_maps.get(entity),
+                        emissives.get(entity),
+                        rot,
+                        &sprite_images,
+                    ),
+                });
+
+                // A sprite can be drawn more than once when it crosses a wrapping or mirroring
+                // edge
+                let edge_mode = edge_modes.as_ref().map_or(EdgeMode::Clip, |edge_modes| {
+                    edge_modes.get(sprite_component.layer)
+                });
+                for (pos, flip) in edge_mode.positions(pos, size, (buffer_width, buffer_height)) {
+                    // Skip the sprites that fall completely outside of the buffer
+                    if pos.0 - overhang >= buffer_width
+                        || pos.1 - overhang >= buffer_height
+                        || pos.0 + size.0 as i32 + overhang <= 0
+                        || pos.1 + size.1 as i32 + overhang <= 0
+                    {
+                        culled += 1;
+                        continue;
+                    }
+
+                    visible.push((
+                        effects.clone().with_flip(flip),
+                        sprite,
+                        pos,
+                        size,
+                        sprite_component.layer,
+                        surface,
+                        hooks.get(entity),
+                    ));
+                }
+            }
+        }
+
+        // Skip the lowest priority layers when there's too much to draw
+        let dropped_layers = match budget {
+            Some(budget) => budget.layers_to_drop(
+                &visible
+                    .iter()
+                    .map(|(_, _, _, size, layer, _, _)| (*layer, size.0 * size.1))
+                    .collect::<Vec<_>>(),
+            ),
+            None => Vec::new(),
+        };
+        let before_drop = visible.len();
+        visible.retain(|(_, _, _, _, layer, _, _)| !dropped_layers.contains(layer));
+        let culled_at = Instant::now();
+
+        if let Some(mut stats) = stats.filter(|_| is_main) {
+            *stats = RenderStats {
+                sprites_drawn: visible.len(),
+                pixels_drawn: visible
+                    .iter()
+                    .map(|(_, _, _, size, _, _, _)| size.0 * size.1)
+                    .sum(),
+                sprites_culled: culled,
+                sprites_dropped: before_drop - visible.len(),
+                dropped_layers,
+            };
+        }
+
+        let buffer_size = (buffer.width(), buffer.height());
+        // Only the parts of the main buffer that are drawn on are cleared again
+        let mut drawn_regions = drawn_regions.filter(|_| is_main);
+        for (effects, sprite, pos, size, _, surface, hook) in visible {
+            if let Some(hook) = hook {
+                hook.before(buffer, pos, size);
+            }
 
             // Draw the sprite on the buffer
-            sprite.blit(&mut buffer.pixels, width, pos);
+            effects.blit(&self.blitter, sprite, buffer, pos, size);
+
+            if let Some(hook) = hook {
+                hook.after(buffer, pos, size);
+            }
+
+            if let (Some(normal_buffer), Some(surface)) = (normal_buffer.as_mut(), surface) {
+                normal_buffer.draw(sprite, surface, pos, size, effects.flip());
+            }
+
+            if let Some(heatmap) = heatmap.as_mut().filter(|_| is_main) {
+                heatmap.add(sprite, pos, size, buffer_size);
+            }
+
+            if let Some(drawn_regions) = drawn_regions.as_mut() {
+                // Effects can draw outside of the bounds of the sprite
+                let overhang = effects.overhang();
+                drawn_regions.add(
+                    (pos.0 - overhang, pos.1 - overhang),
+                    (
+                        size.0 + overhang as usize * 2,
+                        size.1 + overhang as usize * 2,
+                    ),
+                    buffer_size,
+                );
+            }
+        }
+
+        // One-shot commands are drawn over the sprites
+        if let Some(draw_queue) = draw_queue.filter(|_| is_main) {
+            let commands = draw_queue.take_sorted();
+            // Where the commands are drawn isn't known, so everything is cleared
+            if let Some(drawn_regions) = drawn_regions.as_mut().filter(|_| !commands.is_empty()) {
+                drawn_regions.invalidate();
+            }
+
+            for (_, command) in commands {
+                command.draw(buffer, &camera, &self.blitter, &sprite_images);
+            }
+        }
+
+        if let Some(mut profiler) = profiler {
+            let end = Instant::now();
+            profiler.record(RenderPhase::Sort, sorted_at - queried_at);
+            profiler.record(
+                RenderPhase::Cull,
+                queried_at - start + (culled_at - sorted_at),
+            );
+            profiler.record(RenderPhase::Blit, end - culled_at);
+            profiler.record_system("RenderSystem", end - start);
         }
     }
 }
@@ -322,7 +1535,7 @@ impl<'a> System<'a> for RenderSystem {
 /// # Ok(())
 /// # }
 /// ```
-pub fn load(sprite: BlitBuffer) -> Result<SpriteRef> {
+pub fn load(sprite: BlitBuffer) -> Result<SpriteRef, SpriteError> {
     load_rotations(sprite, 1)
 }
 
@@ -331,7 +1544,7 @@ pub fn load(sprite: BlitBuffer) -> Result<SpriteRef> {
 /// Calls `load_rotations_range` with a range of `(0.0, 360.0)`.
 ///
 /// Returns an index that can be used in sprite components.
-pub fn load_rotations(sprite: BlitBuffer, rotations: u16) -> Result<SpriteRef> {
+pub fn load_rotations(sprite: BlitBuffer, rotations: u16) -> Result<SpriteRef, SpriteError> {
     load_rotations_range(sprite, rotations, (0, 360))
 }
 
@@ -341,7 +1554,7 @@ pub fn load_rotations(sprite: BlitBuffer, rotations: u16) -> Result<SpriteRef> {
 ///
 /// ```rust
 /// use blit::{BlitBuffer, Color};
-/// use specs_blit::load;
+/// use specs_blit::load_rotations_range;
 ///
 /// const MASK_COLOR: u32 = 0xFF00FF;
 ///
@@ -350,107 +1563,444 @@ pub fn load_rotations(sprite: BlitBuffer, rotations: u16) -> Result<SpriteRef> {
 /// let sprite = BlitBuffer::from_buffer(&[0, MASK_COLOR, 0, 0], 2, MASK_COLOR);
 ///
 /// // Load the sprite in rotations of -15, 0, 15 degrees and get a reference
-/// let sprite_ref = load_rotations_range(sprite, 3, (-15.0, 15.0))?;
+/// let sprite_ref = load_rotations_range(sprite, 3, (-15, 15))?;
 /// # Ok(())
 /// # }
 /// ```
-#[cfg(not(feature = "parallel"))]
 pub fn load_rotations_range(
     sprite: BlitBuffer,
     rotations: u16,
     range: (i16, i16),
-) -> Result<SpriteRef> {
-    let rotations = if rotations == 0 { 1 } else { rotations };
+) -> Result<SpriteRef, SpriteError> {
+    load_rotations_with(sprite, rotations, range, RotationAlgorithm::default())
+}
 
-    let rot_divisor = (range.1 - range.0) as f64 / (rotations as f64);
-    let raw_buffer = sprite.to_raw_buffer();
+/// Load a sprite buffer and place it onto the heap with a set amount of rotations, created with
+/// the rotation algorithm.
+///
+/// Returns an index that can be used in sprite components.
+///
+/// ```rust
+/// use blit::{BlitBuffer, Color};
+/// use specs_blit::{load_rotations_with, RotationAlgorithm};
+///
+/// const MASK_COLOR: u32 = 0xFF00FF;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// // Create a sprite of 4 pixels
+/// let sprite = BlitBuffer::from_buffer(&[0, MASK_COLOR, 0, 0], 2, MASK_COLOR);
+///
+/// // Load the sprite in 8 rotations using the fast nearest-neighbor rotation
+/// let sprite_ref = load_rotations_with(sprite, 8, (0, 360), RotationAlgorithm::NearestNeighbor)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_rotations_with(
+    sprite: BlitBuffer,
+    rotations: u16,
+    range: (i16, i16),
+    algorithm: RotationAlgorithm,
+) -> Result<SpriteRef, SpriteError> {
+    load_image(
+        SpriteImage::from_blit_buffer(&sprite),
+        rotations,
+        range,
+        &algorithm,
+    )
+}
 
-    // Create a rotation sprite for all rotations
-    let sprites = (0..rotations)
-        .into_iter()
-        .map(|r| {
-            let (rotated_width, rotated_height, rotated) = rotsprite::rotsprite(
-                &raw_buffer,
-                &sprite.mask_color().u32(),
-                sprite.size().0 as usize,
-                range.0 as f64 + (r as f64 * rot_divisor),
-            )?;
+/// Load pixels with an alpha channel in the `0xAARRGGBB` format and place them onto the heap with
+/// a set amount of rotations.
+///
+/// Anti-aliased edges are blended with what's behind them instead of being drawn as a solid
+/// color, unless the alpha is thresholded with [`AlphaMode::Threshold`].
+///
+/// Returns an index that can be used in sprite components.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{load_rgba, testing, AlphaMode, PixelBuffer, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // A red pixel, a half transparent red pixel and a transparent pixel
+/// let pixels = [0xFF_FF_00_00, 0x80_FF_00_00, 0x00_FF_00_00];
+/// world
+///     .create_entity()
+///     .with(Sprite::new(load_rgba(&pixels, 3, 1, AlphaMode::Blend)?))
+///     .build();
+///
+/// let buffer = testing::render(&mut world, 3, 1);
+/// assert_eq!(buffer.pixels()[0] & 0xFFFFFF, 0xFF_00_00);
+/// assert_eq!(buffer.pixels()[1] & 0xFFFFFF, 0x80_00_00);
+/// assert_eq!(buffer.pixels()[2], 0);
+///
+/// // Premultiplied alpha results in the same colors
+/// world.delete_all();
+/// world
+///     .create_entity()
+///     .with(Sprite::new(load_rgba(&pixels, 3, 1, AlphaMode::Premultiplied)?))
+///     .build();
+///
+/// let buffer = testing::render(&mut world, 3, 1);
+/// assert_eq!(buffer.pixels()[1] & 0xFFFFFF, 0x80_00_00);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_rgba(
+    pixels: &[u32],
+    width: usize,
+    rotations: u16,
+    alpha_mode: AlphaMode,
+) -> Result<SpriteRef, SpriteError> {
+    let image = match alpha_mode {
+        AlphaMode::Blend => SpriteImage::from_rgba(pixels, width),
+        AlphaMode::Threshold(cutoff) => {
+            let pixels: Vec<u32> = pixels
+                .iter()
+                .map(|&pixel| {
+                    if (pixel >> 24) as u8 >= cutoff.max(1) {
+                        pixel | 0xFF_00_00_00
+                    } else {
+                        0
+                    }
+                })
+                .collect();
 
-            let rotated_sprite =
-                BlitBuffer::from_buffer(&rotated, rotated_width as i32, sprite.mask_color());
+            SpriteImage::from_rgba(&pixels, width)
+        }
+        AlphaMode::Premultiplied => SpriteImage::from_rgba(pixels, width).premultiply(),
+    };
 
-            let mut sprites_vec = SPRITES.write().unwrap();
-            sprites_vec.push(rotated_sprite);
+    load_image(image, rotations, (0, 360), &RotationAlgorithm::default())
+}
 
-            let index = sprites_vec.len() - 1;
+/// Load a sprite buffer whose colors are added to what's behind it when drawn, and place it
+/// onto the heap with a set amount of rotations.
+///
+/// Black pixels don't change anything and the colors saturate to white, which makes it useful
+/// for lights, projectiles and explosions. Use [`load_glow`] for a ready-made light.
+///
+/// Returns an index that can be used in sprite components.
+pub fn load_additive(sprite: BlitBuffer, rotations: u16) -> Result<SpriteRef, SpriteError> {
+    load_image(
+        SpriteImage::from_blit_buffer(&sprite).additive(),
+        rotations,
+        (0, 360),
+        &RotationAlgorithm::default(),
+    )
+}
 
-            let x_offset = (sprite.width() - rotated_width as i32) / 2;
-            let y_offset = (sprite.height() - rotated_height as i32) / 2;
+/// Create a round glow of the color that fades out towards the radius, and place it onto the
+/// heap.
+///
+/// The glow is drawn additively like sprites loaded with [`load_additive`].
+///
+/// Returns an index that can be used in sprite components.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{load_glow, testing, Anchor, PixelBuffer, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// let mut glow = Sprite::new(load_glow(4, 0xFF_80_00)?);
+/// glow.set_anchor(Anchor::Center);
+/// glow.set_pos(5, 5);
+/// world.create_entity().with(glow).build();
+///
+/// let buffer = testing::render(&mut world, 10, 10);
+/// // The center is the brightest
+/// assert!(buffer.pixels()[5 * 10 + 5] & 0xFF_00_00 > buffer.pixels()[5 * 10 + 7] & 0xFF_00_00);
+/// // Outside of the radius nothing is drawn
+/// assert_eq!(buffer.pixels()[0], 0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_glow(radius: u32, color: u32) -> Result<SpriteRef, SpriteError> {
+    let size = radius as usize * 2;
+    let center = radius as f64;
+
+    let pixels: Vec<u32> = (0..size * size)
+        .map(|index| {
+            // Distance from the center of the pixel to the center of the glow
+            let x = (index % size) as f64 + 0.5 - center;
+            let y = (index / size) as f64 + 0.5 - center;
+            let distance = (x * x + y * y).sqrt() / center;
+
+            // Fade out quadratically so the center stays bright
+            let intensity = (1.0 - distance).max(0.0).powi(2);
+            let alpha = (intensity * 255.0).round() as u32;
 
-            Ok((index, x_offset, y_offset))
+            (alpha << 24) | (color & 0xFF_FF_FF)
         })
-        .collect::<Result<Vec<_>>>()?
-        // Return the first error
-        .into_iter()
-        .collect::<_>();
+        .collect();
 
-    Ok(SpriteRef {
-        rot_range_start: range.0,
-        rot_divisor,
-        sprites,
-    })
+    load_image(
+        SpriteImage::from_rgba(&pixels, size).additive(),
+        1,
+        (0, 360),
+        &RotationAlgorithm::default(),
+    )
 }
 
-#[cfg(feature = "parallel")]
-pub fn load_rotations_range(
+/// Load a sprite buffer upscaled with a pixel art scaling algorithm and place it onto the heap
+/// with a set amount of rotations.
+///
+/// The rotations are created from the upscaled sprite.
+///
+/// Returns an index that can be used in sprite components.
+///
+/// ```rust
+/// use blit::{BlitBuffer, Color};
+/// use specs::prelude::*;
+/// use specs_blit::{load_scaled, ScaleAlgorithm, Sprite};
+///
+/// const MASK_COLOR: u32 = 0xFF00FF;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// // Create a sprite of 4 pixels
+/// let sprite = BlitBuffer::from_buffer(&[0, MASK_COLOR, 0, 0], 2, MASK_COLOR);
+///
+/// // Load the sprite at double the size without rotations
+/// let sprite_ref = load_scaled(sprite, ScaleAlgorithm::Scale2x, 1)?;
+/// assert_eq!(Sprite::new(sprite_ref).size(), (4, 4));
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_scaled(
     sprite: BlitBuffer,
+    algorithm: ScaleAlgorithm,
+    rotations: u16,
+) -> Result<SpriteRef, SpriteError> {
+    let image = SpriteImage::from_blit_buffer(&sprite);
+    validate_load(&image, (0, 360))?;
+
+    load_image(
+        algorithm.apply(&image),
+        rotations,
+        (0, 360),
+        &RotationAlgorithm::default(),
+    )
+}
+
+/// Load a sprite buffer with a set amount of rotations, together with versions of half and a
+/// quarter of the size.
+///
+/// When the sprite is drawn at half the size or smaller, for example because the [`Camera`] is
+/// zoomed out, the render system uses the smaller version.
+///
+/// Returns an index that can be used in sprite components.
+///
+/// ```rust
+/// use blit::{BlitBuffer, Color};
+/// use specs::prelude::*;
+/// use specs_blit::{load_with_lods, testing, Camera, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // Load a big red sprite, the smaller versions are generated from it
+/// let sprite = BlitBuffer::from_buffer(&[0xFF0000; 64 * 64], 64, 0xFF00FF);
+/// world.create_entity().with(Sprite::new(load_with_lods(sprite, 1)?)).build();
+///
+/// // Zoom out so the sprite is drawn using the quarter size version
+/// let mut camera = Camera::default();
+/// camera.set_zoom(0.25);
+/// world.insert(camera);
+///
+/// let buffer = testing::render(&mut world, 32, 32);
+/// assert_eq!(buffer.pixels()[15 * 32 + 15] & 0xFFFFFF, 0xFF0000);
+/// assert_eq!(buffer.pixels()[16 * 32 + 16], 0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_with_lods(sprite: BlitBuffer, rotations: u16) -> Result<SpriteRef, SpriteError> {
+    let image = SpriteImage::from_blit_buffer(&sprite);
+    let half = scale::downscale_half(&image);
+    let quarter = half.as_ref().and_then(scale::downscale_half);
+
+    let mut sprite_ref = load_image(image, rotations, (0, 360), &RotationAlgorithm::default())?;
+    for lod in half.into_iter().chain(quarter) {
+        sprite_ref.lods.push(load_image(
+            lod,
+            rotations,
+            (0, 360),
+            &RotationAlgorithm::default(),
+        )?);
+    }
+
+    Ok(sprite_ref)
+}
+
+/// Load a sprite buffer without the rows and columns at its borders that are fully
+/// transparent, and place it onto the heap with a set amount of rotations.
+///
+/// Sprites are drawn at the same position as with [`load_rotations`], but the transparent
+/// padding doesn't take up memory and isn't looked at when drawing. Sprites without visible
+/// pixels are kept as they are.
+///
+/// Returns an index that can be used in sprite components.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load_trimmed, testing, Sprite};
+///
+/// const MASK_COLOR: u32 = 0xFF00FF;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // A single visible pixel with a lot of padding
+/// let mut pixels = [MASK_COLOR; 16];
+/// pixels[2 * 4 + 1] = 0xFFFFFF;
+/// let sprite_ref = load_trimmed(BlitBuffer::from_buffer(&pixels, 4, MASK_COLOR), 1)?;
+/// world.create_entity().with(Sprite::new(sprite_ref)).build();
+///
+/// let buffer = testing::render(&mut world, 4, 4);
+/// assert_eq!(buffer.pixels()[2 * 4 + 1] & 0xFFFFFF, 0xFFFFFF);
+/// assert_eq!(buffer.pixels().iter().filter(|&&pixel| pixel != 0).count(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_trimmed(sprite: BlitBuffer, rotations: u16) -> Result<SpriteRef, SpriteError> {
+    let image = SpriteImage::from_blit_buffer(&sprite);
+    let size = (image.width() as i32, image.height() as i32);
+    let (pos, trimmed) = image.trim();
+
+    load_image_part(
+        trimmed,
+        (pos.0 as i32, pos.1 as i32),
+        size,
+        rotations,
+        (0, 360),
+        &RotationAlgorithm::default(),
+    )
+}
+
+/// Place an image onto the heap with a set amount of rotations.
+fn load_image(
+    image: SpriteImage,
+    rotations: u16,
+    range: (i16, i16),
+    algorithm: &RotationAlgorithm,
+) -> Result<SpriteRef, SpriteError> {
+    let size = (image.width() as i32, image.height() as i32);
+
+    load_image_part(image, (0, 0), size, rotations, range, algorithm)
+}
+
+/// Place an image that's the part at the position of a sprite of the size onto the heap with a
+/// set amount of rotations, it's drawn where it would be in the whole sprite.
+fn load_image_part(
+    image: SpriteImage,
+    pos: (i32, i32),
+    size: (i32, i32),
     rotations: u16,
     range: (i16, i16),
-) -> Result<SpriteRef> {
+    algorithm: &RotationAlgorithm,
+) -> Result<SpriteRef, SpriteError> {
     let rotations = if rotations == 0 { 1 } else { rotations };
 
-    let rot_divisor = (range.1 - range.0) as f64 / (rotations as f64);
-    let raw_buffer = sprite.to_raw_buffer();
+    validate_load(&image, range)?;
 
-    // Create a rotation sprite for all rotations
-    let sprites = (0..rotations)
-        .into_par_iter()
-        .map(|r| {
-            let (rotated_width, rotated_height, rotated) = rotsprite::rotsprite(
-                &raw_buffer,
-                &sprite.mask_color().u32(),
-                sprite.size().0 as usize,
-                range.0 as f64 + (r as f64 * rot_divisor),
-            )?;
+    let rot_divisor = (i32::from(range.1) - i32::from(range.0)) as f64 / (rotations as f64);
+    let (width, height) = size;
 
-            let rotated_sprite =
-                BlitBuffer::from_buffer(&rotated, rotated_width as i32, sprite.mask_color());
+    // Center of the part relative to the center of the whole sprite, it turns with the sprite
+    let part_center = (
+        f64::from(pos.0) + (image.width() as f64 - f64::from(width)) / 2.0,
+        f64::from(pos.1) + (image.height() as f64 - f64::from(height)) / 2.0,
+    );
 
-            let mut sprites_vec = SPRITES.write().unwrap();
-            sprites_vec.push(rotated_sprite);
+    // Create a rotation sprite for all rotations, the order is kept when baking in parallel
+    #[cfg(feature = "parallel")]
+    let rotations = (0..rotations).into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let rotations = 0..rotations;
+    let mut rotated_sprites = rotations
+        .map(|r| algorithm.rotate(&image, range.0 as f64 + (r as f64 * rot_divisor)))
+        // Return the first error
+        .collect::<Result<Vec<_>, SpriteError>>()?;
 
-            let index = sprites_vec.len() - 1;
+    // Keep the pixels of all rotations next to each other, a rotating sprite uses all of them
+    sprite_image::pack(&mut rotated_sprites, usize::MAX);
 
-            let x_offset = (sprite.width() - rotated_width as i32) / 2;
-            let y_offset = (sprite.height() - rotated_height as i32) / 2;
+    let offsets: Vec<(i32, i32)> = rotated_sprites
+        .iter()
+        .enumerate()
+        .map(|(r, rotated_sprite)| {
+            let (rotated_width, rotated_height) = (
+                rotated_sprite.width() as i32,
+                rotated_sprite.height() as i32,
+            );
+            let (x, y) = part_center;
+            if x == 0.0 && y == 0.0 {
+                return ((width - rotated_width) / 2, (height - rotated_height) / 2);
+            }
 
-            Ok((index, x_offset, y_offset))
+            let degrees = range.0 as f64 + (r as f64 * rot_divisor);
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            (
+                (f64::from(width - rotated_width) / 2.0 + x * cos - y * sin).round() as i32,
+                (f64::from(height - rotated_height) / 2.0 + x * sin + y * cos).round() as i32,
+            )
         })
-        .collect::<Result<Vec<_>>>()?
-        // Return the first error
-        .into_iter()
-        .collect::<_>();
+        .collect();
+
+    // Store all rotations while holding the lock only once, slots of freed sprites are reused
+    let mut sprites_vec = SPRITES.write().map_err(|_| SpriteError::StorePoisoned)?;
+    let slots = reclaim::store(&mut sprites_vec, rotated_sprites)?;
+    let sprites = slots
+        .iter()
+        .zip(offsets)
+        .map(|(&index, (x_offset, y_offset))| (index, x_offset, y_offset))
+        .collect();
 
     Ok(SpriteRef {
         rot_range_start: range.0,
         rot_divisor,
+        size: (width, height),
         sprites,
+        lods: Vec::new(),
+        sockets: Arc::default(),
+        _slots: Arc::new(SlotGuard::new(slots)),
     })
 }
 
+/// Check whether the sprite can be loaded with the rotation range.
+fn validate_load(image: &SpriteImage, range: (i16, i16)) -> Result<(), SpriteError> {
+    if image.width() == 0 || image.height() == 0 {
+        return Err(SpriteError::Empty {
+            width: image.width(),
+            height: image.height(),
+        });
+    }
+
+    if range.1 <= range.0 {
+        return Err(SpriteError::InvalidRotationRange {
+            start: range.0,
+            end: range.1,
+        });
+    }
+
+    Ok(())
+}
+
 /// Delete all cached buffers.
 ///
-/// Marked unsafe because it will invalidate all sprite references.
-pub unsafe fn clear_all() {
-    SPRITES.write().unwrap().clear();
+/// All sprite references are invalidated. Sprites drawn with a reference created before the call
+/// are skipped, or drawn with the image of a sprite that's loaded later at the same index.
+pub fn clear_all() {
+    let mut sprites = SPRITES.write().unwrap();
+    sprites.clear();
+    reclaim::reset();
+    lazy::reset();
 }
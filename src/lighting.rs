@@ -0,0 +1,551 @@
+//! Shading the rendered pixels with point lights and normal maps.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    sprite_image::Composite,
+    Camera, PixelBuffer, SpriteError, SpriteImage, SpriteRef,
+};
+use blit::BlitBuffer;
+use specs::prelude::*;
+use web_time::Instant;
+
+/// Normal map drawn by the [`RenderSystem`](crate::RenderSystem) into the [`NormalBuffer`] where
+/// the [`Sprite`](crate::Sprite) of the same entity is drawn.
+///
+/// Every pixel is a direction in the usual normal map colors: red points right, green points up
+/// and blue points towards the viewer, so `0x80_80_FF` faces the viewer. The normal map must have
+/// the same size as the sprite. When the sprite is rotated the normals are rotated along.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     load, Lighting, LightingSystem, NormalMap, PixelBuffer, PointLight, RenderSystem, Sprite,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(2, 1));
+/// world.insert(Lighting { ambient: 0 });
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     .with_thread_local(LightingSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// // Two white pixels, the left one faces left and the right one faces the viewer
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 2], 2, 0xFF_00_FF))?;
+/// let normals = BlitBuffer::from_buffer(&[0x00_80_80, 0x80_80_FF], 2, 0xFF_00_FF);
+/// let normal_map = NormalMap::load(normals, &sprite_ref)?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(normal_map)
+///     .build();
+///
+/// // A light to the right of the sprite
+/// world.create_entity().with(PointLight::new(4, 0, 16.0)).build();
+///
+/// dispatcher.dispatch(&world);
+///
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[0] & 0xFF_FF_FF, 0);
+/// assert!(buffer.pixels()[1] & 0xFF > 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NormalMap {
+    /// Loaded normal map with the same rotations as the sprite.
+    reference: SpriteRef,
+}
+
+impl NormalMap {
+    /// Use an already loaded normal map, it should be loaded with the same rotations as the
+    /// sprite.
+    pub fn new(reference: SpriteRef) -> Self {
+        Self { reference }
+    }
+
+    /// Load the normal map with the same rotations as the loaded sprite.
+    pub fn load(normal_map: BlitBuffer, sprite: &SpriteRef) -> Result<Self, SpriteError> {
+        Ok(Self::new(load_companion(normal_map, sprite)?))
+    }
+
+    /// Get the loaded normal map.
+    pub fn reference(&self) -> &SpriteRef {
+        &self.reference
+    }
+}
+
+impl Component for NormalMap {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Marks a sprite as glowing, so the [`LightingSystem`] doesn't darken it.
+///
+/// Either all pixels of the sprite glow or only the pixels covered by a mask, e.g. the eyes of a
+/// creature in the dark. The pixels are marked in the [`NormalBuffer`] so other passes can pick
+/// them up too.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Emissive, Lighting, LightingSystem, PixelBuffer, RenderSystem, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(2, 1));
+/// world.insert(Lighting { ambient: 0 });
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     .with_thread_local(LightingSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// // Only the right pixel of the sprite glows
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00; 2], 2, 0xFF_00_FF))?;
+/// let mask = BlitBuffer::from_buffer(&[0xFF_00_FF, 0xFF_FF_FF], 2, 0xFF_00_FF);
+/// let emissive = Emissive::load_mask(mask, &sprite_ref)?;
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(emissive)
+///     .build();
+///
+/// dispatcher.dispatch(&world);
+///
+/// // Without any lights only the glowing pixel can be seen
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[0] & 0xFF_FF_FF, 0);
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[1] & 0xFF_FF_FF, 0xFF_00_00);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Emissive {
+    /// Loaded mask with the same rotations as the sprite, `None` when all pixels glow.
+    mask: Option<SpriteRef>,
+}
+
+impl Emissive {
+    /// Let all pixels of the sprite glow.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only let the pixels of the sprite glow where the already loaded mask isn't transparent,
+    /// it should be loaded with the same rotations as the sprite.
+    pub fn with_mask(mask: SpriteRef) -> Self {
+        Self { mask: Some(mask) }
+    }
+
+    /// Load the mask with the same rotations as the loaded sprite and only let the pixels glow
+    /// where it isn't transparent.
+    pub fn load_mask(mask: BlitBuffer, sprite: &SpriteRef) -> Result<Self, SpriteError> {
+        Ok(Self::with_mask(load_companion(mask, sprite)?))
+    }
+
+    /// Get the loaded mask, `None` when all pixels glow.
+    pub fn mask(&self) -> Option<&SpriteRef> {
+        self.mask.as_ref()
+    }
+}
+
+impl Component for Emissive {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Load an image belonging to a sprite with the same rotations.
+fn load_companion(buffer: BlitBuffer, sprite: &SpriteRef) -> Result<SpriteRef, SpriteError> {
+    let rotations = sprite.sprites.len() as u16;
+    let range_end = f64::from(sprite.rot_range_start) + sprite.rot_divisor * rotations as f64;
+
+    crate::load_rotations_range(
+        buffer,
+        rotations,
+        (sprite.rot_range_start, range_end.round() as i16),
+    )
+}
+
+/// Get the rotated image in the store with the degrees it's rotated by.
+fn rotated<'a>(
+    reference: &SpriteRef,
+    rot: i16,
+    sprite_images: &'a [SpriteImage],
+) -> Option<(&'a SpriteImage, f64)> {
+    let rotation_index = reference.rotation_index(rot);
+
+    sprite_images
+        .get(reference.sprites[rotation_index].0)
+        .map(|image| (image, reference.degrees(rotation_index)))
+}
+
+/// Which pixels of a drawn sprite glow.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Emission<'a> {
+    /// None of the pixels.
+    None,
+    /// All pixels.
+    All,
+    /// The pixels where the rotated mask isn't transparent.
+    Mask(&'a SpriteImage),
+}
+
+/// What the lighting needs to know about the pixels of a drawn sprite.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Surface<'a> {
+    /// Rotated normal map with the degrees it's rotated by.
+    normal_map: Option<(&'a SpriteImage, f64)>,
+    /// Which pixels glow.
+    emission: Emission<'a>,
+}
+
+impl<'a> Surface<'a> {
+    /// Find the rotated images of the components of a sprite with the rotation.
+    pub(crate) fn new(
+        normal_map: Option<&NormalMap>,
+        emissive: Option<&Emissive>,
+        rot: i16,
+        sprite_images: &'a [SpriteImage],
+    ) -> Self {
+        Self {
+            normal_map: normal_map
+                .and_then(|normal_map| rotated(&normal_map.reference, rot, sprite_images)),
+            emission: match emissive.map(|emissive| &emissive.mask) {
+                None => Emission::None,
+                Some(None) => Emission::All,
+                Some(Some(mask)) => rotated(mask, rot, sprite_images)
+                    .map_or(Emission::None, |(mask, _)| Emission::Mask(mask)),
+            },
+        }
+    }
+}
+
+/// Directions the pixels of the [`PixelBuffer`] are facing, filled by the
+/// [`RenderSystem`](crate::RenderSystem) from the [`NormalMap`]s of the sprites.
+///
+/// Pixels without a normal map face the viewer. The pixels of [`Emissive`] sprites are marked as
+/// glowing. It's added by the [`LightingSystem`], the render system doesn't fill it when it's
+/// missing.
+#[derive(Debug, Default)]
+pub struct NormalBuffer {
+    /// Normals in the normal map colors with a full alpha channel, `0` for facing the viewer.
+    normals: Vec<u32>,
+    /// Whether the pixels glow.
+    emissive: Vec<bool>,
+    /// Width in pixels.
+    width: usize,
+    /// Height in pixels.
+    height: usize,
+}
+
+impl NormalBuffer {
+    /// Get the width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the direction the pixel is facing as a unit vector, where `x` points right, `y` points
+    /// down and `z` points towards the viewer.
+    pub fn normal(&self, x: usize, y: usize) -> (f32, f32, f32) {
+        if x >= self.width || y >= self.height {
+            return (0.0, 0.0, 1.0);
+        }
+
+        match self.normals[y * self.width + x] {
+            0 => (0.0, 0.0, 1.0),
+            color => decode(color),
+        }
+    }
+
+    /// Whether the pixel belongs to an [`Emissive`] sprite and isn't darkened by the lighting.
+    pub fn is_emissive(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.emissive[y * self.width + x]
+    }
+
+    /// Make every pixel face the viewer without glowing, resizing it to the size of the buffer.
+    pub(crate) fn reset(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.normals.clear();
+        self.normals.resize(width * height, 0);
+        self.emissive.clear();
+        self.emissive.resize(width * height, false);
+    }
+
+    /// Set the normals and the glowing pixels where the image is drawn at the position with the
+    /// size, pixels without a normal map face the viewer.
+    ///
+    /// The image and its normals are mirrored horizontally and vertically when the flags are set.
+    pub(crate) fn draw(
+        &mut self,
+        image: &SpriteImage,
+        surface: Surface,
+        pos: (i32, i32),
+        size: (usize, usize),
+        flip: (bool, bool),
+    ) {
+        if image.width() == 0 || image.height() == 0 {
+            return;
+        }
+
+        // Find the pixel of an image covering the pixel of the drawn sprite
+        let sample = |image: &SpriteImage, local_x: usize, local_y: usize| {
+            let local_x = if flip.0 {
+                size.0 - 1 - local_x
+            } else {
+                local_x
+            };
+            let local_y = if flip.1 {
+                size.1 - 1 - local_y
+            } else {
+                local_y
+            };
+            image.pixels()[local_y * image.height() / size.1 * image.width()
+                + local_x * image.width() / size.0]
+        };
+        let mirror = |value: f32, flipped: bool| if flipped { -value } else { value };
+
+        let normal_map = surface.normal_map;
+        let (sin, cos) = normal_map.map_or((0.0, 1.0), |(_, degrees)| {
+            (degrees as f32).to_radians().sin_cos()
+        });
+        for local_y in 0..size.1 {
+            let y = pos.1 + local_y as i32;
+            if y < 0 || y >= self.height as i32 {
+                continue;
+            }
+            for local_x in 0..size.0 {
+                let x = pos.0 + local_x as i32;
+                if x < 0 || x >= self.width as i32 {
+                    continue;
+                }
+
+                // Only the pixels covered by the sprite get its normals
+                let src = sample(image, local_x, local_y);
+                if !image.is_visible(src)
+                    || (image.composite() != Composite::Mask && src >> 24 == 0)
+                {
+                    continue;
+                }
+
+                let normal = normal_map.and_then(|(normal_map, _)| {
+                    let normal = sample(normal_map, local_x, local_y);
+
+                    if normal_map.is_visible(normal) {
+                        // Turn the direction along with the sprite
+                        let (normal_x, normal_y, normal_z) = decode(normal);
+                        Some(encode((
+                            mirror(normal_x * cos - normal_y * sin, flip.0),
+                            mirror(normal_x * sin + normal_y * cos, flip.1),
+                            normal_z,
+                        )))
+                    } else {
+                        None
+                    }
+                });
+
+                let index = y as usize * self.width + x as usize;
+                self.normals[index] = normal.unwrap_or(0);
+                self.emissive[index] = match surface.emission {
+                    Emission::None => false,
+                    Emission::All => true,
+                    Emission::Mask(mask) => mask.is_visible(sample(mask, local_x, local_y)),
+                };
+            }
+        }
+    }
+}
+
+/// Convert a normal map color to a unit vector pointing down for positive `y`.
+fn decode(color: u32) -> (f32, f32, f32) {
+    let channel = |shift: u32| ((color >> shift) & 0xFF) as f32 / 127.5 - 1.0;
+    let (x, y, z) = (channel(16), -channel(8), channel(0));
+
+    let length = (x * x + y * y + z * z).sqrt();
+    if length == 0.0 {
+        (0.0, 0.0, 1.0)
+    } else {
+        (x / length, y / length, z / length)
+    }
+}
+
+/// Convert a vector pointing down for positive `y` to a normal map color with a full alpha
+/// channel.
+fn encode((x, y, z): (f32, f32, f32)) -> u32 {
+    let channel = |value: f32| ((value + 1.0) * 127.5).round().clamp(0.0, 255.0) as u32;
+
+    0xFF_00_00_00 | channel(x) << 16 | channel(-y) << 8 | channel(z)
+}
+
+/// Light shining on the pixels around it, drawn by the [`LightingSystem`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointLight {
+    /// Position in the world.
+    pub pos: (i32, i32),
+    /// Color of the light.
+    pub color: u32,
+    /// Distance in world pixels after which the light doesn't reach anymore.
+    pub radius: f32,
+    /// Distance in world pixels the light hovers above the sprites, lower lights make the
+    /// normals stand out more.
+    pub height: f32,
+    /// Multiplier of the color, values above `1.0` can make pixels brighter than their color.
+    pub intensity: f32,
+}
+
+impl PointLight {
+    /// Create a white light at the position.
+    pub fn new(x: i32, y: i32, radius: f32) -> Self {
+        Self {
+            pos: (x, y),
+            color: 0xFF_FF_FF,
+            radius,
+            height: 8.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// Change the color of the light.
+    pub fn with_color(mut self, color: u32) -> Self {
+        self.color = color;
+
+        self
+    }
+}
+
+impl Component for PointLight {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Resource with the light reaching all pixels, read by the [`LightingSystem`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lighting {
+    /// Color of the light shining on everything, `0` is pitch black outside of the lights.
+    pub ambient: u32,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            ambient: 0x40_40_40,
+        }
+    }
+}
+
+/// Specs system darkening the [`PixelBuffer`] except where it's lit by the [`PointLight`]s.
+///
+/// The pixels are shaded with the directions of the [`NormalBuffer`], so sprites with a
+/// [`NormalMap`] look lit from the side of the light. Pixels of [`Emissive`] sprites aren't
+/// shaded. Run it with `.with_thread_local()` after the
+/// [`RenderSystem`](crate::RenderSystem) and before drawing the interface.
+#[derive(Debug, Default)]
+pub struct LightingSystem {
+    /// Amount of light reaching every pixel as red, green and blue multipliers.
+    light: Vec<[f32; 3]>,
+}
+
+impl<'a> System<'a> for LightingSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Read<'a, NormalBuffer>,
+        Read<'a, Lighting>,
+        Option<Read<'a, Camera>>,
+        ReadStorage<'a, PointLight>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, normals, lighting, camera, lights, profiler): Self::SystemData) {
+        let start = Instant::now();
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+        let zoom = camera.zoom() as f32;
+        let (width, height) = (buffer.width(), buffer.height());
+
+        self.light.clear();
+        self.light
+            .resize(width * height, rgb(lighting.ambient, 1.0));
+
+        for light in lights.join() {
+            let (light_x, light_y) = camera.to_screen(light.pos);
+            let radius = light.radius * zoom;
+            let light_height = light.height * zoom;
+            let color = rgb(light.color, light.intensity);
+
+            // Only the pixels within the radius are lit
+            let reach = radius.ceil() as i32;
+            let (start_x, end_x) = (
+                (light_x - reach).max(0),
+                (light_x + reach).min(width as i32),
+            );
+            let (start_y, end_y) = (
+                (light_y - reach).max(0),
+                (light_y + reach).min(height as i32),
+            );
+            for y in start_y..end_y {
+                for x in start_x..end_x {
+                    let (dx, dy) = ((light_x - x) as f32, (light_y - y) as f32);
+                    let distance = (dx * dx + dy * dy).sqrt();
+                    if distance >= radius {
+                        continue;
+                    }
+
+                    // How much the pixel faces the light
+                    let (normal_x, normal_y, normal_z) = normals.normal(x as usize, y as usize);
+                    let length = (distance * distance + light_height * light_height).sqrt();
+                    let diffuse = if length == 0.0 {
+                        normal_z
+                    } else {
+                        (normal_x * dx + normal_y * dy + normal_z * light_height) / length
+                    };
+                    if diffuse <= 0.0 {
+                        continue;
+                    }
+
+                    let falloff = (1.0 - distance / radius).powi(2);
+                    let light = &mut self.light[y as usize * width + x as usize];
+                    for (light, color) in light.iter_mut().zip(color.iter()) {
+                        *light += color * falloff * diffuse;
+                    }
+                }
+            }
+        }
+
+        let emissive = (normals.width, normals.height) == (width, height);
+        for (index, (pixel, light)) in buffer
+            .pixels_mut()
+            .iter_mut()
+            .zip(self.light.iter())
+            .enumerate()
+        {
+            // Glowing pixels keep their color
+            if emissive && normals.emissive[index] {
+                continue;
+            }
+
+            let channel = |shift: u32, light: f32| {
+                let value = ((*pixel >> shift) & 0xFF) as f32 * light;
+
+                (value.round().min(255.0) as u32) << shift
+            };
+
+            *pixel = (*pixel & 0xFF_00_00_00)
+                | channel(16, light[0])
+                | channel(8, light[1])
+                | channel(0, light[2]);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "LightingSystem", start);
+    }
+}
+
+/// Split the color into red, green and blue multipliers where `255` is `1.0`.
+fn rgb(color: u32, intensity: f32) -> [f32; 3] {
+    [16, 8, 0].map(|shift| ((color >> shift) & 0xFF) as f32 / 255.0 * intensity)
+}
@@ -0,0 +1,219 @@
+//! Drawing sprites kept in a resource instead of in component storages.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    Camera, ColorAdjust, Dissolve, PixelBuffer, Sprite, SpriteDraw, Stipple, Wave,
+};
+use specs::prelude::*;
+use web_time::Instant;
+
+/// Effects applied to a single sprite that isn't stored on an entity.
+///
+/// These are the same effects the [`RenderSystem`](crate::RenderSystem) applies with the
+/// [`ColorAdjust`], [`Flash`](crate::Flash), [`Lifetime`](crate::Lifetime), [`Stipple`],
+/// [`Dissolve`] and [`Wave`] components.
+#[derive(Debug, Clone)]
+pub struct SpriteEffects {
+    /// Transformation of the colors.
+    pub(crate) color_adjust: Option<ColorAdjust>,
+    /// Color of the silhouette.
+    pub(crate) flash: Option<u32>,
+    /// Dithered opacity where `255` is fully visible.
+    pub(crate) opacity: u8,
+    /// Pattern of the pixels that are drawn.
+    pub(crate) stipple: Option<Stipple>,
+    /// Which pixels relative to the sprite are skipped.
+    pub(crate) dissolve: Option<Dissolve>,
+    /// Displacement of the rows or columns.
+    pub(crate) wave: Option<Wave>,
+    /// Whether the image is mirrored horizontally and vertically.
+    pub(crate) flip: (bool, bool),
+}
+
+impl Default for SpriteEffects {
+    fn default() -> Self {
+        Self {
+            color_adjust: None,
+            flash: None,
+            opacity: u8::MAX,
+            stipple: None,
+            dissolve: None,
+            wave: None,
+            flip: (false, false),
+        }
+    }
+}
+
+impl SpriteEffects {
+    /// No effects at all, the sprite is drawn as is.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Transform the colors of the sprite.
+    pub fn with_color_adjust(mut self, color_adjust: ColorAdjust) -> Self {
+        self.color_adjust = Some(color_adjust);
+
+        self
+    }
+
+    /// Draw the silhouette of the sprite in a single color.
+    pub fn with_flash(mut self, color: u32) -> Self {
+        self.flash = Some(color);
+
+        self
+    }
+
+    /// Draw the sprite dithered with the opacity where `255` is fully visible.
+    pub fn with_opacity(mut self, opacity: u8) -> Self {
+        self.opacity = opacity;
+
+        self
+    }
+
+    /// Only draw the pixels of the pattern.
+    pub fn with_stipple(mut self, stipple: Stipple) -> Self {
+        self.stipple = Some(stipple);
+
+        self
+    }
+
+    /// Skip a part of the pixels of the sprite.
+    pub fn with_dissolve(mut self, dissolve: Dissolve) -> Self {
+        self.dissolve = Some(dissolve);
+
+        self
+    }
+
+    /// Move the rows or columns of the sprite.
+    pub fn with_wave(mut self, wave: Wave) -> Self {
+        self.wave = Some(wave);
+
+        self
+    }
+
+    /// Mirror the sprite horizontally and vertically when the flags are set.
+    pub fn with_flip(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.flip = (horizontal, vertical);
+
+        self
+    }
+}
+
+/// Resource with sprites drawn by the [`SpriteListRenderSystem`], for managing the sprites
+/// without specs entities and storages.
+///
+/// Only plain resources are used, so it also works with a bare `shred` world and dispatcher.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     load, PixelBuffer, Sprite, SpriteEffects, SpriteList, SpriteListRenderSystem,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// // No components are registered
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(2, 1));
+///
+/// let mut system = SpriteListRenderSystem;
+/// System::setup(&mut system, &mut world);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+/// let mut flashing = Sprite::new(sprite_ref.clone());
+/// flashing.set_pos(1, 0);
+///
+/// let mut list = world.write_resource::<SpriteList>();
+/// list.push(Sprite::new(sprite_ref));
+/// list.push_with_effects(flashing, SpriteEffects::new().with_flash(0xFF_00_00));
+/// drop(list);
+///
+/// system.run_now(&world);
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[0] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// assert_eq!(buffer.pixels()[1] & 0xFF_FF_FF, 0xFF_00_00);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SpriteList {
+    /// Sprites with their effects in the order they were added.
+    sprites: Vec<(Sprite, SpriteEffects)>,
+}
+
+impl SpriteList {
+    /// Create an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sprite drawn without effects.
+    pub fn push(&mut self, sprite: Sprite) {
+        self.push_with_effects(sprite, SpriteEffects::default());
+    }
+
+    /// Add a sprite drawn with the effects.
+    pub fn push_with_effects(&mut self, sprite: Sprite, effects: SpriteEffects) {
+        self.sprites.push((sprite, effects));
+    }
+
+    /// Remove all sprites.
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    /// Get the sprites with their effects in the order they were added.
+    pub fn sprites(&self) -> &[(Sprite, SpriteEffects)] {
+        &self.sprites
+    }
+
+    /// Get the sprites with their effects so they can be moved or changed.
+    pub fn sprites_mut(&mut self) -> &mut Vec<(Sprite, SpriteEffects)> {
+        &mut self.sprites
+    }
+}
+
+impl PixelBuffer {
+    /// Draw the sprite through the camera with the effects applied.
+    ///
+    /// The sprite is drawn the same way as the [`RenderSystem`](crate::RenderSystem) draws an
+    /// entity with the effect components.
+    pub fn draw_sprite_with_effects(
+        &mut self,
+        sprite: &Sprite,
+        effects: &SpriteEffects,
+        camera: &Camera,
+    ) {
+        self.draw_sprite_with(SpriteDraw::new(sprite).with_effects(Some(effects)), camera);
+    }
+}
+
+/// Specs system drawing the sprites of the [`SpriteList`] resource.
+///
+/// The sprites are drawn through the [`Camera`] resource sorted by their layer and z-order,
+/// sprites with the same values are drawn in the order they were added. The list is inserted when
+/// the system is set up.
+#[derive(Debug, Default)]
+pub struct SpriteListRenderSystem;
+impl<'a> System<'a> for SpriteListRenderSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Read<'a, SpriteList>,
+        Option<Read<'a, Camera>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, list, camera, profiler): Self::SystemData) {
+        let start = Instant::now();
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+
+        let mut sprites: Vec<&(Sprite, SpriteEffects)> = list.sprites.iter().collect();
+        sprites.sort_by_key(|(sprite, _)| (sprite.layer(), sprite.z()));
+        for (sprite, effects) in sprites {
+            buffer.draw_sprite_with_effects(sprite, effects, &camera);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "SpriteListRenderSystem", start);
+    }
+}
@@ -0,0 +1,232 @@
+//! A small overview of the world.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    Camera, PixelBuffer, Sprite, Tilemap, SPRITES,
+};
+use specs::prelude::*;
+use web_time::Instant;
+
+/// Specs component drawing a dot on the [`Minimap`] at the position of the [`Sprite`] of the
+/// same entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinimapMarker {
+    /// Color of the dot.
+    pub color: u32,
+    /// Width and height of the dot in pixels of the minimap.
+    pub size: u32,
+}
+
+impl MinimapMarker {
+    /// Create a marker of a single pixel.
+    pub fn new(color: u32) -> Self {
+        Self { color, size: 1 }
+    }
+}
+
+impl Component for MinimapMarker {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Resource with a part of the world that's drawn scaled down by the [`MinimapSystem`].
+///
+/// The minimap is rendered into its own buffer, which is copied onto the main buffer when it has
+/// a position, otherwise it can be read with [`Minimap::buffer`] and shown anywhere. The tilemaps,
+/// the sprites on a single layer and the markers are drawn in that order.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     load, Minimap, MinimapMarker, MinimapSystem, PixelBuffer, RenderSystem, Sprite,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(64, 64));
+///
+/// // Show the world of 256x256 pixels in the top right corner at a size of 16x16
+/// let mut minimap = Minimap::new(16, 16);
+/// minimap.world_size = (256, 256);
+/// minimap.pos = Some((48, 0));
+/// world.insert(minimap);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     // Draw the minimap over the sprites
+///     .with_thread_local(MinimapSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// // The player is shown as a white dot
+/// let mut sprite = Sprite::new(load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?);
+/// sprite.set_pos(128, 128);
+/// world
+///     .create_entity()
+///     .with(sprite)
+///     .with(MinimapMarker::new(0xFF_FF_FF))
+///     .build();
+///
+/// dispatcher.dispatch(&world);
+///
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[8 * 64 + 48 + 8], 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Minimap {
+    /// World position shown at the top left corner of the minimap.
+    pub world_pos: (i32, i32),
+    /// Width and height of the part of the world that's shown, scaled down evenly to fit.
+    pub world_size: (i32, i32),
+    /// Position of the top left corner on the main buffer, `None` to only draw it in its own
+    /// buffer.
+    pub pos: Option<(i32, i32)>,
+    /// Whether the tilemaps are drawn.
+    pub tilemaps: bool,
+    /// Layer of the sprites that are drawn, `None` to not draw any sprites.
+    pub layer: Option<u8>,
+    /// Color the minimap is filled with before drawing.
+    pub background: u32,
+    /// The rendered minimap.
+    buffer: PixelBuffer,
+}
+
+impl Minimap {
+    /// Create a minimap of the size in pixels, showing the world at the same size.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            world_pos: (0, 0),
+            world_size: (width as i32, height as i32),
+            pos: None,
+            tilemaps: true,
+            layer: None,
+            background: 0,
+            buffer: PixelBuffer::new(width, height),
+        }
+    }
+
+    /// The minimap as it was rendered last.
+    pub fn buffer(&self) -> &PixelBuffer {
+        &self.buffer
+    }
+
+    /// Camera looking at the shown part of the world from the minimap buffer.
+    pub fn camera(&self) -> Camera {
+        let zoom = (self.buffer.width() as f64 / f64::from(self.world_size.0.max(1)))
+            .min(self.buffer.height() as f64 / f64::from(self.world_size.1.max(1)));
+
+        let mut camera = Camera::new(self.world_pos.0, self.world_pos.1);
+        camera.set_zoom(zoom);
+
+        camera
+    }
+
+    /// Copy the rendered minimap onto the buffer at its position.
+    pub fn draw(&self, buffer: &mut PixelBuffer) {
+        let pos = match self.pos {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        let (width, height) = (self.buffer.width() as i32, self.buffer.height() as i32);
+        let (buffer_width, buffer_height) = (buffer.width() as i32, buffer.height() as i32);
+
+        // Only copy the part that's visible in the buffer
+        let start = (pos.0.max(0), pos.1.max(0));
+        let end = (
+            (pos.0 + width).min(buffer_width),
+            (pos.1 + height).min(buffer_height),
+        );
+        if start.0 >= end.0 || start.1 >= end.1 {
+            return;
+        }
+
+        let len = (end.0 - start.0) as usize;
+        for y in start.1..end.1 {
+            let src_start = ((y - pos.1) * width + start.0 - pos.0) as usize;
+            let dst_start = (y * buffer_width + start.0) as usize;
+
+            buffer.pixels[dst_start..dst_start + len]
+                .copy_from_slice(&self.buffer.pixels[src_start..src_start + len]);
+        }
+    }
+}
+
+/// Specs system rendering the [`Minimap`] resource and drawing it on the buffer.
+///
+/// Does nothing when the resource doesn't exist. Run it after the
+/// [`RenderSystem`](crate::RenderSystem) with `.with_thread_local()` so the minimap is drawn over
+/// the sprites.
+#[derive(Debug, Default)]
+pub struct MinimapSystem;
+impl<'a> System<'a> for MinimapSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Write<'a, Minimap>>,
+        ReadStorage<'a, Sprite>,
+        ReadStorage<'a, Tilemap>,
+        ReadStorage<'a, MinimapMarker>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(
+        &mut self,
+        (mut buffer, minimap, sprites, tilemaps, markers, profiler): Self::SystemData,
+    ) {
+        let start = Instant::now();
+        let mut minimap = match minimap {
+            Some(minimap) => minimap,
+            None => return,
+        };
+
+        let camera = minimap.camera();
+        let background = minimap.background;
+        minimap.buffer.clear(background);
+
+        if minimap.tilemaps {
+            for tilemap in tilemaps.join() {
+                tilemap.draw(&mut minimap.buffer, &camera);
+            }
+        }
+
+        if let Some(layer) = minimap.layer {
+            let mut layer_sprites: Vec<&Sprite> = sprites
+                .join()
+                .filter(|sprite| sprite.layer() == layer)
+                .collect();
+            layer_sprites.sort_by_key(|sprite| sprite.z());
+
+            let sprite_images = SPRITES.read().unwrap();
+            for sprite in layer_sprites {
+                let render_info = sprite.render_info(camera.zoom());
+                let image = match sprite_images.get(render_info.index) {
+                    Some(image) => image,
+                    None => continue,
+                };
+
+                let pos = camera.to_screen(sprite.pos());
+                let pos = (pos.0 + render_info.offset.0, pos.1 + render_info.offset.1);
+                let size = render_info.size(image);
+                image.blit_scaled_with(&mut minimap.buffer, pos, size, |src, dst, _, _| *dst = src);
+            }
+        }
+
+        // Markers are drawn at their full size regardless of the zoom
+        let (width, height) = (minimap.buffer.width(), minimap.buffer.height());
+        for (sprite, marker) in (&sprites, &markers).join() {
+            let (x, y) = camera.to_screen(sprite.pos());
+            let half = marker.size as i32 / 2;
+            for dot_y in (y - half).max(0)..(y - half + marker.size as i32).min(height as i32) {
+                for dot_x in (x - half).max(0)..(x - half + marker.size as i32).min(width as i32) {
+                    minimap.buffer.pixels[dot_y as usize * width + dot_x as usize] = marker.color;
+                }
+            }
+        }
+
+        minimap.draw(&mut buffer);
+
+        profiler::record(profiler, RenderPhase::Blit, "MinimapSystem", start);
+    }
+}
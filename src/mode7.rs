@@ -0,0 +1,158 @@
+//! Perspective ground plane rendering like the Mode 7 of the SNES.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    PixelBuffer, SpriteImage,
+};
+use blit::BlitBuffer;
+use specs::prelude::*;
+use web_time::Instant;
+
+/// Resource with a big image that's drawn as a ground plane seen from above at an angle by the
+/// [`Mode7System`].
+///
+/// The camera is positioned above the pixel coordinates of the image.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{Mode7Plane, Mode7System, PixelBuffer, RenderSystem};
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(64, 48));
+///
+/// // A track of 256x256 pixels
+/// let track = BlitBuffer::from_buffer(&[0x00_80_00; 256 * 256], 256, 0xFF_00_FF);
+/// let mut plane = Mode7Plane::new(&track);
+/// plane.pos = (128.0, 200.0);
+/// plane.horizon = 16;
+/// world.insert(plane);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     // Draw the ground before the sprites
+///     .with_thread_local(Mode7System)
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+///
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// // Above the horizon nothing is drawn
+/// assert_eq!(buffer.pixels()[0], 0);
+/// assert_eq!(buffer.pixels()[40 * 64] & 0xFF_FF_FF, 0x00_80_00);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mode7Plane {
+    /// The image on the ground.
+    texture: SpriteImage,
+    /// Position of the camera on the image in pixels.
+    pub pos: (f32, f32),
+    /// Direction the camera is looking at in degrees, `0.0` looks up the image and positive
+    /// values turn clockwise.
+    pub angle: f32,
+    /// Height of the camera above the plane.
+    pub height: f32,
+    /// Row of the buffer where the plane ends, the rows above it are left untouched.
+    pub horizon: usize,
+    /// Distance of the camera to the screen, lower values give a wider view.
+    pub focal_length: f32,
+    /// Whether the image repeats infinitely, otherwise nothing is drawn outside of it.
+    pub repeat: bool,
+}
+
+impl Mode7Plane {
+    /// Create a plane from an image, with the camera above the top left corner.
+    pub fn new(texture: &BlitBuffer) -> Self {
+        Self {
+            texture: SpriteImage::from_blit_buffer(texture),
+            pos: (0.0, 0.0),
+            angle: 0.0,
+            height: 32.0,
+            horizon: 0,
+            focal_length: 64.0,
+            repeat: false,
+        }
+    }
+
+    /// Get the image on the ground.
+    pub fn texture(&self) -> &SpriteImage {
+        &self.texture
+    }
+
+    /// Draw the plane on the rows of the buffer below the horizon.
+    pub fn draw(&self, buffer: &mut PixelBuffer) {
+        let (width, height) = (self.texture.width() as i64, self.texture.height() as i64);
+        if width == 0 || height == 0 || self.focal_length <= 0.0 {
+            return;
+        }
+
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let forward = (sin, -cos);
+        let right = (cos, sin);
+        let buffer_width = buffer.width();
+        let half_width = buffer_width as f32 / 2.0;
+
+        let start = self.horizon + 1;
+        for (index, row) in buffer
+            .pixels_mut()
+            .chunks_exact_mut(buffer_width)
+            .enumerate()
+            .skip(start)
+        {
+            // Distance to the part of the plane shown on this row
+            let distance = self.height * self.focal_length / (index - self.horizon) as f32;
+            let step = distance / self.focal_length;
+
+            // The point on the plane at the left edge of the row
+            let mut x = self.pos.0 + forward.0 * distance - right.0 * half_width * step;
+            let mut y = self.pos.1 + forward.1 * distance - right.1 * half_width * step;
+            let (step_x, step_y) = (right.0 * step, right.1 * step);
+
+            for pixel in row.iter_mut() {
+                let (mut texture_x, mut texture_y) = (x.floor() as i64, y.floor() as i64);
+                x += step_x;
+                y += step_y;
+
+                if self.repeat {
+                    texture_x = texture_x.rem_euclid(width);
+                    texture_y = texture_y.rem_euclid(height);
+                } else if texture_x < 0
+                    || texture_y < 0
+                    || texture_x >= width
+                    || texture_y >= height
+                {
+                    continue;
+                }
+
+                let color = self.texture.pixels()[(texture_y * width + texture_x) as usize];
+                if self.texture.is_visible(color) {
+                    *pixel = color;
+                }
+            }
+        }
+    }
+}
+
+/// Specs system drawing the [`Mode7Plane`] resource on the buffer.
+///
+/// Does nothing when the resource doesn't exist. Run it before the
+/// [`RenderSystem`](crate::RenderSystem) with `.with_thread_local()` so the sprites are drawn on
+/// top of the plane.
+#[derive(Debug, Default)]
+pub struct Mode7System;
+impl<'a> System<'a> for Mode7System {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Mode7Plane>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, plane, profiler): Self::SystemData) {
+        let start = Instant::now();
+        if let Some(plane) = plane {
+            plane.draw(&mut buffer);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "Mode7System", start);
+    }
+}
@@ -0,0 +1,171 @@
+//! Drawing several different sprites from a single entity.
+
+use crate::SpriteRef;
+use specs::prelude::*;
+
+/// A single sprite drawn as part of a [`MultiSprite`].
+#[derive(Debug, Clone)]
+pub struct SpritePart {
+    /// The loaded sprite that's drawn.
+    pub(crate) reference: SpriteRef,
+    /// Position relative to the position of the sprite, turned along with it.
+    offset: (i32, i32),
+    /// Rotation in degrees added to the rotation of the sprite.
+    rot: i16,
+    /// Order relative to the sprite, negative parts are drawn below it.
+    z: i32,
+}
+
+impl SpritePart {
+    /// Create a part drawn over the sprite at the same position with the same rotation.
+    pub fn new(reference: SpriteRef) -> Self {
+        Self {
+            reference,
+            offset: (0, 0),
+            rot: 0,
+            z: 0,
+        }
+    }
+
+    /// Move the part by the offset in pixels of the unrotated sprite.
+    ///
+    /// The offset is turned and stretched along with the sprite.
+    pub fn with_offset(mut self, x: i32, y: i32) -> Self {
+        self.offset = (x, y);
+
+        self
+    }
+
+    /// Turn the part by the degrees relative to the sprite.
+    ///
+    /// The rotation will attempt to match the nearest degrees of rotation divisor.
+    pub fn with_rot(mut self, rotation: i16) -> Self {
+        self.rot = rotation;
+
+        self
+    }
+
+    /// Set the order relative to the sprite, parts with a negative z-order are drawn below it.
+    ///
+    /// Parts with the same z-order are drawn in the order they are added.
+    pub fn with_z(mut self, z: i32) -> Self {
+        self.z = z;
+
+        self
+    }
+
+    /// Get the position relative to the position of the sprite.
+    pub fn offset(&self) -> (i32, i32) {
+        self.offset
+    }
+
+    /// Get the rotation in degrees relative to the sprite.
+    pub fn rot(&self) -> i16 {
+        self.rot
+    }
+
+    /// Get the order relative to the sprite.
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    /// Get the offset from the position of a sprite with the rotation and the horizontal and
+    /// vertical stretch factors.
+    pub(crate) fn world_offset(&self, rot: i16, scale: (f64, f64)) -> (i32, i32) {
+        let (x, y) = (
+            f64::from(self.offset.0) * scale.0,
+            f64::from(self.offset.1) * scale.1,
+        );
+        let (sin, cos) = f64::from(rot).to_radians().sin_cos();
+
+        (
+            (x * cos - y * sin).round() as i32,
+            (x * sin + y * cos).round() as i32,
+        )
+    }
+
+    /// Get the rotation of the part on a sprite with the rotation.
+    pub(crate) fn world_rot(&self, rot: i16) -> i16 {
+        (i32::from(rot) + i32::from(self.rot)).rem_euclid(360) as i16
+    }
+}
+
+/// Specs component drawing extra sprites with the [`Sprite`](crate::Sprite) of the same entity,
+/// e.g. equipment over a character.
+///
+/// The parts follow the position, rotation, scale and layer of the sprite and share its effects,
+/// without needing an entity for every part. They are drawn together with the sprite, sorted by
+/// their z-order where the sprite itself has a z-order of zero. With
+/// [`Instances`](crate::Instances) every instance is drawn with all parts.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, MultiSprite, Sprite, SpritePart};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<MultiSprite>();
+///
+/// let body = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?;
+/// let hat = load(BlitBuffer::from_buffer(&[0xFF_00_00; 2], 2, 0xFF_00_FF))?;
+/// let shadow = load(BlitBuffer::from_buffer(&[0x33_33_33; 4], 2, 0xFF_00_FF))?;
+///
+/// let mut parts = MultiSprite::new();
+/// parts.push(SpritePart::new(hat));
+/// // The shadow is covered by the body where they overlap
+/// parts.push(SpritePart::new(shadow).with_offset(0, 1).with_z(-1));
+///
+/// let mut sprite = Sprite::new(body);
+/// sprite.set_pos(0, 0);
+/// world.create_entity().with(sprite).with(parts).build();
+///
+/// let buffer = testing::render(&mut world, 2, 3);
+/// let colors: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+/// assert_eq!(
+///     colors,
+///     [0xFF_00_00, 0xFF_00_00, 0xFF_FF_FF, 0xFF_FF_FF, 0x33_33_33, 0x33_33_33]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultiSprite {
+    /// Parts sorted by their z-order.
+    parts: Vec<SpritePart>,
+}
+
+impl Component for MultiSprite {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl MultiSprite {
+    /// Create the component without any parts, so only the sprite is drawn.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a part that's drawn with the sprite.
+    pub fn push(&mut self, part: SpritePart) {
+        // Keep the parts sorted, after the parts with the same z-order
+        let index = self.parts.partition_point(|other| other.z <= part.z);
+        self.parts.insert(index, part);
+    }
+
+    /// Remove all parts.
+    pub fn clear(&mut self) {
+        self.parts.clear();
+    }
+
+    /// Get all parts sorted by their z-order.
+    pub fn parts(&self) -> &[SpritePart] {
+        &self.parts
+    }
+
+    /// The parts drawn below and the parts drawn above the sprite.
+    pub(crate) fn split(&self) -> (&[SpritePart], &[SpritePart]) {
+        self.parts
+            .split_at(self.parts.partition_point(|part| part.z < 0))
+    }
+}
@@ -0,0 +1,14 @@
+//! Ordering of the sprites when rendering.
+
+use crate::Sprite;
+use specs::prelude::*;
+
+/// Sort the sprites with their entities by their layer and z-order.
+///
+/// Specs joins the sprites in the order of their entity ids, the stable sort keeps that order for
+/// sprites with the same layer and z-order so the same world always results in the same frame.
+pub(crate) fn sort(mut sorted: Vec<(Entity, &Sprite)>) -> Vec<(Entity, &Sprite)> {
+    sorted.sort_by_key(|(_, sprite)| (sprite.layer, sprite.z));
+
+    sorted
+}
@@ -0,0 +1,201 @@
+//! Showing how often every pixel is drawn.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    PixelBuffer, SpriteImage,
+};
+use specs::prelude::*;
+use web_time::Instant;
+
+/// Colors of the heatmap from no writes to the maximum amount of writes.
+const GRADIENT: [u32; 5] = [0x00_00_00, 0x00_00_FF, 0x00_FF_00, 0xFF_FF_00, 0xFF_00_00];
+
+/// Debug resource counting how many times every pixel is written in a frame, shown as a heatmap
+/// over the frame by the [`OverdrawHeatmapSystem`].
+///
+/// The [`RenderSystem`](crate::RenderSystem), the
+/// [`TilemapRenderSystem`](crate::TilemapRenderSystem) and the
+/// [`ScrollingBackgroundSystem`](crate::ScrollingBackgroundSystem) only count their pixels when
+/// the resource is added to the world. Every visible pixel of a sprite is counted, regardless of
+/// its effects. The counts of a frame are kept until the next frame is drawn.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     load, OverdrawHeatmap, OverdrawHeatmapSystem, PixelBuffer, RenderSystem, Sprite,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(4, 4));
+/// world.insert(OverdrawHeatmap::default());
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     // Draw the heatmap over everything
+///     .with_thread_local(OverdrawHeatmapSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// // Stack the same sprite three times in the top left corner
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?;
+/// for _ in 0..3 {
+///     world.create_entity().with(Sprite::new(sprite_ref.clone())).build();
+/// }
+///
+/// dispatcher.dispatch(&world);
+///
+/// let heatmap = world.read_resource::<OverdrawHeatmap>();
+/// assert_eq!(heatmap.count(1, 1), 3);
+/// assert_eq!(heatmap.count(3, 3), 0);
+/// assert_eq!(heatmap.max_count(), 3);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct OverdrawHeatmap {
+    /// Amount of writes at every pixel.
+    counts: PixelBuffer,
+    /// Whether the counts belong to a frame that's already shown.
+    finished: bool,
+    /// Amount of writes that's shown as the hottest color.
+    pub max: u32,
+    /// How much the heatmap covers the frame, from `0.0` for invisible to `1.0` for completely.
+    pub opacity: f32,
+}
+
+impl Default for OverdrawHeatmap {
+    fn default() -> Self {
+        Self {
+            counts: PixelBuffer::new(0, 0),
+            finished: false,
+            max: 8,
+            opacity: 0.75,
+        }
+    }
+}
+
+impl OverdrawHeatmap {
+    /// Amount of times the pixel was written in the last frame, `0` when it's outside of the
+    /// buffer.
+    pub fn count(&self, x: usize, y: usize) -> u32 {
+        if x < self.counts.width() && y < self.counts.height() {
+            self.counts.pixels()[y * self.counts.width() + x]
+        } else {
+            0
+        }
+    }
+
+    /// Amount of times the pixel that's written the most was written in the last frame.
+    pub fn max_count(&self) -> u32 {
+        self.counts
+            .pixels()
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Average amount of writes per pixel in the last frame.
+    pub fn average_count(&self) -> f64 {
+        let pixels = self.counts.pixels();
+        if pixels.is_empty() {
+            return 0.0;
+        }
+
+        pixels.iter().map(|&count| f64::from(count)).sum::<f64>() / pixels.len() as f64
+    }
+
+    /// Start counting a new frame when the last one is shown or the buffer is resized.
+    fn start_frame(&mut self, width: usize, height: usize) {
+        if self.finished || self.counts.width() != width || self.counts.height() != height {
+            self.counts = PixelBuffer::new(width, height);
+            self.finished = false;
+        }
+    }
+
+    /// Count the visible pixels of the image stretched to the size at the position on a buffer of
+    /// the size.
+    pub(crate) fn add(
+        &mut self,
+        image: &SpriteImage,
+        pos: (i32, i32),
+        size: (usize, usize),
+        buffer_size: (usize, usize),
+    ) {
+        self.start_frame(buffer_size.0, buffer_size.1);
+
+        if size == (image.width(), image.height()) {
+            image.blit_with(&mut self.counts, pos, |_, count, _, _| *count += 1);
+        } else {
+            image.blit_scaled_with(&mut self.counts, pos, size, |_, count, _, _| *count += 1);
+        }
+    }
+
+    /// Color of the amount of writes, blended between the colors of the gradient.
+    fn color(&self, count: u32) -> u32 {
+        let position =
+            (count as f32 / self.max.max(1) as f32).min(1.0) * (GRADIENT.len() - 1) as f32;
+        let index = (position as usize).min(GRADIENT.len() - 2);
+        let fraction = position - index as f32;
+
+        mix(GRADIENT[index], GRADIENT[index + 1], fraction)
+    }
+
+    /// Draw the counts of the last frame over the buffer.
+    pub fn draw(&self, buffer: &mut PixelBuffer) {
+        if self.counts.width() != buffer.width() || self.counts.height() != buffer.height() {
+            return;
+        }
+
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        for (pixel, &count) in buffer.pixels_mut().iter_mut().zip(self.counts.pixels()) {
+            *pixel = 0xFF_00_00_00 | mix(*pixel, self.color(count), opacity);
+        }
+    }
+}
+
+/// Blend the color channels of both colors, `0.0` is the first color and `1.0` the second.
+fn mix(from: u32, to: u32, fraction: f32) -> u32 {
+    let channel = |shift: u32| {
+        let from = (from >> shift & 0xFF) as f32;
+        let to = (to >> shift & 0xFF) as f32;
+
+        ((from + (to - from) * fraction).round() as u32) << shift
+    };
+
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Specs system drawing the [`OverdrawHeatmap`] resource over the buffer.
+///
+/// Does nothing when the resource doesn't exist. It has to run after everything else is drawn,
+/// so add it last with `.with_thread_local()` or make it depend on all drawing systems.
+#[derive(Debug, Default)]
+pub struct OverdrawHeatmapSystem;
+impl<'a> System<'a> for OverdrawHeatmapSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Write<'a, OverdrawHeatmap>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, heatmap, profiler): Self::SystemData) {
+        let start = Instant::now();
+        if let Some(mut heatmap) = heatmap {
+            // Nothing was drawn when the last frame is still there
+            heatmap.start_frame(buffer.width(), buffer.height());
+
+            heatmap.draw(&mut buffer);
+            heatmap.finished = true;
+        }
+
+        profiler::record(
+            profiler,
+            RenderPhase::PostProcess,
+            "OverdrawHeatmapSystem",
+            start,
+        );
+    }
+}
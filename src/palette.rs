@@ -0,0 +1,177 @@
+//! Remapping the colors of the rendered scene to a limited palette.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    PixelBuffer,
+};
+use specs::prelude::*;
+use std::collections::HashMap;
+use web_time::Instant;
+
+/// Limited set of colors every pixel is mapped to, the nearest color is picked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    /// Colors in the `0x00RRGGBB` format.
+    colors: Vec<u32>,
+}
+
+impl Palette {
+    /// Create a palette from the colors, an empty palette keeps all colors as they are.
+    pub fn new(colors: Vec<u32>) -> Self {
+        Self {
+            colors: colors.into_iter().map(|color| color & 0xFF_FF_FF).collect(),
+        }
+    }
+
+    /// The four greens of the original Game Boy.
+    pub fn gameboy() -> Self {
+        Self::new(vec![0x0F_38_0F, 0x30_62_30, 0x8B_AC_0F, 0x9B_BC_0F])
+    }
+
+    /// Get the colors.
+    pub fn colors(&self) -> &[u32] {
+        &self.colors
+    }
+
+    /// Get the color of the palette closest to the color, the alpha channel is kept.
+    pub fn nearest(&self, color: u32) -> u32 {
+        let channels = |color: u32| [16, 8, 0].map(|shift| ((color >> shift) & 0xFF) as i32);
+        let target = channels(color);
+
+        self.colors
+            .iter()
+            .min_by_key(|&&candidate| {
+                channels(candidate)
+                    .iter()
+                    .zip(target.iter())
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<i32>()
+            })
+            .map_or(color, |nearest| (color & 0xFF_00_00_00) | nearest)
+    }
+
+    /// Map all pixels of the buffer to the nearest colors of the palette.
+    pub fn remap(&self, buffer: &mut PixelBuffer) {
+        if self.colors.is_empty() {
+            return;
+        }
+
+        // Scenes contain few distinct colors, so remember the ones already looked up
+        let mut cache = HashMap::new();
+        for pixel in buffer.pixels_mut().iter_mut() {
+            let color = *pixel;
+            *pixel = *cache.entry(color).or_insert_with(|| self.nearest(color));
+        }
+    }
+}
+
+/// Resource with named [`Palette`]s, the active one is applied to everything drawn by the
+/// [`PaletteSystem`].
+///
+/// Layers of the [`CompositorSystem`](crate::CompositorSystem) can use their own palette with
+/// [`CompositeLayer::with_palette`](crate::CompositeLayer::with_palette).
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{Palette, PaletteSystem, Palettes, PixelBuffer};
+///
+/// let mut world = World::new();
+/// let mut buffer = PixelBuffer::new(2, 1);
+/// buffer.pixels_mut().copy_from_slice(&[0x10_10_10, 0xF0_F0_F0]);
+/// world.insert(buffer);
+///
+/// let mut palettes = Palettes::new();
+/// palettes.insert("mono", Palette::new(vec![0x00_00_00, 0xFF_FF_FF]));
+/// palettes.insert("gameboy", Palette::gameboy());
+/// palettes.set_active("mono");
+/// world.insert(palettes);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(PaletteSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+///
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels(), &[0x00_00_00, 0xFF_FF_FF]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Palettes {
+    /// Palettes by their name.
+    palettes: HashMap<String, Palette>,
+    /// Name of the palette applied to the whole scene.
+    active: Option<String>,
+}
+
+impl Palettes {
+    /// Create an empty collection of palettes without an active one.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a palette with the name, returns the palette it replaces.
+    pub fn insert<S>(&mut self, name: S, palette: Palette) -> Option<Palette>
+    where
+        S: Into<String>,
+    {
+        self.palettes.insert(name.into(), palette)
+    }
+
+    /// Remove the palette with the name.
+    pub fn remove(&mut self, name: &str) -> Option<Palette> {
+        self.palettes.remove(name)
+    }
+
+    /// Get the palette with the name.
+    pub fn get(&self, name: &str) -> Option<&Palette> {
+        self.palettes.get(name)
+    }
+
+    /// Apply the palette with the name to the whole scene.
+    pub fn set_active<S>(&mut self, name: S)
+    where
+        S: Into<String>,
+    {
+        self.active = Some(name.into());
+    }
+
+    /// Keep the colors of the whole scene as they are.
+    pub fn clear_active(&mut self) {
+        self.active = None;
+    }
+
+    /// Get the name of the palette applied to the whole scene.
+    pub fn active_name(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Get the palette applied to the whole scene, `None` when there's none or it doesn't exist.
+    pub fn active(&self) -> Option<&Palette> {
+        self.active
+            .as_ref()
+            .and_then(|name| self.palettes.get(name))
+    }
+}
+
+/// Specs system mapping the [`PixelBuffer`] to the active palette of the [`Palettes`] resource.
+///
+/// Run it with `.with_thread_local()` after everything is drawn. Nothing happens when there's no
+/// active palette.
+#[derive(Debug, Default)]
+pub struct PaletteSystem;
+impl<'a> System<'a> for PaletteSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Palettes>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, palettes, profiler): Self::SystemData) {
+        let start = Instant::now();
+
+        if let Some(palette) = palettes.as_ref().and_then(|palettes| palettes.active()) {
+            palette.remap(&mut buffer);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "PaletteSystem", start);
+    }
+}
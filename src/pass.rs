@@ -0,0 +1,211 @@
+//! The order in which the parts of a frame are drawn.
+
+use specs::{prelude::*, RunNow};
+use std::{collections::HashMap, fmt};
+
+/// System that's run directly on the world.
+type BoxedSystem = Box<dyn for<'a> RunNow<'a>>;
+
+/// Step of drawing a frame, run by the [`RenderPasses`] in the order of the [`RenderOrder`]
+/// resource.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RenderPass {
+    /// Clearing the buffer.
+    Clear,
+    /// Drawing the tilemaps and backgrounds behind everything else.
+    Tilemap,
+    /// Drawing the sprites.
+    Sprites,
+    /// Drawing particles and weather.
+    Particles,
+    /// Drawing text.
+    Text,
+    /// Drawing debug information over the scene.
+    Debug,
+    /// Effects applied to the whole buffer after everything is drawn.
+    PostProcess,
+    /// Step with a name, e.g. to draw the sprites of some layers separately.
+    Custom(&'static str),
+}
+
+/// Resource with the order in which the [`RenderPasses`] run the render passes.
+///
+/// Passes can be inserted, removed and moved between frames without building the dispatcher
+/// again. Passes that aren't in the order aren't run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOrder {
+    /// Passes in the order they are run.
+    passes: Vec<RenderPass>,
+}
+
+impl Default for RenderOrder {
+    fn default() -> Self {
+        Self::new(vec![
+            RenderPass::Clear,
+            RenderPass::Tilemap,
+            RenderPass::Sprites,
+            RenderPass::Particles,
+            RenderPass::Text,
+            RenderPass::Debug,
+            RenderPass::PostProcess,
+        ])
+    }
+}
+
+impl RenderOrder {
+    /// Run the passes in this order.
+    pub fn new(passes: Vec<RenderPass>) -> Self {
+        Self { passes }
+    }
+
+    /// Get the passes in the order they are run.
+    pub fn passes(&self) -> &[RenderPass] {
+        &self.passes
+    }
+
+    /// Run the pass last.
+    pub fn push(&mut self, pass: RenderPass) {
+        self.passes.push(pass);
+    }
+
+    /// Run the pass right before another pass, returns `false` when the other pass isn't in the
+    /// order and nothing is inserted.
+    pub fn insert_before(&mut self, before: &RenderPass, pass: RenderPass) -> bool {
+        match self.position(before) {
+            Some(index) => {
+                self.passes.insert(index, pass);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Run the pass right after another pass, returns `false` when the other pass isn't in the
+    /// order and nothing is inserted.
+    pub fn insert_after(&mut self, after: &RenderPass, pass: RenderPass) -> bool {
+        match self.position(after) {
+            Some(index) => {
+                self.passes.insert(index + 1, pass);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop running the pass, returns whether it was in the order.
+    pub fn remove(&mut self, pass: &RenderPass) -> bool {
+        let len = self.passes.len();
+        self.passes.retain(|other| other != pass);
+
+        self.passes.len() != len
+    }
+
+    /// Get where the pass is in the order.
+    fn position(&self, pass: &RenderPass) -> Option<usize> {
+        self.passes.iter().position(|other| other == pass)
+    }
+}
+
+/// Systems drawing a frame grouped by their [`RenderPass`], run in the order of the
+/// [`RenderOrder`] resource.
+///
+/// Use it instead of adding the render systems to the dispatcher, after dispatching the other
+/// systems. The systems of a pass run in the order they were added, the default order is used
+/// when there's no [`RenderOrder`] resource.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{
+///     load, PixelBuffer, RenderOrder, RenderPass, RenderPasses, RenderSystem, Sprite,
+///     TextRenderSystem,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(2, 2));
+///
+/// let mut passes = RenderPasses::new()
+///     .with_pass(RenderPass::Sprites, RenderSystem::default())
+///     .with_pass(RenderPass::Text, TextRenderSystem);
+/// passes.setup(&mut world);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+/// world.create_entity().with(Sprite::new(sprite_ref)).build();
+///
+/// // Skip drawing the sprites
+/// world.write_resource::<RenderOrder>().remove(&RenderPass::Sprites);
+/// passes.run(&world);
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[0], 0);
+///
+/// // Draw them again
+/// world
+///     .write_resource::<RenderOrder>()
+///     .insert_before(&RenderPass::Text, RenderPass::Sprites);
+/// passes.run(&world);
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[0] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct RenderPasses {
+    /// Systems of every pass in the order they run.
+    passes: HashMap<RenderPass, Vec<BoxedSystem>>,
+}
+
+impl RenderPasses {
+    /// Create the passes without any systems.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run the system as part of the pass, after the systems already added to it.
+    pub fn with_pass<S>(mut self, pass: RenderPass, system: S) -> Self
+    where
+        S: for<'a> RunNow<'a> + 'static,
+    {
+        self.passes.entry(pass).or_default().push(Box::new(system));
+
+        self
+    }
+
+    /// Set up all systems and insert the [`RenderOrder`] resource when it doesn't exist.
+    pub fn setup(&mut self, world: &mut World) {
+        world
+            .entry::<RenderOrder>()
+            .or_insert_with(RenderOrder::default);
+
+        for system in self.passes.values_mut().flatten() {
+            system.setup(world);
+        }
+    }
+
+    /// Run the systems of all passes in the order of the [`RenderOrder`] resource.
+    pub fn run(&mut self, world: &World) {
+        let order = world
+            .try_fetch::<RenderOrder>()
+            .map(|order| RenderOrder::clone(&order))
+            .unwrap_or_default();
+
+        for pass in order.passes() {
+            for system in self.passes.get_mut(pass).into_iter().flatten() {
+                system.run_now(world);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for RenderPasses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.passes
+                    .iter()
+                    .map(|(pass, systems)| (pass, systems.len())),
+            )
+            .finish()
+    }
+}
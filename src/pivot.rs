@@ -0,0 +1,97 @@
+/// The point around which a rotated sprite turns.
+///
+/// The pivot stays at the same screen position for every rotation of the sprite, the rotated
+/// image is moved around it.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load_rotations, testing, Pivot, Sprite};
+///
+/// const MASK_COLOR: u32 = 0xFF00FF;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+///
+/// // A green horizontal bar of 4 pixels with a red left end
+/// #[rustfmt::skip]
+/// let pixels = [
+///     0xFF0000, 0xFF00, 0xFF00, 0xFF00,
+///     MASK_COLOR, MASK_COLOR, MASK_COLOR, MASK_COLOR,
+/// ];
+/// let sprite = BlitBuffer::from_buffer(&pixels, 4, MASK_COLOR);
+/// let mut sprite = Sprite::new(load_rotations(sprite, 4)?);
+/// sprite.set_pos(5, 5);
+///
+/// // Swing the bar around its left end instead of its center
+/// sprite.set_pivot(Pivot::Pixel(0, 0));
+/// sprite.set_rot(90);
+/// world.create_entity().with(sprite).build();
+///
+/// // The left end stays in place and the bar now points down
+/// let buffer = testing::render(&mut world, 12, 12);
+/// assert_eq!(buffer.pixels()[5 * 12 + 5] & 0xFFFFFF, 0xFF0000);
+/// assert_eq!(buffer.pixels()[8 * 12 + 5] & 0xFFFFFF, 0xFF00);
+/// assert_eq!(buffer.pixels()[5 * 12 + 8], 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Pivot {
+    /// Rotate around the center of the sprite.
+    #[default]
+    Center,
+    /// Rotate around the top left corner of the sprite.
+    TopLeft,
+    /// Rotate around the top right corner of the sprite.
+    TopRight,
+    /// Rotate around the bottom left corner of the sprite.
+    BottomLeft,
+    /// Rotate around the bottom right corner of the sprite.
+    BottomRight,
+    /// Rotate around the center of the pixel at the (x, y) coordinates of the unrotated sprite.
+    ///
+    /// The coordinates may lie outside of the sprite.
+    Pixel(i32, i32),
+}
+
+impl Pivot {
+    /// Get the position of the pivot relative to the top left corner of a sprite with this size.
+    pub(crate) fn point(self, width: i32, height: i32) -> (f64, f64) {
+        let (width, height) = (f64::from(width), f64::from(height));
+
+        match self {
+            Pivot::Center => (width / 2.0, height / 2.0),
+            Pivot::TopLeft => (0.0, 0.0),
+            Pivot::TopRight => (width, 0.0),
+            Pivot::BottomLeft => (0.0, height),
+            Pivot::BottomRight => (width, height),
+            Pivot::Pixel(x, y) => (f64::from(x) + 0.5, f64::from(y) + 0.5),
+        }
+    }
+
+    /// Calculate how far a sprite rotated around its center needs to be moved so it appears to be
+    /// rotated around this pivot instead.
+    pub(crate) fn offset(self, width: i32, height: i32, degrees: f64) -> (i32, i32) {
+        if self == Pivot::Center {
+            return (0, 0);
+        }
+
+        let (pivot_x, pivot_y) = self.point(width, height);
+        // Vector from the center to the pivot
+        let dx = pivot_x - f64::from(width) / 2.0;
+        let dy = pivot_y - f64::from(height) / 2.0;
+
+        // The pivot must stay in place, so the rotated image moves by the difference between the
+        // original and the rotated vector
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let rotated_x = dx * cos - dy * sin;
+        let rotated_y = dx * sin + dy * cos;
+
+        (
+            (dx - rotated_x).round() as i32,
+            (dy - rotated_y).round() as i32,
+        )
+    }
+}
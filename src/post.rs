@@ -0,0 +1,159 @@
+//! Effects applied to the whole buffer after the sprites are rendered.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    PixelBuffer, Time,
+};
+use specs::prelude::*;
+use std::{fmt, time::Duration};
+use web_time::Instant;
+
+/// Function returning how many pixels a row is moved to the right at a moment.
+pub type RowOffsetFn = dyn Fn(usize, Duration) -> i32 + Send + Sync;
+
+impl PixelBuffer {
+    /// Move every row of the buffer horizontally by the amount of pixels returned by the function
+    /// for that row, pixels moved out on one side come back on the other side.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(3, 2);
+    /// buffer.pixels_mut().copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+    ///
+    /// // Move the first row one pixel to the right and the second one to the left
+    /// buffer.displace_rows(|row| if row == 0 { 1 } else { -1 });
+    /// assert_eq!(buffer.pixels(), &[3, 1, 2, 5, 6, 4]);
+    /// ```
+    pub fn displace_rows<F>(&mut self, mut offset: F)
+    where
+        F: FnMut(usize) -> i32,
+    {
+        let width = self.width();
+        if width == 0 {
+            return;
+        }
+
+        for (index, row) in self.pixels_mut().chunks_exact_mut(width).enumerate() {
+            let offset = offset(index).rem_euclid(width as i32) as usize;
+            if offset != 0 {
+                row.rotate_right(offset);
+            }
+        }
+    }
+}
+
+/// Resource describing how far every row of the buffer is moved by the
+/// [`ScanlineDisplacementSystem`], for full screen water, shockwave and glitch effects.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{PixelBuffer, RenderSystem, ScanlineDisplacement, ScanlineDisplacementSystem};
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(320, 240));
+///
+/// // Let the whole screen wobble like it's underwater
+/// world.insert(ScanlineDisplacement::sine(3.0, 40.0, 0.5));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     // Must run after the sprites are rendered
+///     .with_thread_local(ScanlineDisplacementSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+/// ```
+pub struct ScanlineDisplacement {
+    /// Pixels a row is moved to the right.
+    offset: Box<RowOffsetFn>,
+}
+
+impl ScanlineDisplacement {
+    /// Move the rows with a function receiving the row and the total time passed.
+    pub fn new<F>(offset: F) -> Self
+    where
+        F: Fn(usize, Duration) -> i32 + Send + Sync + 'static,
+    {
+        Self {
+            offset: Box::new(offset),
+        }
+    }
+
+    /// Move the rows with a sine wave, with the amplitude and wavelength in pixels and the speed
+    /// in waves per second.
+    pub fn sine(amplitude: f32, wavelength: f32, speed: f32) -> Self {
+        Self::new(move |row, elapsed| {
+            if wavelength == 0.0 {
+                return 0;
+            }
+
+            let phase = row as f32 / wavelength + elapsed.as_secs_f32() * speed;
+
+            (amplitude * (phase * std::f32::consts::TAU).sin()).round() as i32
+        })
+    }
+
+    /// Move every row by a random amount up to the amplitude, the rows get new offsets the amount
+    /// of times per second.
+    pub fn noise(amplitude: u32, changes_per_second: f32) -> Self {
+        Self::new(move |row, elapsed| {
+            if amplitude == 0 {
+                return 0;
+            }
+
+            let step = (elapsed.as_secs_f32() * changes_per_second) as u64;
+
+            // Hash the row and the step, based on SplitMix64
+            let mut hash = (row as u64) << 32 ^ step;
+            hash = hash.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            hash ^= hash >> 31;
+
+            (hash % (u64::from(amplitude) * 2 + 1)) as i32 - amplitude as i32
+        })
+    }
+
+    /// Pixels the row is moved to the right at the moment.
+    pub fn offset(&self, row: usize, elapsed: Duration) -> i32 {
+        (self.offset)(row, elapsed)
+    }
+}
+
+impl fmt::Debug for ScanlineDisplacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ScanlineDisplacement")
+    }
+}
+
+/// Specs system moving the rows of the buffer with the [`ScanlineDisplacement`] resource.
+///
+/// Does nothing when the resource doesn't exist. It has to run after the
+/// [`RenderSystem`](crate::RenderSystem), add it with `.with_thread_local()` after it or with
+/// `.with()` depending on it.
+#[derive(Debug, Default)]
+pub struct ScanlineDisplacementSystem;
+impl<'a> System<'a> for ScanlineDisplacementSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Read<'a, Time>,
+        Option<Read<'a, ScanlineDisplacement>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, time, displacement, profiler): Self::SystemData) {
+        let start = Instant::now();
+        if let Some(displacement) = displacement {
+            let elapsed = time.elapsed();
+            buffer.displace_rows(|row| displacement.offset(row, elapsed));
+        }
+
+        profiler::record(
+            profiler,
+            RenderPhase::PostProcess,
+            "ScanlineDisplacementSystem",
+            start,
+        );
+    }
+}
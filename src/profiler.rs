@@ -0,0 +1,232 @@
+//! Measuring where the time of a frame is spent.
+
+use specs::prelude::*;
+use std::{collections::VecDeque, time::Duration};
+use web_time::Instant;
+
+/// Part of rendering a frame that's timed by the [`RenderProfiler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderPhase {
+    /// Clearing the buffer before drawing by the [`ClearSystem`](crate::ClearSystem).
+    Clear,
+    /// Ordering the sprites by their layer and z-order.
+    Sort,
+    /// Skipping the sprites that fall outside of the buffer.
+    Cull,
+    /// Drawing the sprites, tilemaps and backgrounds.
+    Blit,
+    /// Effects applied to the whole buffer after everything is drawn.
+    PostProcess,
+}
+
+impl RenderPhase {
+    /// All phases in the order they happen.
+    pub const ALL: [RenderPhase; 5] = [
+        RenderPhase::Clear,
+        RenderPhase::Sort,
+        RenderPhase::Cull,
+        RenderPhase::Blit,
+        RenderPhase::PostProcess,
+    ];
+
+    /// Position in the list of all phases.
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Durations of the last frames of something that's timed.
+#[derive(Debug, Clone, Default)]
+struct Timings {
+    /// Time spent in the frame that's not finished yet.
+    current: Duration,
+    /// Time spent in the finished frames, the newest last.
+    history: VecDeque<Duration>,
+}
+
+impl Timings {
+    /// Store the time of the current frame and start a new one.
+    fn finish_frame(&mut self, window: usize) {
+        self.history.push_back(self.current);
+        while self.history.len() > window {
+            self.history.pop_front();
+        }
+
+        self.current = Duration::default();
+    }
+
+    /// Time spent in the last finished frame.
+    fn last(&self) -> Duration {
+        self.history.back().copied().unwrap_or_default()
+    }
+
+    /// Average time spent in the stored frames.
+    fn average(&self) -> Duration {
+        if self.history.is_empty() {
+            return Duration::default();
+        }
+
+        self.history.iter().sum::<Duration>() / self.history.len() as u32
+    }
+}
+
+/// Resource recording the time spent in every [`RenderPhase`] and every system of this crate.
+///
+/// The systems only measure themselves when the resource is added to the world. Times are summed
+/// until [`RenderProfiler::finish_frame`] is called, which should happen once after dispatching a
+/// frame. The averages are calculated over the last frames.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{PixelBuffer, RenderPhase, RenderProfiler, RenderSystem};
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(64, 64));
+///
+/// // Average over the last 30 frames
+/// world.insert(RenderProfiler::new(30));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// for _ in 0..10 {
+///     // Clearing is measured manually
+///     world.exec(|(mut profiler, mut buffer): (Write<RenderProfiler>, Write<PixelBuffer>)| {
+///         profiler.measure(RenderPhase::Clear, || buffer.clear(0));
+///     });
+///
+///     dispatcher.dispatch(&world);
+///     world.write_resource::<RenderProfiler>().finish_frame();
+/// }
+///
+/// let profiler = world.read_resource::<RenderProfiler>();
+/// assert_eq!(profiler.frames(), 10);
+/// assert!(profiler.system_names().any(|name| name == "RenderSystem"));
+/// println!("blitting takes {:?}", profiler.average(RenderPhase::Blit));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenderProfiler {
+    /// Amount of frames the averages are calculated over.
+    window: usize,
+    /// Timings of every phase.
+    phases: [Timings; 5],
+    /// Timings of every system by name.
+    systems: Vec<(&'static str, Timings)>,
+}
+
+impl Default for RenderProfiler {
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+impl RenderProfiler {
+    /// Create a profiler averaging over the amount of frames.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            phases: Default::default(),
+            systems: Vec::new(),
+        }
+    }
+
+    /// Add time spent in the phase to the current frame.
+    pub fn record(&mut self, phase: RenderPhase, duration: Duration) {
+        self.phases[phase.index()].current += duration;
+    }
+
+    /// Add time spent in the system to the current frame.
+    pub fn record_system(&mut self, name: &'static str, duration: Duration) {
+        match self.systems.iter_mut().find(|(system, _)| *system == name) {
+            Some((_, timings)) => timings.current += duration,
+            None => self.systems.push((
+                name,
+                Timings {
+                    current: duration,
+                    history: VecDeque::new(),
+                },
+            )),
+        }
+    }
+
+    /// Call the function and add the time it took to the phase.
+    pub fn measure<F, R>(&mut self, phase: RenderPhase, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let start = Instant::now();
+        let result = f();
+        self.record(phase, start.elapsed());
+
+        result
+    }
+
+    /// Store the times of the current frame and start a new frame.
+    pub fn finish_frame(&mut self) {
+        let window = self.window;
+        for timings in self.phases.iter_mut() {
+            timings.finish_frame(window);
+        }
+        for (_, timings) in self.systems.iter_mut() {
+            timings.finish_frame(window);
+        }
+    }
+
+    /// Amount of finished frames the averages are calculated over.
+    pub fn frames(&self) -> usize {
+        self.phases[0].history.len()
+    }
+
+    /// Time spent in the phase in the last finished frame.
+    pub fn last(&self, phase: RenderPhase) -> Duration {
+        self.phases[phase.index()].last()
+    }
+
+    /// Average time spent in the phase per frame.
+    pub fn average(&self, phase: RenderPhase) -> Duration {
+        self.phases[phase.index()].average()
+    }
+
+    /// Average time spent in all phases per frame.
+    pub fn average_total(&self) -> Duration {
+        self.phases.iter().map(Timings::average).sum()
+    }
+
+    /// Names of all systems that recorded their time.
+    pub fn system_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.systems.iter().map(|(name, _)| *name)
+    }
+
+    /// Time spent in the system in the last finished frame, `None` when it never recorded its
+    /// time.
+    pub fn system_last(&self, name: &str) -> Option<Duration> {
+        self.systems
+            .iter()
+            .find(|(system, _)| *system == name)
+            .map(|(_, timings)| timings.last())
+    }
+
+    /// Average time spent in the system per frame, `None` when it never recorded its time.
+    pub fn system_average(&self, name: &str) -> Option<Duration> {
+        self.systems
+            .iter()
+            .find(|(system, _)| *system == name)
+            .map(|(_, timings)| timings.average())
+    }
+}
+
+/// Record the time since the start for the phase and the system when the profiler exists.
+pub(crate) fn record(
+    profiler: Option<Write<RenderProfiler>>,
+    phase: RenderPhase,
+    system: &'static str,
+    start: Instant,
+) {
+    if let Some(mut profiler) = profiler {
+        let duration = start.elapsed();
+        profiler.record(phase, duration);
+        profiler.record_system(system, duration);
+    }
+}
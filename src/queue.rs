@@ -0,0 +1,181 @@
+//! One-shot drawing from any system without creating entities.
+
+use crate::{effect::Effects, Blitter, Camera, Font, PixelBuffer, Sprite, SpriteImage, SpriteRef};
+use std::sync::Mutex;
+
+/// Something drawn once by the [`DrawQueue`].
+///
+/// All positions are world positions, they are moved by the [`Camera`]. Only sprites are scaled by
+/// the zoom of the camera, lines, rectangles and text keep their size in pixels.
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    /// A sprite with the top left corner at the position, rotated by the degrees.
+    Sprite {
+        /// Loaded sprite that's drawn.
+        sprite: SpriteRef,
+        /// Position of the top left corner.
+        pos: (i32, i32),
+        /// Rotation in degrees.
+        rot: i16,
+    },
+    /// A line of single pixels between both positions.
+    Line {
+        /// Start of the line.
+        from: (i32, i32),
+        /// End of the line, it's included.
+        to: (i32, i32),
+        /// Color of the pixels.
+        color: u32,
+    },
+    /// A rectangle with the top left corner at the position.
+    Rect {
+        /// Position of the top left corner.
+        pos: (i32, i32),
+        /// Width and height in pixels.
+        size: (usize, usize),
+        /// Color of the pixels.
+        color: u32,
+        /// Whether the rectangle is filled or only the outline is drawn.
+        filled: bool,
+    },
+    /// Text with the top left corner at the position, see [`Font::draw`].
+    Text {
+        /// Font the text is drawn with.
+        font: Font,
+        /// Text that's drawn, every newline starts a new line.
+        text: String,
+        /// Position of the top left corner.
+        pos: (i32, i32),
+        /// Color of the text, `None` to use the colors of the glyphs.
+        color: Option<u32>,
+    },
+}
+
+impl DrawCommand {
+    /// Draw the command on the buffer as seen by the camera, the images are the locked sprite
+    /// store.
+    pub(crate) fn draw<B>(
+        &self,
+        buffer: &mut PixelBuffer,
+        camera: &Camera,
+        blitter: &B,
+        sprite_images: &[SpriteImage],
+    ) where
+        B: Blitter,
+    {
+        match self {
+            DrawCommand::Sprite { sprite, pos, rot } => {
+                let mut sprite = Sprite::new(sprite.clone());
+                sprite.set_rot(*rot);
+
+                let render_info = sprite.render_info(camera.zoom());
+                if let Some(image) = sprite_images.get(render_info.index) {
+                    let pos = camera.to_screen(*pos);
+                    let pos = (pos.0 + render_info.offset.0, pos.1 + render_info.offset.1);
+                    let size = render_info.size(image);
+                    Effects::none().blit(blitter, image, buffer, pos, size);
+                }
+            }
+            DrawCommand::Line { from, to, color } => {
+                buffer.draw_line(camera.to_screen(*from), camera.to_screen(*to), *color)
+            }
+            DrawCommand::Rect {
+                pos,
+                size,
+                color,
+                filled,
+            } => {
+                let pos = camera.to_screen(*pos);
+                if *filled {
+                    buffer.fill_rect(pos, *size, *color);
+                } else {
+                    buffer.draw_rect(pos, *size, *color);
+                }
+            }
+            DrawCommand::Text {
+                font,
+                text,
+                pos,
+                color,
+            } => buffer.draw_text(font, text, camera.to_screen(*pos), *color),
+        }
+    }
+}
+
+/// Resource where any system can add [`DrawCommand`]s that are drawn once.
+///
+/// The [`RenderSystem`](crate::RenderSystem) draws the commands after all sprites, sorted by
+/// their z-order, and removes them afterwards. Commands with the same z-order are drawn in the
+/// order they are added. Adding only needs read access, so systems running in parallel can add
+/// commands at the same time.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{DrawCommand, DrawQueue, PixelBuffer, RenderSystem};
+///
+/// struct DebugSystem;
+/// impl<'a> System<'a> for DebugSystem {
+///     type SystemData = Read<'a, DrawQueue>;
+///
+///     fn run(&mut self, queue: Self::SystemData) {
+///         queue.push(
+///             0,
+///             DrawCommand::Line {
+///                 from: (0, 0),
+///                 to: (7, 7),
+///                 color: 0xFF_00_00,
+///             },
+///         );
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(8, 8));
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(DebugSystem, "debug", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+/// dispatcher.dispatch(&world);
+///
+/// assert_eq!(world.read_resource::<PixelBuffer>().pixels()[7 * 8 + 7], 0xFF_00_00);
+/// assert!(world.read_resource::<DrawQueue>().is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct DrawQueue {
+    /// Commands with their z-order in the order they are added.
+    commands: Mutex<Vec<(i32, DrawCommand)>>,
+}
+
+impl DrawQueue {
+    /// Add a command that's drawn with the z-order, higher values are drawn on top.
+    pub fn push(&self, z: i32, command: DrawCommand) {
+        self.commands.lock().unwrap().push((z, command));
+    }
+
+    /// Amount of commands that aren't drawn yet.
+    pub fn len(&self) -> usize {
+        self.commands.lock().unwrap().len()
+    }
+
+    /// Whether there are no commands waiting to be drawn.
+    pub fn is_empty(&self) -> bool {
+        self.commands.lock().unwrap().is_empty()
+    }
+
+    /// Remove all commands without drawing them.
+    pub fn clear(&self) {
+        self.commands.lock().unwrap().clear();
+    }
+
+    /// Remove all commands sorted in the order they must be drawn.
+    pub(crate) fn take_sorted(&self) -> Vec<(i32, DrawCommand)> {
+        let mut commands = std::mem::take(&mut *self.commands.lock().unwrap());
+
+        // A stable sort keeps the order they were added in for equal z-orders
+        commands.sort_by_key(|(z, _)| *z);
+
+        commands
+    }
+}
@@ -0,0 +1,382 @@
+//! Pseudo-3D rendering of a tile grid as walls, like the first person shooters of the early
+//! nineties.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    Anchor, Depth, DepthBuffer, PixelBuffer, Sprite, SpriteImage,
+};
+use blit::BlitBuffer;
+use specs::prelude::*;
+use web_time::Instant;
+
+/// Resource with a grid of walls that's drawn from a first person view by the
+/// [`RaycastSystem`].
+///
+/// Every tile is one unit wide, tile `0` is empty and tile `n` is a wall with the texture at
+/// index `n - 1`. Tiles without a texture are also empty. The camera is half a tile above the
+/// floor.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Billboard, PixelBuffer, Raycaster, RaycastSystem, RenderSystem, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(32, 24));
+///
+/// // A room of 3x3 tiles with a red wall on top and blue walls elsewhere
+/// let red = BlitBuffer::from_buffer(&[0xFF_00_00; 16], 4, 0xFF_00_FF);
+/// let blue = BlitBuffer::from_buffer(&[0x00_00_FF; 16], 4, 0xFF_00_FF);
+/// #[rustfmt::skip]
+/// let tiles = [
+///     1, 1, 1, 1, 1,
+///     2, 0, 0, 0, 2,
+///     2, 0, 0, 0, 2,
+///     2, 0, 0, 0, 2,
+///     2, 2, 2, 2, 2,
+/// ];
+/// let mut raycaster = Raycaster::new(&tiles, 5, &[red, blue]);
+///
+/// // Stand in the bottom of the room looking at the red wall
+/// raycaster.pos = (2.5, 3.5);
+/// raycaster.floor_color = Some(0x80_80_80);
+/// world.insert(raycaster);
+///
+/// // Place a white sprite in the room
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?;
+/// let mut dispatcher = DispatcherBuilder::new()
+///     // Draw the walls before the sprites
+///     .with_thread_local(RaycastSystem)
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// dispatcher.setup(&mut world);
+/// world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(Billboard::new(2.5, 2.0))
+///     .build();
+/// dispatcher.dispatch(&world);
+///
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[12 * 32 + 2] & 0xFF_FF_FF, 0xFF_00_00);
+/// // Without a ceiling color nothing is drawn above the walls
+/// assert_eq!(buffer.pixels()[0], 0);
+/// assert_eq!(buffer.pixels()[23 * 32] & 0xFF_FF_FF, 0x80_80_80);
+/// // The sprite stands in the middle of the floor
+/// assert_eq!(buffer.pixels()[14 * 32 + 16] & 0xFF_FF_FF, 0xFF_FF_FF);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Raycaster {
+    /// Amount of tiles in a row.
+    width: usize,
+    /// Texture index plus one of every tile.
+    tiles: Vec<u8>,
+    /// Images on the walls.
+    textures: Vec<SpriteImage>,
+    /// Position of the camera in tiles.
+    pub pos: (f32, f32),
+    /// Direction the camera is looking at in degrees, `0.0` looks up the grid and positive values
+    /// turn clockwise.
+    pub angle: f32,
+    /// Horizontal field of view in degrees.
+    pub fov: f32,
+    /// Color drawn above the horizon, nothing is drawn there when it's `None`.
+    pub ceiling_color: Option<u32>,
+    /// Color drawn below the horizon, nothing is drawn there when it's `None`.
+    pub floor_color: Option<u32>,
+}
+
+impl Raycaster {
+    /// Create a grid from rows of tiles and the textures of the walls.
+    pub fn new(tiles: &[u8], width: usize, textures: &[BlitBuffer]) -> Self {
+        let height = tiles.len().checked_div(width).unwrap_or(0);
+
+        Self {
+            width,
+            tiles: tiles[..width * height].to_vec(),
+            textures: textures.iter().map(SpriteImage::from_blit_buffer).collect(),
+            pos: (0.0, 0.0),
+            angle: 0.0,
+            fov: 66.0,
+            ceiling_color: None,
+            floor_color: None,
+        }
+    }
+
+    /// Amount of tiles in a row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Amount of rows.
+    pub fn height(&self) -> usize {
+        self.tiles.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    /// Get the tile at the position, tiles outside of the grid are empty.
+    pub fn tile(&self, x: i64, y: i64) -> u8 {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height() as i64 {
+            return 0;
+        }
+
+        self.tiles[y as usize * self.width + x as usize]
+    }
+
+    /// Change the tile at the position, ignored when it's outside of the grid.
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: u8) {
+        if x < self.width && y < self.height() {
+            self.tiles[y * self.width + x] = tile;
+        }
+    }
+
+    /// Direction the camera is looking at and the direction to the right of it.
+    fn directions(&self) -> ((f32, f32), (f32, f32)) {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+
+        ((sin, -cos), (cos, sin))
+    }
+
+    /// Distance of the camera to the screen in pixels, which is also the height in pixels of a
+    /// tile one unit away.
+    fn focal_length(&self, buffer_width: usize) -> f32 {
+        buffer_width as f32 / 2.0 / (self.fov.to_radians() / 2.0).tan()
+    }
+
+    /// Get the texture of the wall at the position.
+    fn wall(&self, x: i64, y: i64) -> Option<&SpriteImage> {
+        let tile = self.tile(x, y);
+        if tile == 0 {
+            return None;
+        }
+
+        self.textures.get(tile as usize - 1)
+    }
+
+    /// Follow a ray through the grid until it hits a wall.
+    ///
+    /// Returns the texture, the distance along the viewing direction and the horizontal position
+    /// on the texture from `0.0` to `1.0`.
+    fn cast(&self, ray: (f32, f32)) -> Option<(&SpriteImage, f32, f32)> {
+        let mut tile = (self.pos.0.floor() as i64, self.pos.1.floor() as i64);
+
+        // Distance along the ray to the next vertical and horizontal grid line
+        let axis = |pos: f32, tile: i64, ray: f32| {
+            if ray == 0.0 {
+                (0, f32::INFINITY, f32::INFINITY)
+            } else {
+                let delta = ray.recip().abs();
+                if ray < 0.0 {
+                    (-1, (pos - tile as f32) * delta, delta)
+                } else {
+                    (1, (tile as f32 + 1.0 - pos) * delta, delta)
+                }
+            }
+        };
+        let (step_x, mut next_x, delta_x) = axis(self.pos.0, tile.0, ray.0);
+        let (step_y, mut next_y, delta_y) = axis(self.pos.1, tile.1, ray.1);
+
+        // A ray can't cross more grid lines than there are in the grid
+        for _ in 0..self.width + self.height() + 2 {
+            let vertical = next_x < next_y;
+            let distance = if vertical {
+                tile.0 += step_x;
+                next_x += delta_x;
+
+                next_x - delta_x
+            } else {
+                tile.1 += step_y;
+                next_y += delta_y;
+
+                next_y - delta_y
+            };
+
+            if let Some(texture) = self.wall(tile.0, tile.1) {
+                // Keep the textures readable from left to right on every side
+                let (hit, flip) = if vertical {
+                    (self.pos.1 + distance * ray.1, ray.0 < 0.0)
+                } else {
+                    (self.pos.0 + distance * ray.0, ray.1 > 0.0)
+                };
+                let fraction = hit - hit.floor();
+
+                return Some((
+                    texture,
+                    distance,
+                    if flip { 1.0 - fraction } else { fraction },
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// Draw the walls on the buffer and store the distance of every column.
+    pub fn draw(&self, buffer: &mut PixelBuffer, depth_buffer: &mut DepthBuffer) {
+        let (width, height) = (buffer.width(), buffer.height());
+        depth_buffer.reset(width);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // Fill the background
+        let horizon = height / 2;
+        let (ceiling, floor) = buffer.pixels_mut().split_at_mut(horizon * width);
+        if let Some(color) = self.ceiling_color {
+            ceiling.fill(color | 0xFF_00_00_00);
+        }
+        if let Some(color) = self.floor_color {
+            floor.fill(color | 0xFF_00_00_00);
+        }
+
+        let (forward, right) = self.directions();
+        let focal_length = self.focal_length(width);
+        let pixels = buffer.pixels_mut();
+        for x in 0..width {
+            // The direction through the center of the column
+            let side = (x as f32 + 0.5 - width as f32 / 2.0) / focal_length;
+            let ray = (forward.0 + right.0 * side, forward.1 + right.1 * side);
+
+            let (texture, distance, fraction) = match self.cast(ray) {
+                Some(hit) => hit,
+                None => continue,
+            };
+            depth_buffer.set(x, distance);
+
+            let line_height = focal_length / distance;
+            let top = height as f32 / 2.0 - line_height / 2.0;
+            let start = top.max(0.0) as usize;
+            let end = ((top + line_height).ceil().max(0.0) as usize).min(height);
+
+            let texture_x = ((fraction * texture.width() as f32) as usize).min(texture.width() - 1);
+            for y in start..end {
+                let v = (y as f32 + 0.5 - top) / line_height;
+                let texture_y = ((v * texture.height() as f32) as usize).min(texture.height() - 1);
+
+                let color = texture.pixels()[texture_y * texture.width() + texture_x];
+                if texture.is_visible(color) {
+                    pixels[y * width + x] = color;
+                }
+            }
+        }
+    }
+
+    /// Position the sprite of a billboard on the buffer as if it's standing on the floor.
+    ///
+    /// Returns the distance to the camera, which is negative when it's behind it.
+    fn place(
+        &self,
+        billboard: &Billboard,
+        sprite: &mut Sprite,
+        buffer_size: (usize, usize),
+    ) -> f32 {
+        let (forward, right) = self.directions();
+        let relative = (billboard.pos.0 - self.pos.0, billboard.pos.1 - self.pos.1);
+        let distance = relative.0 * forward.0 + relative.1 * forward.1;
+        if distance <= f32::EPSILON {
+            return -1.0;
+        }
+
+        let focal_length = self.focal_length(buffer_size.0);
+        let side = relative.0 * right.0 + relative.1 * right.1;
+        let tile_size = focal_length / distance;
+
+        let x = buffer_size.0 as f32 / 2.0 + side * tile_size;
+        let y = (buffer_size.1 as f32 + tile_size) / 2.0;
+        sprite.set_anchor(Anchor::BottomCenter);
+        sprite.set_pos(x.round() as i32, y.round() as i32);
+
+        let sprite_height = sprite.size().1;
+        if sprite_height > 0 {
+            let scale = f64::from(billboard.size * tile_size) / sprite_height as f64;
+            sprite.set_scale(scale, scale);
+        }
+
+        // Draw the sprites far away first
+        sprite.set_z(-(distance * 256.0) as i32);
+
+        distance
+    }
+}
+
+/// Stand a sprite on the floor of the [`Raycaster`] grid, always facing the camera.
+///
+/// The [`RaycastSystem`] sets the position, scale, anchor and z-order of the [`Sprite`] of the
+/// entity, and adds a [`Depth`] component so it's hidden behind walls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Billboard {
+    /// Position on the grid in tiles.
+    pub pos: (f32, f32),
+    /// Height of the sprite in tiles.
+    pub size: f32,
+}
+
+impl Billboard {
+    /// Place a billboard as high as a tile on the position.
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            pos: (x, y),
+            size: 1.0,
+        }
+    }
+}
+
+impl Component for Billboard {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system drawing the walls of the [`Raycaster`] resource and placing the [`Billboard`]
+/// sprites.
+///
+/// Does nothing when the resource doesn't exist. Run it before the
+/// [`RenderSystem`](crate::RenderSystem) with `.with_thread_local()` so the sprites are drawn on
+/// top of the walls.
+#[derive(Debug, Default)]
+pub struct RaycastSystem;
+impl<'a> System<'a> for RaycastSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Write<'a, DepthBuffer>,
+        Option<Read<'a, Raycaster>>,
+        Entities<'a>,
+        ReadStorage<'a, Billboard>,
+        WriteStorage<'a, Sprite>,
+        WriteStorage<'a, Depth>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut buffer,
+            mut depth_buffer,
+            raycaster,
+            entities,
+            billboards,
+            mut sprites,
+            mut depths,
+            profiler,
+        ): Self::SystemData,
+    ) {
+        let start = Instant::now();
+
+        let raycaster = match raycaster {
+            Some(raycaster) => raycaster,
+            None => return,
+        };
+
+        raycaster.draw(&mut buffer, &mut depth_buffer);
+
+        let buffer_size = (buffer.width(), buffer.height());
+        for (entity, billboard, sprite) in (&entities, &billboards, &mut sprites).join() {
+            let distance = raycaster.place(billboard, sprite, buffer_size);
+
+            // Inserting only fails for dead entities, which aren't joined
+            let _ = depths.insert(entity, Depth(distance));
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "RaycastSystem", start);
+    }
+}
@@ -0,0 +1,180 @@
+//! Freeing the sprites that aren't used anymore.
+
+use crate::{lazy, SpriteError, SpriteImage, SPRITES};
+use lazy_static::lazy_static;
+use specs::prelude::*;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+lazy_static! {
+    /// Slots of sprites whose references are all dropped, waiting to be freed.
+    static ref RELEASED: Mutex<Vec<usize>> = Mutex::new(vec![]);
+    /// Freed slots that can be filled by new sprites.
+    static ref FREE: Mutex<Vec<usize>> = Mutex::new(vec![]);
+}
+
+/// Increased every time the store is cleared, so slots of references from before aren't freed.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Maximum amount of slots in the store.
+static MAX_SPRITES: AtomicUsize = AtomicUsize::new(u32::MAX as usize);
+
+/// Shared by all clones of a sprite reference, releases the slots when the last one is dropped.
+#[derive(Debug)]
+pub(crate) struct SlotGuard {
+    /// Slots in the store of all rotations.
+    slots: Vec<usize>,
+    /// Generation of the store the slots belong to.
+    generation: usize,
+}
+
+impl SlotGuard {
+    /// Guard the slots of newly loaded sprites.
+    pub(crate) fn new(slots: Vec<usize>) -> Self {
+        Self {
+            slots,
+            generation: GENERATION.load(Ordering::SeqCst),
+        }
+    }
+}
+
+impl Drop for SlotGuard {
+    fn drop(&mut self) {
+        // The store can't be locked here because the last reference can be dropped while it's
+        // being read, so the slots are freed later
+        if self.generation == GENERATION.load(Ordering::SeqCst) {
+            if let Ok(mut released) = RELEASED.lock() {
+                released.extend(self.slots.drain(..));
+            }
+        }
+    }
+}
+
+/// Put the images in the store, reusing freed slots first, and get the slots they are put in.
+///
+/// Nothing is stored when the images don't fit in the slots that are left.
+pub(crate) fn store(
+    store: &mut Vec<SpriteImage>,
+    images: Vec<SpriteImage>,
+) -> Result<Vec<usize>, SpriteError> {
+    let mut free = FREE.lock().map_err(|_| SpriteError::StorePoisoned)?;
+
+    let max = MAX_SPRITES.load(Ordering::SeqCst);
+    let new_slots = images.len().saturating_sub(free.len());
+    if store.len().saturating_add(new_slots) > max {
+        return Err(SpriteError::TooManySprites {
+            requested: images.len(),
+            max,
+        });
+    }
+
+    Ok(images
+        .into_iter()
+        .map(|image| match free.pop() {
+            Some(slot) => {
+                store[slot] = image;
+
+                slot
+            }
+            None => {
+                store.push(image);
+
+                store.len() - 1
+            }
+        })
+        .collect())
+}
+
+/// Set the maximum amount of rotations and downscaled versions of sprites that can be loaded at
+/// the same time, by default it's `u32::MAX`.
+///
+/// Loading a sprite that doesn't fit returns [`SpriteError::TooManySprites`], slots freed by
+/// [`reclaim_sprites`] can be filled again. Lowering the maximum doesn't remove sprites that are
+/// already loaded.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::{load, reclaim_sprites, set_max_sprites, SpriteError};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// set_max_sprites(1);
+///
+/// let sprite = || BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF);
+/// let sprite_ref = load(sprite())?;
+/// assert!(matches!(
+///     load(sprite()),
+///     Err(SpriteError::TooManySprites { requested: 1, max: 1 })
+/// ));
+///
+/// // The slot can be reused when the first sprite is freed
+/// drop(sprite_ref);
+/// reclaim_sprites();
+/// load(sprite())?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn set_max_sprites(max: usize) {
+    MAX_SPRITES.store(max, Ordering::SeqCst);
+}
+
+/// Forget all released and freed slots, called when the store is cleared.
+pub(crate) fn reset() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    RELEASED.lock().unwrap().clear();
+    FREE.lock().unwrap().clear();
+}
+
+/// Free the memory of all sprites whose [`SpriteRef`](crate::SpriteRef)s are all dropped.
+///
+/// Returns the amount of freed rotations and downscaled versions. The freed slots are reused by
+/// sprites loaded afterwards. Call it regularly when not using the [`SpriteReclaimSystem`].
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::{load_rotations, reclaim_sprites};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let sprite = BlitBuffer::from_buffer(&[0xFF_FF_FF; 16], 4, 0xFF_00_FF);
+/// let sprite_ref = load_rotations(sprite, 4)?;
+///
+/// // Clones keep the sprite alive
+/// let clone = sprite_ref.clone();
+/// drop(sprite_ref);
+/// assert_eq!(reclaim_sprites(), 0);
+///
+/// drop(clone);
+/// assert_eq!(reclaim_sprites(), 4);
+/// # Ok(())
+/// # }
+/// ```
+pub fn reclaim_sprites() -> usize {
+    let released = std::mem::take(&mut *RELEASED.lock().unwrap());
+    if released.is_empty() {
+        return 0;
+    }
+
+    let mut store = SPRITES.write().unwrap();
+    for &slot in released.iter() {
+        if let Some(image) = store.get_mut(slot) {
+            *image = SpriteImage::from_buffer(&[], 0, 0);
+        }
+    }
+    lazy::forget(&released);
+    FREE.lock().unwrap().extend(released.iter().copied());
+
+    released.len()
+}
+
+/// Specs system calling [`reclaim_sprites`] every frame, so the memory of sprites that aren't
+/// used by any component or handle anymore is freed automatically.
+#[derive(Debug, Default)]
+pub struct SpriteReclaimSystem;
+impl<'a> System<'a> for SpriteReclaimSystem {
+    type SystemData = ();
+
+    fn run(&mut self, _: Self::SystemData) {
+        reclaim_sprites();
+    }
+}
@@ -0,0 +1,113 @@
+//! Algorithms for creating the rotated versions of sprites when loading them.
+
+use crate::{SpriteError, SpriteImage};
+use std::{fmt, sync::Arc};
+
+/// Function rotating an image clockwise by degrees, see [`RotationAlgorithm::Custom`].
+pub type RotateFn = dyn Fn(&SpriteImage, f64) -> SpriteImage + Send + Sync;
+
+/// How the rotated versions of a sprite are created with
+/// [`load_rotations_with`](crate::load_rotations_with).
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::{load_rotations_with, RotationAlgorithm};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let sprite = BlitBuffer::from_buffer(&[0xFF0000; 64 * 64], 64, 0xFF00FF);
+///
+/// // Rotating big sprites with rotsprite takes a long time
+/// let sprite_ref = load_rotations_with(sprite, 64, (0, 360), RotationAlgorithm::NearestNeighbor)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub enum RotationAlgorithm {
+    /// Rotate with the rotsprite algorithm, which keeps the pixel art style intact.
+    ///
+    /// This is the default when the `rotsprite` feature is enabled.
+    #[cfg(feature = "rotsprite")]
+    #[default]
+    RotSprite,
+    /// Rotate by picking the nearest pixel, fast but with jagged edges.
+    #[cfg_attr(not(feature = "rotsprite"), default)]
+    NearestNeighbor,
+    /// Rotate with a function receiving the image and the clockwise rotation in degrees.
+    ///
+    /// The function is not called for a rotation of 0 degrees. The rotated image is centered on the
+    /// original image.
+    Custom(Arc<RotateFn>),
+}
+
+impl RotationAlgorithm {
+    /// Create a rotation algorithm from a function receiving the image and the clockwise rotation
+    /// in degrees.
+    pub fn custom<F>(rotate: F) -> Self
+    where
+        F: Fn(&SpriteImage, f64) -> SpriteImage + Send + Sync + 'static,
+    {
+        RotationAlgorithm::Custom(Arc::new(rotate))
+    }
+
+    /// Create a rotated version of the image.
+    pub(crate) fn rotate(
+        &self,
+        image: &SpriteImage,
+        degrees: f64,
+    ) -> Result<SpriteImage, SpriteError> {
+        // Without rotation there's nothing to do, this keeps loading unrotated sprites cheap
+        if degrees == 0.0 {
+            return Ok(image.clone());
+        }
+
+        match self {
+            #[cfg(feature = "rotsprite")]
+            RotationAlgorithm::RotSprite => {
+                let mask_color = image.mask_color();
+                let (rotated_width, _, rotated) =
+                    rotsprite::rotsprite(image.pixels(), &mask_color, image.width(), degrees)?;
+
+                Ok(image.with_pixels(&rotated, rotated_width))
+            }
+            RotationAlgorithm::NearestNeighbor => Ok(nearest_neighbor(image, degrees)),
+            RotationAlgorithm::Custom(rotate) => Ok(rotate(image, degrees)),
+        }
+    }
+}
+
+impl fmt::Debug for RotationAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "rotsprite")]
+            RotationAlgorithm::RotSprite => write!(f, "RotSprite"),
+            RotationAlgorithm::NearestNeighbor => write!(f, "NearestNeighbor"),
+            RotationAlgorithm::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Rotate the image clockwise by sampling the nearest pixel of the original for every pixel.
+fn nearest_neighbor(image: &SpriteImage, degrees: f64) -> SpriteImage {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (width, height) = (image.width() as f64, image.height() as f64);
+
+    // The bounding box of the rotated image, ignore tiny rounding errors at right angles
+    let rotated_width = (width * cos.abs() + height * sin.abs() - 1e-6).ceil() as usize;
+    let rotated_height = (width * sin.abs() + height * cos.abs() - 1e-6).ceil() as usize;
+
+    let mut pixels = vec![image.mask_color(); rotated_width * rotated_height];
+    for (index, pixel) in pixels.iter_mut().enumerate() {
+        // Center of the pixel relative to the center of the rotated image
+        let x = (index % rotated_width) as f64 + 0.5 - rotated_width as f64 / 2.0;
+        let y = (index / rotated_width) as f64 + 0.5 - rotated_height as f64 / 2.0;
+
+        // Rotate back to find the pixel in the original image
+        let source_x = (x * cos + y * sin + width / 2.0).floor();
+        let source_y = (y * cos - x * sin + height / 2.0).floor();
+        if source_x >= 0.0 && source_x < width && source_y >= 0.0 && source_y < height {
+            *pixel = image.pixels()[source_y as usize * image.width() + source_x as usize];
+        }
+    }
+
+    image.with_pixels(&pixels, rotated_width)
+}
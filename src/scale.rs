@@ -0,0 +1,256 @@
+//! Pixel art scaling filters applied when loading sprites.
+
+use crate::SpriteImage;
+
+/// Algorithm used to upscale a sprite with [`load_scaled`](crate::load_scaled).
+///
+/// All algorithms only output colors that are already in the sprite, so the mask color stays
+/// intact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleAlgorithm {
+    /// Double the size with the Scale2x (EPX) algorithm.
+    Scale2x,
+    /// Triple the size with the Scale3x algorithm.
+    Scale3x,
+    /// Double the size with the xBR algorithm without blending, smoother diagonals than Scale2x.
+    Xbr,
+}
+
+impl ScaleAlgorithm {
+    /// How many times bigger the result is in both directions.
+    pub fn factor(self) -> usize {
+        match self {
+            ScaleAlgorithm::Scale2x | ScaleAlgorithm::Xbr => 2,
+            ScaleAlgorithm::Scale3x => 3,
+        }
+    }
+
+    /// Create a bigger version of the image.
+    pub(crate) fn apply(self, image: &SpriteImage) -> SpriteImage {
+        let source = Source {
+            pixels: image.pixels(),
+            width: image.width() as i32,
+            height: image.height() as i32,
+        };
+
+        let factor = self.factor();
+        let scaled_width = image.width() * factor;
+        let mut scaled = vec![image.mask_color(); scaled_width * image.height() * factor];
+
+        for y in 0..source.height {
+            for x in 0..source.width {
+                let block = match self {
+                    ScaleAlgorithm::Scale2x => scale2x(&source, x, y),
+                    ScaleAlgorithm::Scale3x => scale3x(&source, x, y),
+                    ScaleAlgorithm::Xbr => xbr(&source, x, y),
+                };
+
+                // Copy the block of pixels to the scaled image
+                for (i, pixel) in block.iter().take(factor * factor).enumerate() {
+                    let scaled_x = x as usize * factor + i % factor;
+                    let scaled_y = y as usize * factor + i / factor;
+                    scaled[scaled_y * scaled_width + scaled_x] = *pixel;
+                }
+            }
+        }
+
+        image.with_pixels(&scaled, scaled_width)
+    }
+}
+
+/// Create a version of the image with half the width and height.
+///
+/// Every block of 2x2 pixels becomes the most common visible color in it, or transparent when
+/// less than half of the pixels are visible. Returns `None` when the image is too small.
+pub(crate) fn downscale_half(image: &SpriteImage) -> Option<SpriteImage> {
+    if image.width() < 2 || image.height() < 2 {
+        return None;
+    }
+
+    let source = Source {
+        pixels: image.pixels(),
+        width: image.width() as i32,
+        height: image.height() as i32,
+    };
+    let mask_color = image.mask_color();
+
+    let width = image.width().div_ceil(2);
+    let height = image.height().div_ceil(2);
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let block = [
+                source.get(x * 2, y * 2),
+                source.get(x * 2 + 1, y * 2),
+                source.get(x * 2, y * 2 + 1),
+                source.get(x * 2 + 1, y * 2 + 1),
+            ];
+
+            let visible = block.iter().filter(|&&pixel| pixel != mask_color).count();
+            let pixel = if visible < 2 {
+                mask_color
+            } else {
+                // Prefer the color that appears the most, the first one wins on a tie
+                *block
+                    .iter()
+                    .filter(|&&pixel| pixel != mask_color)
+                    .rev()
+                    .max_by_key(|&&pixel| block.iter().filter(|&&other| other == pixel).count())
+                    .unwrap_or(&mask_color)
+            };
+
+            pixels.push(pixel);
+        }
+    }
+
+    Some(image.with_pixels(&pixels, width))
+}
+
+/// The image that's being scaled.
+struct Source<'a> {
+    pixels: &'a [u32],
+    width: i32,
+    height: i32,
+}
+
+impl Source<'_> {
+    /// Get a pixel, coordinates outside of the image are clamped to the edge.
+    fn get(&self, x: i32, y: i32) -> u32 {
+        let x = x.clamp(0, self.width - 1);
+        let y = y.clamp(0, self.height - 1);
+
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+/// Scale a single pixel to a 2x2 block, the last 5 pixels are unused.
+fn scale2x(source: &Source, x: i32, y: i32) -> [u32; 9] {
+    let e = source.get(x, y);
+    let b = source.get(x, y - 1);
+    let d = source.get(x - 1, y);
+    let f = source.get(x + 1, y);
+    let h = source.get(x, y + 1);
+
+    if b == h || d == f {
+        return [e; 9];
+    }
+
+    [
+        if d == b { d } else { e },
+        if b == f { f } else { e },
+        if d == h { d } else { e },
+        if h == f { f } else { e },
+        e,
+        e,
+        e,
+        e,
+        e,
+    ]
+}
+
+/// Scale a single pixel to a 3x3 block.
+fn scale3x(source: &Source, x: i32, y: i32) -> [u32; 9] {
+    let a = source.get(x - 1, y - 1);
+    let b = source.get(x, y - 1);
+    let c = source.get(x + 1, y - 1);
+    let d = source.get(x - 1, y);
+    let e = source.get(x, y);
+    let f = source.get(x + 1, y);
+    let g = source.get(x - 1, y + 1);
+    let h = source.get(x, y + 1);
+    let i = source.get(x + 1, y + 1);
+
+    if b == h || d == f {
+        return [e; 9];
+    }
+
+    [
+        if d == b { d } else { e },
+        if (d == b && e != c) || (b == f && e != a) {
+            b
+        } else {
+            e
+        },
+        if b == f { f } else { e },
+        if (d == b && e != g) || (d == h && e != a) {
+            d
+        } else {
+            e
+        },
+        e,
+        if (b == f && e != i) || (h == f && e != c) {
+            f
+        } else {
+            e
+        },
+        if d == h { d } else { e },
+        if (d == h && e != i) || (h == f && e != g) {
+            h
+        } else {
+            e
+        },
+        if h == f { f } else { e },
+    ]
+}
+
+/// Scale a single pixel to a 2x2 block, the last 5 pixels are unused.
+fn xbr(source: &Source, x: i32, y: i32) -> [u32; 9] {
+    let e = source.get(x, y);
+    let mut block = [e; 9];
+
+    // Apply the same rule to every corner by mirroring the neighborhood
+    for (index, (sx, sy)) in [(-1, -1), (1, -1), (-1, 1), (1, 1)].iter().enumerate() {
+        let get = |u: i32, v: i32| source.get(x + u * sx, y + v * sy);
+
+        let (b, d, f, h) = (get(0, -1), get(-1, 0), get(1, 0), get(0, 1));
+        if e == f || e == h {
+            continue;
+        }
+
+        let (c, g, i) = (get(1, -1), get(-1, 1), get(1, 1));
+        let (f4, h5, i4, i5) = (get(2, 0), get(0, 2), get(2, 1), get(1, 2));
+
+        // Compare the weight of the edge along the corner with the edge across it
+        let along = distance(e, c)
+            + distance(e, g)
+            + distance(i, f4)
+            + distance(i, h5)
+            + 4 * distance(h, f);
+        let across = distance(h, d)
+            + distance(h, i5)
+            + distance(f, i4)
+            + distance(f, b)
+            + 4 * distance(e, i);
+
+        if along < across {
+            block[index] = if distance(e, f) <= distance(e, h) {
+                f
+            } else {
+                h
+            };
+        }
+    }
+
+    block
+}
+
+/// Perceptual difference between two colors in the YUV color space.
+fn distance(a: u32, b: u32) -> u32 {
+    let (ay, au, av) = yuv(a);
+    let (by, bu, bv) = yuv(b);
+
+    48 * ay.abs_diff(by) + 7 * au.abs_diff(bu) + 6 * av.abs_diff(bv)
+}
+
+/// Convert a color to the YUV color space.
+fn yuv(color: u32) -> (i32, i32, i32) {
+    let r = ((color >> 16) & 0xFF) as i32;
+    let g = ((color >> 8) & 0xFF) as i32;
+    let b = (color & 0xFF) as i32;
+
+    (
+        (299 * r + 587 * g + 114 * b) / 1000,
+        (-169 * r - 331 * g + 500 * b) / 1000 + 128,
+        (500 * r - 419 * g - 81 * b) / 1000 + 128,
+    )
+}
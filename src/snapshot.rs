@@ -0,0 +1,147 @@
+//! Capturing and restoring the exact pixels of a frame.
+
+use crate::PixelBuffer;
+use std::convert::TryInto;
+use thiserror::Error;
+
+/// Bytes before the pixels of a serialized frame, the width and height as little-endian `u32`s.
+const HEADER_SIZE: usize = 8;
+
+/// Error returned when reading a serialized frame or [`FrameDelta`](crate::FrameDelta) fails.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    /// There are fewer bytes than the header takes.
+    #[error("{0} bytes are too short for the header")]
+    MissingHeader(usize),
+    /// The amount of bytes doesn't match the width and height.
+    #[error("frame of {width}x{height} pixels needs {expected} bytes but has {actual} bytes")]
+    SizeMismatch {
+        /// Width of the frame in pixels.
+        width: usize,
+        /// Height of the frame in pixels.
+        height: usize,
+        /// Amount of bytes the frame should have.
+        expected: usize,
+        /// Amount of bytes the frame has.
+        actual: usize,
+    },
+    /// The bytes end before all runs of a delta are read.
+    #[error("delta of {0} bytes ends before its last run")]
+    Truncated(usize),
+    /// The frame of a delta has more pixels than
+    /// [`FrameDelta::MAX_PIXELS`](crate::FrameDelta::MAX_PIXELS).
+    #[error("frame of {width}x{height} pixels is too large")]
+    FrameTooLarge {
+        /// Width of the frame in pixels.
+        width: usize,
+        /// Height of the frame in pixels.
+        height: usize,
+    },
+    /// A run of a delta doesn't fit in the frame.
+    #[error("run of {len} pixels starting at pixel {start} is outside of the frame")]
+    RunOutOfBounds {
+        /// Index of the first pixel of the run.
+        start: usize,
+        /// Amount of pixels in the run.
+        len: usize,
+    },
+}
+
+impl PixelBuffer {
+    /// Copy the pixels exactly as they are, including the alpha channel.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(2, 1);
+    /// buffer.clear(0x11_22_33);
+    /// let snapshot = buffer.snapshot();
+    ///
+    /// buffer.clear(0);
+    /// buffer.restore(&snapshot);
+    /// assert_eq!(buffer.pixels(), &[0x11_22_33; 2]);
+    /// ```
+    pub fn snapshot(&self) -> Vec<u32> {
+        self.pixels.clone()
+    }
+
+    /// Overwrite all pixels with a snapshot taken with [`PixelBuffer::snapshot`].
+    ///
+    /// # Panics
+    ///
+    /// When the snapshot has a different amount of pixels than the buffer.
+    pub fn restore(&mut self, snapshot: &[u32]) {
+        assert_eq!(
+            snapshot.len(),
+            self.pixels.len(),
+            "snapshot of {} pixels doesn't fit in the buffer of {} pixels",
+            snapshot.len(),
+            self.pixels.len()
+        );
+
+        self.pixels.copy_from_slice(snapshot);
+    }
+
+    /// Serialize the size and the exact pixels, so the frame can be saved or sent and compared
+    /// byte for byte later.
+    ///
+    /// The width and height come first, followed by every pixel from the top left to the bottom
+    /// right, all as little-endian `u32`s.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut buffer = PixelBuffer::new(3, 2);
+    /// buffer.pixels_mut()[4] = 0xFF_11_22_33;
+    ///
+    /// let bytes = buffer.to_bytes();
+    /// assert_eq!(bytes.len(), 8 + 3 * 2 * 4);
+    ///
+    /// let loaded = PixelBuffer::from_bytes(&bytes)?;
+    /// assert_eq!((loaded.width(), loaded.height()), (3, 2));
+    /// assert_eq!(loaded.pixels(), buffer.pixels());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.pixels.len() * 4);
+        bytes.extend_from_slice(&(self.width as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u32).to_le_bytes());
+        for pixel in &self.pixels {
+            bytes.extend_from_slice(&pixel.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Create a buffer from a frame serialized with [`PixelBuffer::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(SnapshotError::MissingHeader(bytes.len()));
+        }
+
+        let read = |chunk: &[u8]| u32::from_le_bytes(chunk.try_into().unwrap());
+        let (width, height) = (read(&bytes[0..4]) as usize, read(&bytes[4..8]) as usize);
+
+        let expected = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .and_then(|pixels| pixels.checked_add(HEADER_SIZE));
+        if expected != Some(bytes.len()) {
+            return Err(SnapshotError::SizeMismatch {
+                width,
+                height,
+                expected: expected.unwrap_or(usize::MAX),
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(Self {
+            pixels: bytes[HEADER_SIZE..].chunks_exact(4).map(read).collect(),
+            width,
+            height,
+        })
+    }
+}
@@ -0,0 +1,132 @@
+//! Finding the sprites in a part of the world without checking all of them.
+
+use crate::{Sprite, SpriteImage};
+use specs::{hibitset::BitSet, prelude::*};
+use std::collections::HashMap;
+
+/// Grid of equally sized cells containing the entities whose bounds overlap them.
+#[derive(Debug)]
+pub(crate) struct SpatialHash {
+    /// Width and height of a cell in world pixels.
+    cell_size: i32,
+    /// Entities overlapping every cell that contains any.
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+    /// Bounds of every entity in world pixels as left, top, right and bottom, indexed by the
+    /// entity id.
+    bounds: HashMap<u32, (i32, i32, i32, i32)>,
+}
+
+impl SpatialHash {
+    /// Create an empty grid with the size of a cell in world pixels.
+    pub(crate) fn new(cell_size: i32) -> Self {
+        Self {
+            cell_size: cell_size.max(1),
+            cells: HashMap::new(),
+            bounds: HashMap::new(),
+        }
+    }
+
+    /// Width and height of a cell in world pixels.
+    pub(crate) fn cell_size(&self) -> i32 {
+        self.cell_size
+    }
+
+    /// Cells covered by the bounds as the left, top, right and bottom cell, inclusive.
+    fn cell_range(&self, (left, top, right, bottom): (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
+        (
+            left.div_euclid(self.cell_size),
+            top.div_euclid(self.cell_size),
+            (right - 1).max(left).div_euclid(self.cell_size),
+            (bottom - 1).max(top).div_euclid(self.cell_size),
+        )
+    }
+
+    /// Add the entity with its bounds, replacing the bounds it had before.
+    pub(crate) fn insert(&mut self, entity: Entity, bounds: (i32, i32, i32, i32)) {
+        self.remove(entity.id());
+
+        let (left, top, right, bottom) = self.cell_range(bounds);
+        for y in top..=bottom {
+            for x in left..=right {
+                self.cells.entry((x, y)).or_default().push(entity);
+            }
+        }
+
+        self.bounds.insert(entity.id(), bounds);
+    }
+
+    /// Remove the entity with the id, does nothing when it's not in the grid.
+    pub(crate) fn remove(&mut self, id: u32) {
+        let bounds = match self.bounds.remove(&id) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let (left, top, right, bottom) = self.cell_range(bounds);
+        for y in top..=bottom {
+            for x in left..=right {
+                if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                    cell.retain(|entity| entity.id() != id);
+                    if cell.is_empty() {
+                        self.cells.remove(&(x, y));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add or update the entity from the bounds of its sprite when it's drawn without zoom, the
+    /// images are the locked sprite store.
+    pub(crate) fn insert_sprite(
+        &mut self,
+        entity: Entity,
+        sprite: &Sprite,
+        sprite_images: &[SpriteImage],
+    ) {
+        let render_info = sprite.render_info(1.0);
+        let size = match sprite_images.get(render_info.index) {
+            Some(image) => render_info.size(image),
+            None => {
+                self.remove(entity.id());
+                return;
+            }
+        };
+
+        let left = sprite.pos.0 + render_info.offset.0;
+        let top = sprite.pos.1 + render_info.offset.1;
+        self.insert(
+            entity,
+            (left, top, left + size.0 as i32, top + size.1 as i32),
+        );
+    }
+
+    /// Ids of all entities overlapping the bounds.
+    pub(crate) fn query(&self, bounds: (i32, i32, i32, i32)) -> BitSet {
+        let mut found = BitSet::new();
+
+        let (left, top, right, bottom) = self.cell_range(bounds);
+        let area = (i64::from(right - left) + 1) * (i64::from(bottom - top) + 1);
+        if area > self.cells.len() as i64 {
+            // Looking at all filled cells is faster when zoomed out far
+            for (_, cell) in self
+                .cells
+                .iter()
+                .filter(|((x, y), _)| *x >= left && *x <= right && *y >= top && *y <= bottom)
+            {
+                for entity in cell {
+                    found.add(entity.id());
+                }
+            }
+        } else {
+            for y in top..=bottom {
+                for x in left..=right {
+                    for entity in self.cells.get(&(x, y)).into_iter().flatten() {
+                        found.add(entity.id());
+                    }
+                }
+            }
+        }
+
+        found
+    }
+}
@@ -0,0 +1,463 @@
+//! Pixel storage of loaded sprites.
+
+use crate::PixelBuffer;
+use blit::BlitBuffer;
+use std::{
+    cmp, fmt,
+    iter::FromIterator,
+    ops::{Deref, Range},
+    sync::Arc,
+};
+
+/// How the alpha channel of a sprite is used, see [`load_rgba`](crate::load_rgba).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// Blend the pixels with what's behind them.
+    #[default]
+    Blend,
+    /// Draw pixels with an alpha of at least the cutoff fully opaque and skip the others.
+    ///
+    /// This is faster than blending and keeps the hard edges of pixel art.
+    Threshold(u8),
+    /// Blend the pixels with what's behind them after multiplying the colors with the alpha
+    /// channel when loading.
+    ///
+    /// Drawing is cheaper than [`AlphaMode::Blend`] and the colors of soft edges are more
+    /// accurate when the sprite is rotated or scaled. Effects like
+    /// [`ColorAdjust`](crate::ColorAdjust) receive the multiplied colors.
+    Premultiplied,
+}
+
+/// How the pixels of an image are combined with the pixels behind them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Composite {
+    /// Visible pixels are drawn as they are.
+    Mask,
+    /// Pixels are blended using their alpha channel.
+    Alpha,
+    /// Pixels are blended using their alpha channel, the colors are already multiplied with it.
+    Premultiplied,
+    /// Pixels are added to the pixels behind them, scaled by their alpha channel.
+    Additive,
+}
+
+/// The pixels of a single loaded sprite rotation.
+///
+/// Pixels matching the mask color are transparent and won't be drawn.
+#[derive(Debug, Clone)]
+pub struct SpriteImage {
+    width: usize,
+    height: usize,
+    pixels: Pixels,
+    mask_color: u32,
+    /// How the pixels are combined with the pixels behind them.
+    composite: Composite,
+}
+
+impl SpriteImage {
+    /// Create an image from a buffer of pixels.
+    ///
+    /// The alpha channel of the pixels and the mask color is ignored.
+    pub fn from_buffer(pixels: &[u32], width: usize, mask_color: u32) -> Self {
+        let height = pixels.len().checked_div(width).unwrap_or(0);
+
+        Self {
+            width,
+            height,
+            pixels: pixels[..width * height]
+                .iter()
+                .map(|pixel| pixel | 0xFF_00_00_00)
+                .collect(),
+            mask_color: mask_color | 0xFF_00_00_00,
+            composite: Composite::Mask,
+        }
+    }
+
+    /// Create an image from a buffer of pixels with an alpha channel in the `0xAARRGGBB` format.
+    ///
+    /// Pixels with an alpha of `0` are transparent, the others are blended with what's behind
+    /// them when drawn.
+    ///
+    /// ```rust
+    /// use specs_blit::SpriteImage;
+    ///
+    /// let image = SpriteImage::from_rgba(&[0xFF_FF_00_00, 0x80_00_FF_00, 0x00_12_34_56], 3);
+    /// assert!(image.has_alpha());
+    /// assert!(!image.is_visible(image.pixels()[2]));
+    /// ```
+    pub fn from_rgba(pixels: &[u32], width: usize) -> Self {
+        let image = Self::from_rgba_with(pixels, width, Composite::Alpha);
+
+        // Images without translucent pixels can be drawn without blending
+        let translucent = image
+            .pixels
+            .iter()
+            .any(|&pixel| pixel >> 24 != 0xFF && pixel != 0);
+        if translucent {
+            image
+        } else {
+            Self {
+                composite: Composite::Mask,
+                ..image
+            }
+        }
+    }
+
+    /// Create an image from pixels with an alpha channel combined in a specific way.
+    fn from_rgba_with(pixels: &[u32], width: usize, composite: Composite) -> Self {
+        let height = pixels.len().checked_div(width).unwrap_or(0);
+
+        Self {
+            width,
+            height,
+            pixels: pixels[..width * height]
+                .iter()
+                // All fully transparent pixels become the mask color
+                .map(|&pixel| if pixel >> 24 == 0 { 0 } else { pixel })
+                .collect(),
+            mask_color: 0,
+            composite,
+        }
+    }
+
+    /// Multiply the colors of an image with an alpha channel with the alpha.
+    pub(crate) fn premultiply(self) -> Self {
+        if self.composite != Composite::Alpha {
+            return self;
+        }
+
+        let pixels: Vec<u32> = self
+            .pixels
+            .iter()
+            .map(|&pixel| {
+                let alpha = pixel >> 24;
+                let channel =
+                    |shift: u32| ((((pixel >> shift) & 0xFF) * alpha + 0x7F) / 0xFF) << shift;
+
+                (alpha << 24) | channel(16) | channel(8) | channel(0)
+            })
+            .collect();
+
+        Self::from_rgba_with(&pixels, self.width, Composite::Premultiplied)
+    }
+
+    /// Create an image with the same mask color and alpha handling from other pixels, used for
+    /// transforming images.
+    pub(crate) fn with_pixels(&self, pixels: &[u32], width: usize) -> Self {
+        if self.mask_color >> 24 == 0 {
+            // The image was created from pixels with an alpha channel
+            Self::from_rgba_with(pixels, width, self.composite)
+        } else {
+            Self {
+                composite: self.composite,
+                ..Self::from_buffer(pixels, width, self.mask_color)
+            }
+        }
+    }
+
+    /// Cut off the rows and columns at the borders without visible pixels, returns the position
+    /// of the part that's kept.
+    ///
+    /// Images without visible pixels are kept as they are.
+    pub(crate) fn trim(&self) -> ((usize, usize), Self) {
+        let (mut left, mut top, mut right, mut bottom) = (self.width, self.height, 0, 0);
+        for (index, &pixel) in self.pixels.iter().enumerate() {
+            if self.is_visible(pixel) {
+                let (x, y) = (index % self.width, index / self.width);
+                left = left.min(x);
+                top = top.min(y);
+                right = right.max(x + 1);
+                bottom = bottom.max(y + 1);
+            }
+        }
+        if left >= right {
+            return ((0, 0), self.clone());
+        }
+
+        let pixels: Vec<u32> = (top..bottom)
+            .flat_map(|y| self.pixels[y * self.width + left..y * self.width + right].iter())
+            .copied()
+            .collect();
+
+        ((left, top), self.with_pixels(&pixels, right - left))
+    }
+
+    /// Add the pixels of the image to the pixels behind them when drawing.
+    pub(crate) fn additive(self) -> Self {
+        Self {
+            composite: Composite::Additive,
+            ..self
+        }
+    }
+
+    /// Create an image from a blit buffer, keeping the transparent pixels intact.
+    pub fn from_blit_buffer(sprite: &BlitBuffer) -> Self {
+        let mask_color = sprite.mask_color().u32();
+
+        // Blitting on a buffer filled with the mask color is the only way to get both the colors
+        // and the transparent pixels out of the blit buffer
+        let mut pixels = vec![mask_color; (sprite.width() * sprite.height()) as usize];
+        sprite.blit(&mut pixels, sprite.width() as usize, (0, 0));
+
+        Self::from_buffer(&pixels, sprite.width() as usize, mask_color)
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// All pixels, including the ones matching the mask color.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Color of the transparent pixels.
+    pub fn mask_color(&self) -> u32 {
+        self.mask_color
+    }
+
+    /// Whether the pixels have an alpha channel that's blended with what's behind them.
+    pub fn has_alpha(&self) -> bool {
+        matches!(self.composite, Composite::Alpha | Composite::Premultiplied)
+    }
+
+    /// Whether the pixels are added to the pixels behind them.
+    pub fn is_additive(&self) -> bool {
+        self.composite == Composite::Additive
+    }
+
+    /// How the pixels are combined with the pixels behind them.
+    pub(crate) fn composite(&self) -> Composite {
+        self.composite
+    }
+
+    /// Whether the pixel is not transparent.
+    pub fn is_visible(&self, pixel: u32) -> bool {
+        pixel != self.mask_color
+    }
+
+    /// Call a function for every visible pixel that falls inside the buffer when the image is
+    /// placed at the position.
+    ///
+    /// The function receives the color of the image, the pixel of the buffer it's placed on and
+    /// the coordinates of that pixel in the buffer.
+    pub fn blit_with<F>(&self, buffer: &mut PixelBuffer, pos: (i32, i32), f: F)
+    where
+        F: FnMut(u32, &mut u32, i32, i32),
+    {
+        self.blit_region_with(buffer, pos, (0, 0, self.width, self.height), f);
+    }
+
+    /// Call a function for every visible pixel of a part of the image that falls inside the
+    /// buffer when the top left corner of the part is placed at the position.
+    ///
+    /// The part is the horizontal & vertical position, width and height in pixels of the image,
+    /// it's cut off at the edges of the image.
+    pub(crate) fn blit_region_with<F>(
+        &self,
+        buffer: &mut PixelBuffer,
+        pos: (i32, i32),
+        region: (usize, usize, usize, usize),
+        mut f: F,
+    ) where
+        F: FnMut(u32, &mut u32, i32, i32),
+    {
+        let (region_x, region_y) = (
+            cmp::min(region.0, self.width),
+            cmp::min(region.1, self.height),
+        );
+        let (width, height) = (
+            cmp::min(region.2, self.width - region_x) as i32,
+            cmp::min(region.3, self.height - region_y) as i32,
+        );
+        let (buffer_width, buffer_height) = (buffer.width as i32, buffer.height as i32);
+
+        // Only iterate over the part that's visible in the buffer
+        let start = (cmp::max(pos.0, 0), cmp::max(pos.1, 0));
+        let end = (
+            cmp::min(pos.0 + width, buffer_width),
+            cmp::min(pos.1 + height, buffer_height),
+        );
+        if start.0 >= end.0 || start.1 >= end.1 {
+            return;
+        }
+
+        for y in start.1..end.1 {
+            let src_y = (y - pos.1) as usize + region_y;
+            let src_start = src_y * self.width + (start.0 - pos.0) as usize + region_x;
+            let dst_start = (y * buffer_width + start.0) as usize;
+            let len = (end.0 - start.0) as usize;
+
+            let src = &self.pixels[src_start..src_start + len];
+            let dst = &mut buffer.pixels[dst_start..dst_start + len];
+            for ((&src, dst), x) in src.iter().zip(dst.iter_mut()).zip(start.0..) {
+                if src != self.mask_color {
+                    f(src, dst, x, y);
+                }
+            }
+        }
+    }
+
+    /// Call a function for every visible pixel that falls inside the buffer when the image is
+    /// stretched to the size and placed at the position.
+    ///
+    /// The image is scaled with nearest-neighbor sampling, the function receives the same
+    /// arguments as in [`SpriteImage::blit_with`].
+    ///
+    /// ```rust
+    /// use specs_blit::{PixelBuffer, SpriteImage};
+    ///
+    /// let image = SpriteImage::from_buffer(&[0xFF0000, 0xFF00], 2, 0xFF00FF);
+    /// let mut buffer = PixelBuffer::new(4, 1);
+    ///
+    /// // Stretch the image to twice its width
+    /// image.blit_scaled_with(&mut buffer, (0, 0), (4, 1), |src, dst, _, _| *dst = src);
+    /// let pixels: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFFFFFF).collect();
+    /// assert_eq!(pixels, [0xFF0000, 0xFF0000, 0xFF00, 0xFF00]);
+    /// ```
+    pub fn blit_scaled_with<F>(
+        &self,
+        buffer: &mut PixelBuffer,
+        pos: (i32, i32),
+        size: (usize, usize),
+        f: F,
+    ) where
+        F: FnMut(u32, &mut u32, i32, i32),
+    {
+        self.blit_sampled_with(buffer, pos, size, (false, false), f);
+    }
+
+    /// Call a function for every visible pixel that falls inside the buffer when the image is
+    /// stretched to the size, mirrored horizontally and vertically when the flags are set and
+    /// placed at the position.
+    pub(crate) fn blit_sampled_with<F>(
+        &self,
+        buffer: &mut PixelBuffer,
+        pos: (i32, i32),
+        size: (usize, usize),
+        flip: (bool, bool),
+        mut f: F,
+    ) where
+        F: FnMut(u32, &mut u32, i32, i32),
+    {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let (width, height) = (size.0 as i32, size.1 as i32);
+        let (buffer_width, buffer_height) = (buffer.width as i32, buffer.height as i32);
+
+        // Only iterate over the part that's visible in the buffer
+        let start = (cmp::max(pos.0, 0), cmp::max(pos.1, 0));
+        let end = (
+            cmp::min(pos.0 + width, buffer_width),
+            cmp::min(pos.1 + height, buffer_height),
+        );
+        if start.0 >= end.0 || start.1 >= end.1 {
+            return;
+        }
+
+        for y in start.1..end.1 {
+            // Find the nearest row of the image
+            let local_y = (y - pos.1) as usize;
+            let local_y = if flip.1 {
+                size.1 - 1 - local_y
+            } else {
+                local_y
+            };
+            let src_y = local_y * self.height / size.1;
+            let src_row = &self.pixels[src_y * self.width..(src_y + 1) * self.width];
+
+            let dst_start = (y * buffer_width) as usize;
+            for x in start.0..end.0 {
+                let local_x = (x - pos.0) as usize;
+                let local_x = if flip.0 {
+                    size.0 - 1 - local_x
+                } else {
+                    local_x
+                };
+                let src = src_row[local_x * self.width / size.0];
+                if src != self.mask_color {
+                    f(src, &mut buffer.pixels[dst_start + x as usize], x, y);
+                }
+            }
+        }
+    }
+}
+
+/// Pixels of an image, stored in a block of memory that can be shared with other images.
+#[derive(Clone)]
+pub(crate) struct Pixels {
+    /// Pixels of all images in the block.
+    block: Arc<[u32]>,
+    /// Part of the block belonging to this image.
+    range: Range<usize>,
+}
+
+impl Deref for Pixels {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        &self.block[self.range.clone()]
+    }
+}
+
+impl FromIterator<u32> for Pixels {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let block: Arc<[u32]> = iter.into_iter().collect();
+        let range = 0..block.len();
+
+        Self { block, range }
+    }
+}
+
+impl fmt::Debug for Pixels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Move the pixels of the images into shared blocks of at most `block_size` pixels in their
+/// order, returns the amount of blocks.
+///
+/// Images larger than a block get a block of their own.
+pub(crate) fn pack(images: &mut [SpriteImage], block_size: usize) -> usize {
+    let mut blocks = 0;
+    let mut start = 0;
+    while start < images.len() {
+        // Fill the block with as many images as fit
+        let mut end = start + 1;
+        let mut len = images[start].pixels.len();
+        while end < images.len() && len + images[end].pixels.len() <= block_size {
+            len += images[end].pixels.len();
+            end += 1;
+        }
+
+        let mut block = Vec::with_capacity(len);
+        for image in images[start..end].iter() {
+            block.extend_from_slice(&image.pixels);
+        }
+        let block: Arc<[u32]> = block.into();
+
+        let mut offset = 0;
+        for image in images[start..end].iter_mut() {
+            let len = image.pixels.len();
+            image.pixels = Pixels {
+                block: block.clone(),
+                range: offset..offset + len,
+            };
+            offset += len;
+        }
+
+        blocks += 1;
+        start = end;
+    }
+
+    blocks
+}
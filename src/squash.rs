@@ -0,0 +1,184 @@
+//! Squash and stretch animations of the sprite scale.
+
+use crate::{
+    time::{self, PausedLayers},
+    Sprite, Time,
+};
+use specs::prelude::*;
+use std::time::Duration;
+
+/// How an animation progresses over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Progress at a constant speed.
+    Linear,
+    /// Start fast and slow down towards the end.
+    #[default]
+    EaseOut,
+    /// Overshoot the end a few times with decreasing strength, like a spring.
+    Elastic,
+}
+
+impl Easing {
+    /// Map the linear progress between `0.0` and `1.0` to the eased progress.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::Elastic => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    1.0 - 2f64.powf(-10.0 * t)
+                        * ((t * 10.0 - 0.75) * std::f64::consts::TAU / 3.0).cos()
+                }
+            }
+        }
+    }
+}
+
+/// Deform the sprite and let it spring back to its normal shape.
+///
+/// The [`SquashStretchSystem`] animates the [`Sprite::set_scale`] of the sprite from the
+/// deformed scale back to `(1.0, 1.0)` with the [`Time`](crate::Time) resource. Combine it with
+/// [`Anchor::BottomCenter`](crate::Anchor::BottomCenter) to keep the feet of a character on the
+/// ground.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Easing, Sprite, SquashStretch, SquashStretchSystem, Time};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.register::<SquashStretch>();
+/// world.insert(Time::default());
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// let player = world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     // Flatten the sprite when it lands
+///     .with(SquashStretch::squash(0.5, Duration::from_millis(300)).with_easing(Easing::Elastic))
+///     .build();
+///
+/// SquashStretchSystem.run_now(&world);
+/// assert_eq!(world.read_storage::<Sprite>().get(player).unwrap().scale(), (1.5, 0.5));
+///
+/// world.write_resource::<Time>().advance(Duration::from_millis(300));
+/// SquashStretchSystem.run_now(&world);
+/// assert_eq!(world.read_storage::<Sprite>().get(player).unwrap().scale(), (1.0, 1.0));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SquashStretch {
+    /// The scale at the start of the animation.
+    from: (f64, f64),
+    /// How long it takes to get back to the normal scale.
+    duration: Duration,
+    /// How the scale progresses back to normal.
+    easing: Easing,
+    /// Time passed since the start of the animation.
+    elapsed: Duration,
+}
+
+impl SquashStretch {
+    /// Start from a horizontal and vertical scale and return to normal in the duration.
+    pub fn new(x: f64, y: f64, duration: Duration) -> Self {
+        Self {
+            from: (x, y),
+            duration,
+            easing: Easing::default(),
+            elapsed: Duration::default(),
+        }
+    }
+
+    /// Make the sprite wider and flatter by the amount, for example when landing.
+    pub fn squash(amount: f64, duration: Duration) -> Self {
+        Self::new(1.0 + amount, 1.0 - amount, duration)
+    }
+
+    /// Make the sprite narrower and taller by the amount, for example when jumping.
+    pub fn stretch(amount: f64, duration: Duration) -> Self {
+        Self::new(1.0 - amount, 1.0 + amount, duration)
+    }
+
+    /// Set how the scale progresses back to normal.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+
+        self
+    }
+
+    /// Play the animation again from the start.
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::default();
+    }
+
+    /// Whether the sprite is back at its normal scale.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// The scale of the sprite at this moment.
+    pub fn scale(&self) -> (f64, f64) {
+        if self.is_finished() {
+            return (1.0, 1.0);
+        }
+
+        let t = self
+            .easing
+            .apply(self.elapsed.as_secs_f64() / self.duration.as_secs_f64());
+
+        (
+            self.from.0 + (1.0 - self.from.0) * t,
+            self.from.1 + (1.0 - self.from.1) * t,
+        )
+    }
+}
+
+impl Component for SquashStretch {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Specs system advancing every [`SquashStretch`] and setting the scale of its [`Sprite`].
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{RenderSystem, SquashStretchSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(SquashStretchSystem, "squash_stretch", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct SquashStretchSystem;
+impl<'a> System<'a> for SquashStretchSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        Option<Read<'a, PausedLayers>>,
+        WriteStorage<'a, SquashStretch>,
+        WriteStorage<'a, Sprite>,
+    );
+
+    fn run(&mut self, (time, paused_layers, mut squash_stretches, mut sprites): Self::SystemData) {
+        for (squash_stretch, sprite) in (&mut squash_stretches, &mut sprites).join() {
+            // Don't overwrite the scale after the animation is done so other systems can set it
+            if squash_stretch.is_finished() {
+                continue;
+            }
+
+            squash_stretch.elapsed +=
+                time::layer_delta(&time, paused_layers.as_deref(), Some(sprite));
+
+            let (x, y) = squash_stretch.scale();
+            sprite.set_scale(x, y);
+        }
+    }
+}
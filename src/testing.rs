@@ -0,0 +1,223 @@
+//! Utilities for writing regression tests of rendered frames.
+//!
+//! ```rust
+//! use blit::BlitBuffer;
+//! use specs::prelude::*;
+//! use specs_blit::{load, testing, PixelBuffer, Sprite};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut world = World::new();
+//! world.register::<Sprite>();
+//!
+//! let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?;
+//! world.create_entity().with(Sprite::new(sprite_ref)).build();
+//!
+//! // Render a single frame of the world
+//! let frame = testing::render(&mut world, 4, 4);
+//!
+//! // Compare it to what we expect
+//! let mut expected = PixelBuffer::new(4, 4);
+//! for y in 0..2 {
+//!     for x in 0..2 {
+//!         expected.pixels_mut()[x + y * 4] = 0xFF_FF_FF;
+//!     }
+//! }
+//! testing::compare(&frame, &expected, 0)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    PixelBuffer, RenderSystem, ScrollingBackgroundSystem, StarfieldSystem, TilemapRenderSystem,
+};
+use specs::prelude::*;
+use std::{error::Error, fmt};
+
+/// Color of the pixels that differ in the diff image of a [`Mismatch`].
+pub const DIFF_COLOR: u32 = 0xFF_00_00;
+
+/// Render a single frame of the world into a new buffer of the given size.
+///
+/// The starfield and scrolling backgrounds are drawn first, then the tilemaps and the sprites on
+/// top of them, all components used by the render systems are registered. The [`PixelBuffer`]
+/// resource of the world is temporarily replaced, so it's left untouched.
+pub fn render(world: &mut World, width: usize, height: usize) -> PixelBuffer {
+    let original = world.remove::<PixelBuffer>();
+
+    world.insert(PixelBuffer::new(width, height));
+
+    let mut starfield_system = StarfieldSystem;
+    System::setup(&mut starfield_system, world);
+    starfield_system.run_now(world);
+
+    let mut background_system = ScrollingBackgroundSystem;
+    System::setup(&mut background_system, world);
+    background_system.run_now(world);
+
+    let mut tilemap_system = TilemapRenderSystem;
+    System::setup(&mut tilemap_system, world);
+    tilemap_system.run_now(world);
+
+    let mut system = RenderSystem::default();
+    System::setup(&mut system, world);
+    system.run_now(world);
+
+    let frame = world
+        .remove::<PixelBuffer>()
+        .expect("pixel buffer resource was removed while rendering");
+
+    if let Some(original) = original {
+        world.insert(original);
+    }
+
+    frame
+}
+
+/// Compare two frames where every color channel is allowed to differ by `tolerance`.
+///
+/// The alpha channel is ignored.
+pub fn compare(
+    actual: &PixelBuffer,
+    expected: &PixelBuffer,
+    tolerance: u8,
+) -> Result<(), Mismatch> {
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Err(Mismatch {
+            actual_size: (actual.width, actual.height),
+            expected_size: (expected.width, expected.height),
+            differing_pixels: actual.pixels.len().max(expected.pixels.len()),
+            max_difference: u8::MAX,
+            diff: None,
+        });
+    }
+
+    let mut diff = PixelBuffer::new(actual.width, actual.height);
+    let mut differing_pixels = 0;
+    let mut max_difference = 0;
+    for ((diff, &actual), &expected) in diff
+        .pixels
+        .iter_mut()
+        .zip(actual.pixels.iter())
+        .zip(expected.pixels.iter())
+    {
+        let difference = channel_difference(actual, expected);
+        max_difference = max_difference.max(difference);
+
+        *diff = if difference > tolerance {
+            differing_pixels += 1;
+
+            DIFF_COLOR
+        } else {
+            // Show the matching parts as a faded version of the image for reference
+            (actual >> 2) & 0x3F_3F_3F
+        };
+    }
+
+    if differing_pixels == 0 {
+        Ok(())
+    } else {
+        Err(Mismatch {
+            actual_size: (actual.width, actual.height),
+            expected_size: (expected.width, expected.height),
+            differing_pixels,
+            max_difference,
+            diff: Some(diff),
+        })
+    }
+}
+
+/// Compare a frame against a reference image on disk.
+///
+/// When the reference image doesn't exist yet it's created from the frame, this way new
+/// references can be generated by deleting the old ones and running the tests again.
+///
+/// On a mismatch an image with the differences is written next to the reference with a
+/// `.diff.png` suffix.
+#[cfg(feature = "image")]
+pub fn compare_with_image<P>(actual: &PixelBuffer, path: P, tolerance: u8) -> anyhow::Result<()>
+where
+    P: AsRef<std::path::Path>,
+{
+    let path = path.as_ref();
+    if !path.exists() {
+        actual.to_rgba_image().save(path)?;
+
+        return Ok(());
+    }
+
+    let expected = PixelBuffer::from_rgba_image(&image::open(path)?.to_rgba8());
+
+    compare(actual, &expected, tolerance).map_err(|mismatch| {
+        if let Some(diff) = mismatch.diff() {
+            let diff_path = path.with_extension("diff.png");
+            if let Err(err) = diff.to_rgba_image().save(&diff_path) {
+                return anyhow::anyhow!("{}, saving diff image failed: {}", mismatch, err);
+            }
+
+            anyhow::anyhow!("{}, see {}", mismatch, diff_path.display())
+        } else {
+            mismatch.into()
+        }
+    })
+}
+
+/// The frames that were compared are not equal.
+#[derive(Debug)]
+pub struct Mismatch {
+    actual_size: (usize, usize),
+    expected_size: (usize, usize),
+    differing_pixels: usize,
+    max_difference: u8,
+    diff: Option<PixelBuffer>,
+}
+
+impl Mismatch {
+    /// Amount of pixels that differ more than the tolerance.
+    pub fn differing_pixels(&self) -> usize {
+        self.differing_pixels
+    }
+
+    /// Biggest difference of a single color channel.
+    pub fn max_difference(&self) -> u8 {
+        self.max_difference
+    }
+
+    /// Image where the differing pixels are marked with [`DIFF_COLOR`].
+    ///
+    /// Is `None` when the sizes of the frames don't match.
+    pub fn diff(&self) -> Option<&PixelBuffer> {
+        self.diff.as_ref()
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.actual_size != self.expected_size {
+            write!(
+                f,
+                "frame size {}x{} doesn't match expected size {}x{}",
+                self.actual_size.0, self.actual_size.1, self.expected_size.0, self.expected_size.1
+            )
+        } else {
+            write!(
+                f,
+                "{} pixels differ, with a maximum channel difference of {}",
+                self.differing_pixels, self.max_difference
+            )
+        }
+    }
+}
+
+impl Error for Mismatch {}
+
+/// Biggest difference between the red, green & blue channels of two colors.
+fn channel_difference(a: u32, b: u32) -> u8 {
+    (0..3)
+        .map(|channel| {
+            let shift = channel * 8;
+
+            ((a >> shift) as u8).abs_diff((b >> shift) as u8)
+        })
+        .max()
+        .unwrap_or(0)
+}
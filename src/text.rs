@@ -0,0 +1,964 @@
+//! Drawing text with bitmap fonts.
+
+use crate::{
+    dither, effect, load, load_rgba, load_rotations,
+    profiler::{self, RenderPhase, RenderProfiler},
+    sprite_image::Composite,
+    AlphaMode, Lifetime, Pivot, PixelBuffer, SpriteError, SpriteRef, Time, SPRITES,
+};
+use blit::BlitBuffer;
+use specs::prelude::*;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use web_time::Instant;
+
+/// Monospaced font where every character is a sprite.
+///
+/// Cloning is cheap, all clones share the same glyphs.
+///
+/// Text drawn with markup can switch colors with codes like `{red}` or `{#FF0000}` and insert
+/// sprites between the characters with `{icon:coin}`, using the colors and icons registered with
+/// [`Font::with_color`] and [`Font::with_icon`]. `{/}` switches back to the color the text started
+/// with, `{{` draws a single `{` and codes that aren't known are skipped.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs_blit::Font;
+///
+/// const MASK_COLOR: u32 = 0xFF00FF;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// // A sheet with 2 glyphs of 2x2 pixels, for the characters '0' and '1'
+/// let sheet = BlitBuffer::from_buffer(
+///     &[
+///         0xFFFFFF, 0xFFFFFF, 0xFFFFFF, MASK_COLOR,
+///         0xFFFFFF, 0xFFFFFF, 0xFFFFFF, MASK_COLOR,
+///     ],
+///     4,
+///     MASK_COLOR,
+/// );
+///
+/// let font = Font::load(sheet, (2, 2), '0')?;
+/// assert_eq!(font.text_size("10"), (4, 2));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Font(Arc<FontGlyphs>);
+
+#[derive(Debug, Clone)]
+struct FontGlyphs {
+    /// Size of every glyph in pixels.
+    glyph_size: (usize, usize),
+    glyphs: HashMap<char, SpriteRef>,
+    /// Glyph drawn for the characters that are not in the font.
+    fallback: Option<SpriteRef>,
+    /// Colors that can be switched to by name in markup.
+    colors: HashMap<String, u32>,
+    /// Sprites that can be inserted by name in markup.
+    icons: HashMap<String, SpriteRef>,
+}
+
+/// How a text is drawn.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TextStyle {
+    /// Color of all glyph pixels, when `None` the colors of the glyphs are used.
+    pub(crate) color: Option<u32>,
+    /// Rotation in degrees around the top left corner.
+    pub(crate) rot: i16,
+    /// Size in pixels every pixel of the glyphs is drawn with.
+    pub(crate) scale: usize,
+    /// Dithered opacity where `255` is fully visible.
+    pub(crate) opacity: u8,
+    /// Whether the color and icon codes in the text are applied.
+    pub(crate) markup: bool,
+}
+
+impl TextStyle {
+    /// Draw the text as is with the color.
+    pub(crate) fn new(color: Option<u32>) -> Self {
+        Self {
+            color,
+            rot: 0,
+            scale: 1,
+            opacity: u8::MAX,
+            markup: false,
+        }
+    }
+}
+
+/// Part of a line of text, laid out from left to right.
+enum Span<'f> {
+    /// Character drawn with the color, or with the colors of the glyph when `None`.
+    ///
+    /// Characters that are not in the font don't have a glyph but still take up space.
+    Glyph(Option<&'f SpriteRef>, Option<u32>),
+    /// Sprite drawn with its own colors.
+    Icon(&'f SpriteRef),
+}
+
+/// Coverage of a single character rasterized from a vector font, used to create a [`Font`] with
+/// [`Font::from_raster`].
+///
+/// Crates like `fontdue` and `ab_glyph` rasterize the characters of a TTF or OTF font at a pixel
+/// size into a coverage bitmap and the metrics to place it.
+#[derive(Debug, Clone, Copy)]
+pub struct RasterGlyph<'a> {
+    /// Character the glyph is drawn for.
+    pub character: char,
+    /// Width in pixels of the coverage bitmap.
+    pub width: usize,
+    /// Coverage of every pixel from the top left to the bottom right, from `0` for empty to `255`
+    /// for fully covered.
+    pub coverage: &'a [u8],
+    /// Position of the top left corner of the bitmap in the cell of the glyph.
+    pub offset: (i32, i32),
+}
+
+impl Font {
+    /// Load a font from a sheet with glyphs placed next to each other in a grid.
+    ///
+    /// The glyphs are read from left to right and top to bottom, every glyph is the character
+    /// with the code point following the previous one starting at `first`.
+    pub fn load(
+        sheet: BlitBuffer,
+        glyph_size: (usize, usize),
+        first: char,
+    ) -> Result<Self, SpriteError> {
+        Self::load_with(sheet, glyph_size, first..=char::MAX, load)
+    }
+
+    /// Load a font from a sheet like [`Font::load`], where every glyph is the next character of
+    /// the mapping instead of the next code point.
+    ///
+    /// This allows sheets with any characters in any order, e.g. accented or non-Latin ones.
+    /// Glyphs after the last character of the mapping are skipped.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{Font, PixelBuffer};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// // A sheet with 3 glyphs of 1x1 pixels
+    /// let sheet = BlitBuffer::from_buffer(&[0xFF0000, 0xFF00, 0xFF], 3, 0xFF00FF);
+    /// let font = Font::load_mapped(sheet, (1, 1), "?éж")?.with_fallback('?');
+    ///
+    /// assert!(font.has_glyph('ж'));
+    /// assert!(!font.has_glyph('a'));
+    ///
+    /// // The missing character is drawn with the fallback glyph
+    /// let mut buffer = PixelBuffer::new(2, 1);
+    /// font.draw(&mut buffer, "жa", (0, 0), None);
+    /// let colors: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+    /// assert_eq!(colors, [0xFF, 0xFF0000]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_mapped(
+        sheet: BlitBuffer,
+        glyph_size: (usize, usize),
+        mapping: &str,
+    ) -> Result<Self, SpriteError> {
+        Self::load_with(sheet, glyph_size, mapping.chars(), load)
+    }
+
+    /// Load a font like [`Font::load`] with rotated versions of every glyph, so text drawn with
+    /// it can be turned.
+    ///
+    /// The glyphs are rotated the same way as sprites loaded with
+    /// [`load_rotations`](crate::load_rotations).
+    pub fn load_rotations(
+        sheet: BlitBuffer,
+        glyph_size: (usize, usize),
+        first: char,
+        rotations: u16,
+    ) -> Result<Self, SpriteError> {
+        Self::load_with(sheet, glyph_size, first..=char::MAX, |glyph| {
+            load_rotations(glyph, rotations)
+        })
+    }
+
+    /// Create a font from characters rasterized from a vector font, so no bitmap font has to be
+    /// drawn for every size.
+    ///
+    /// Every glyph is placed in a cell of the glyph size and drawn in the color. The coverage is
+    /// used as the alpha channel with the alpha mode, [`AlphaMode::Threshold`] turns it into
+    /// crisp pixel text and [`AlphaMode::Blend`] keeps the edges smooth.
+    ///
+    /// ```rust
+    /// use specs_blit::{AlphaMode, Font, PixelBuffer, RasterGlyph};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// // A vertical bar and a half covered pixel, like a rasterizer would output
+    /// let bar = RasterGlyph {
+    ///     character: '|',
+    ///     width: 1,
+    ///     coverage: &[255, 255],
+    ///     offset: (1, 0),
+    /// };
+    /// let dot = RasterGlyph {
+    ///     character: '.',
+    ///     width: 1,
+    ///     coverage: &[100],
+    ///     offset: (0, 1),
+    /// };
+    ///
+    /// // Only draw the pixels that are covered for at least half
+    /// let alpha_mode = AlphaMode::Threshold(128);
+    /// let font = Font::from_raster(vec![bar, dot], (2, 2), 0xFF_FF_FF, alpha_mode)?;
+    ///
+    /// let mut buffer = PixelBuffer::new(4, 2);
+    /// font.draw(&mut buffer, "|.", (0, 0), None);
+    /// let colors: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+    /// assert_eq!(colors, [0, 0xFF_FF_FF, 0, 0, 0, 0xFF_FF_FF, 0, 0]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_raster<'a, I>(
+        glyphs: I,
+        glyph_size: (usize, usize),
+        color: u32,
+        alpha_mode: AlphaMode,
+    ) -> Result<Self, SpriteError>
+    where
+        I: IntoIterator<Item = RasterGlyph<'a>>,
+    {
+        let (glyph_width, glyph_height) = glyph_size;
+        if glyph_width == 0 || glyph_height == 0 {
+            return Err(SpriteError::Empty {
+                width: glyph_width,
+                height: glyph_height,
+            });
+        }
+
+        let mut loaded = HashMap::new();
+        for glyph in glyphs {
+            // Place the coverage in the cell, the parts outside of it are cut off
+            let mut pixels = vec![0; glyph_width * glyph_height];
+            if glyph.width > 0 {
+                for (index, &coverage) in glyph.coverage.iter().enumerate() {
+                    let x = (index % glyph.width) as i32 + glyph.offset.0;
+                    let y = (index / glyph.width) as i32 + glyph.offset.1;
+                    if x >= 0 && y >= 0 && (x as usize) < glyph_width && (y as usize) < glyph_height
+                    {
+                        pixels[y as usize * glyph_width + x as usize] =
+                            u32::from(coverage) << 24 | color & 0xFF_FF_FF;
+                    }
+                }
+            }
+
+            loaded.insert(
+                glyph.character,
+                load_rgba(&pixels, glyph_width, 1, alpha_mode)?,
+            );
+        }
+
+        Ok(Self(Arc::new(FontGlyphs {
+            glyph_size,
+            glyphs: loaded,
+            fallback: None,
+            colors: HashMap::new(),
+            icons: HashMap::new(),
+        })))
+    }
+
+    /// Create a font by rasterizing the characters of a TTF or OTF font at the pixel size, so no
+    /// bitmap font has to be drawn for every size.
+    ///
+    /// The glyphs are placed on a shared baseline in cells as wide as the widest character and as
+    /// high as a line, and are created like with [`Font::from_raster`]. Characters that are not in
+    /// the font are skipped.
+    ///
+    /// ```rust,no_run
+    /// use specs_blit::{AlphaMode, Font};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let data = std::fs::read("font.ttf")?;
+    ///
+    /// // Crisp pixel text with all printable ASCII characters
+    /// let font = Font::from_ttf(&data, 12.0, ' '..='~', 0xFF_FF_FF, AlphaMode::Threshold(128))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "fontdue")]
+    pub fn from_ttf<C>(
+        data: &[u8],
+        px_size: f32,
+        characters: C,
+        color: u32,
+        alpha_mode: AlphaMode,
+    ) -> Result<Self, SpriteError>
+    where
+        C: IntoIterator<Item = char>,
+    {
+        let settings = fontdue::FontSettings {
+            scale: px_size,
+            ..Default::default()
+        };
+        let font = fontdue::Font::from_bytes(data, settings).map_err(SpriteError::InvalidFont)?;
+
+        let rasterized: Vec<_> = characters
+            .into_iter()
+            .filter(|&character| font.lookup_glyph_index(character) != 0)
+            .map(|character| (character, font.rasterize(character, px_size)))
+            .collect();
+
+        // The baseline is placed the ascent below the top of the cell
+        let (ascent, descent) = font
+            .horizontal_line_metrics(px_size)
+            .map_or((px_size, 0.0), |line| (line.ascent, line.descent));
+        let baseline = ascent.ceil() as i32;
+        let glyph_width = rasterized
+            .iter()
+            .map(|(_, (metrics, _))| metrics.advance_width.ceil() as usize)
+            .max()
+            .unwrap_or(0);
+        let glyph_height = (ascent.ceil() - descent.floor()) as usize;
+
+        let glyphs = rasterized
+            .iter()
+            .map(|(character, (metrics, coverage))| RasterGlyph {
+                character: *character,
+                width: metrics.width,
+                coverage,
+                offset: (
+                    metrics.xmin,
+                    baseline - metrics.ymin - metrics.height as i32,
+                ),
+            });
+
+        Self::from_raster(glyphs, (glyph_width, glyph_height), color, alpha_mode)
+    }
+
+    /// Load a font where the glyphs are the characters in order, loaded with the function.
+    fn load_with<C, F>(
+        sheet: BlitBuffer,
+        glyph_size: (usize, usize),
+        characters: C,
+        mut load_glyph: F,
+    ) -> Result<Self, SpriteError>
+    where
+        C: IntoIterator<Item = char>,
+        F: FnMut(BlitBuffer) -> Result<SpriteRef, SpriteError>,
+    {
+        let (glyph_width, glyph_height) = glyph_size;
+        if glyph_width == 0 || glyph_height == 0 {
+            return Err(SpriteError::Empty {
+                width: glyph_width,
+                height: glyph_height,
+            });
+        }
+
+        let mask_color = sheet.mask_color().u32();
+        let sheet_pixels = {
+            // Keep the transparent pixels so the glyphs can be cut out of the sheet
+            let mut pixels = vec![mask_color; (sheet.width() * sheet.height()) as usize];
+            sheet.blit(&mut pixels, sheet.width() as usize, (0, 0));
+
+            pixels
+        };
+        let sheet_width = sheet.width() as usize;
+        let columns = sheet_width / glyph_width;
+        let rows = sheet.height() as usize / glyph_height;
+
+        let mut glyphs = HashMap::new();
+        for (index, character) in characters.into_iter().take(columns * rows).enumerate() {
+            let (column, row) = (index % columns, index / columns);
+
+            let pixels = (0..glyph_height)
+                .flat_map(|y| {
+                    let start = (row * glyph_height + y) * sheet_width + column * glyph_width;
+
+                    sheet_pixels[start..start + glyph_width].iter().copied()
+                })
+                .collect::<Vec<_>>();
+
+            let glyph = BlitBuffer::from_buffer(&pixels, glyph_width as i32, mask_color);
+            glyphs.insert(character, load_glyph(glyph)?);
+        }
+
+        Ok(Self(Arc::new(FontGlyphs {
+            glyph_size,
+            glyphs,
+            fallback: None,
+            colors: HashMap::new(),
+            icons: HashMap::new(),
+        })))
+    }
+
+    /// Draw the glyph of the character for all characters that are not in the font, instead of
+    /// skipping them.
+    ///
+    /// Nothing changes when the character itself is not in the font.
+    pub fn with_fallback(mut self, character: char) -> Self {
+        let fallback = self.0.glyphs.get(&character).cloned();
+        if fallback.is_some() {
+            Arc::make_mut(&mut self.0).fallback = fallback;
+        }
+
+        self
+    }
+
+    /// Whether the font has a glyph for the character, not counting the fallback glyph.
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.0.glyphs.contains_key(&character)
+    }
+
+    /// Register a color that markup can switch to with `{name}`.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{Font, PixelBuffer};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let sheet = BlitBuffer::from_buffer(&[0xFFFFFF], 1, 0xFF00FF);
+    /// let font = Font::load(sheet, (1, 1), 'a')?.with_color("red", 0xFF_00_00);
+    ///
+    /// let mut buffer = PixelBuffer::new(3, 1);
+    /// font.draw_markup(&mut buffer, "a{red}a{/}a", (0, 0), Some(0x00_00_FF));
+    ///
+    /// let colors: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+    /// assert_eq!(colors, [0x00_00_FF, 0xFF_00_00, 0x00_00_FF]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_color<S>(mut self, name: S, color: u32) -> Self
+    where
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.0).colors.insert(name.into(), color);
+
+        self
+    }
+
+    /// Register a sprite that markup can insert between the characters with `{icon:name}`.
+    ///
+    /// The icon takes up its own width and is vertically centered on the line.
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{load, Font, PixelBuffer};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let coin = load(BlitBuffer::from_buffer(&[0xFF_FF_00; 2], 2, 0xFF_00_FF))?;
+    /// let sheet = BlitBuffer::from_buffer(&[0xFFFFFF], 1, 0xFF00FF);
+    /// let font = Font::load(sheet, (1, 1), '1')?.with_icon("coin", coin);
+    ///
+    /// assert_eq!(font.markup_size("1{icon:coin}"), (3, 1));
+    ///
+    /// let mut buffer = PixelBuffer::new(3, 1);
+    /// font.draw_markup(&mut buffer, "1{icon:coin}", (0, 0), None);
+    /// assert_eq!(buffer.pixels()[2] & 0xFF_FF_FF, 0xFF_FF_00);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_icon<S>(mut self, name: S, icon: SpriteRef) -> Self
+    where
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.0).icons.insert(name.into(), icon);
+
+        self
+    }
+
+    /// Size of every glyph in pixels.
+    pub fn glyph_size(&self) -> (usize, usize) {
+        self.0.glyph_size
+    }
+
+    /// Size in pixels of the text when it's drawn, every newline starts a new line.
+    pub fn text_size(&self, text: &str) -> (usize, usize) {
+        let (glyph_width, glyph_height) = self.0.glyph_size;
+
+        let lines = text.lines().count();
+        let columns = text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        (columns * glyph_width, lines * glyph_height)
+    }
+
+    /// Size in pixels of the text when it's drawn with markup, the codes don't take up space but
+    /// the icons do.
+    pub fn markup_size(&self, text: &str) -> (usize, usize) {
+        let width = text
+            .lines()
+            .map(|line| {
+                self.spans(line, None, true)
+                    .iter()
+                    .map(|span| self.span_size(span).0)
+                    .sum()
+            })
+            .max()
+            .unwrap_or(0);
+
+        (width, text.lines().count() * self.0.glyph_size.1)
+    }
+
+    /// Draw the text with the top left corner at the position.
+    ///
+    /// Every visible pixel of the glyphs is drawn with the color, or with the colors of the
+    /// glyph when the color is `None`. Characters that are not in the font are drawn with the
+    /// fallback glyph, or skipped when there's none.
+    pub fn draw(&self, buffer: &mut PixelBuffer, text: &str, pos: (i32, i32), color: Option<u32>) {
+        self.draw_with(buffer, text, pos, TextStyle::new(color));
+    }
+
+    /// Draw the text like [`Font::draw`] with the color and icon codes applied.
+    pub fn draw_markup(
+        &self,
+        buffer: &mut PixelBuffer,
+        text: &str,
+        pos: (i32, i32),
+        color: Option<u32>,
+    ) {
+        let style = TextStyle {
+            markup: true,
+            ..TextStyle::new(color)
+        };
+        self.draw_with(buffer, text, pos, style);
+    }
+
+    /// Draw the text like [`Font::draw`], turned by the degrees around the top left corner and
+    /// every pixel enlarged to a square of the scale.
+    ///
+    /// Every glyph is drawn with the rotated version closest to the degrees, so the font should
+    /// be loaded with [`Font::load_rotations`].
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs_blit::{Font, PixelBuffer};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// // A single glyph of 2x2 pixels for the character '0'
+    /// let sheet = BlitBuffer::from_buffer(&[0xFFFFFF; 4], 2, 0xFF00FF);
+    /// let font = Font::load_rotations(sheet, (2, 2), '0', 4)?;
+    ///
+    /// // Text going down instead of to the right, twice as large
+    /// let mut buffer = PixelBuffer::new(8, 8);
+    /// font.draw_transformed(&mut buffer, "00", (8, 0), Some(0xFF_00_00), 90, 2);
+    ///
+    /// let red = |x: usize, y: usize| buffer.pixels()[y * 8 + x] & 0xFF_FF_FF == 0xFF_00_00;
+    /// assert!(red(4, 0) && red(7, 0) && red(4, 7) && red(7, 7));
+    /// assert!(!red(3, 0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_transformed(
+        &self,
+        buffer: &mut PixelBuffer,
+        text: &str,
+        pos: (i32, i32),
+        color: Option<u32>,
+        rot: i16,
+        scale: usize,
+    ) {
+        let style = TextStyle {
+            rot,
+            scale,
+            ..TextStyle::new(color)
+        };
+        self.draw_with(buffer, text, pos, style);
+    }
+
+    /// Draw the text in the style.
+    pub(crate) fn draw_with(
+        &self,
+        buffer: &mut PixelBuffer,
+        text: &str,
+        pos: (i32, i32),
+        style: TextStyle,
+    ) {
+        let glyph_height = self.0.glyph_size.1;
+        let color = style.color.map(|color| color | 0xFF_00_00_00);
+        let scale = style.scale.max(1);
+        let (sin, cos) = f64::from(style.rot).to_radians().sin_cos();
+
+        let sprites = SPRITES.read().unwrap();
+        for (row, line) in text.lines().enumerate() {
+            let mut x = 0;
+            for span in self.spans(line, color, style.markup) {
+                let (width, height) = self.span_size(&span);
+                let (sprite, color) = match span {
+                    Span::Glyph(glyph, color) => (glyph, color),
+                    Span::Icon(icon) => (Some(icon), None),
+                };
+                let left = x;
+                x += width;
+
+                let sprite = match sprite {
+                    Some(sprite) => sprite,
+                    None => continue,
+                };
+
+                // Turn the center of the glyph around the top left corner of the text, icons are
+                // centered vertically on the line
+                let half = ((width * scale) as f64 / 2.0, (height * scale) as f64 / 2.0);
+                let (center_x, center_y) = (
+                    (left * scale) as f64 + half.0,
+                    (row * glyph_height * scale) as f64 + (glyph_height * scale) as f64 / 2.0,
+                );
+                let center = (
+                    center_x * cos - center_y * sin,
+                    center_x * sin + center_y * cos,
+                );
+
+                let (index, x_offset, y_offset) = sprite.render_info(style.rot, Pivot::Center);
+                let sprite_pos = (
+                    pos.0 + (center.0 - half.0).round() as i32 + x_offset * scale as i32,
+                    pos.1 + (center.1 - half.1).round() as i32 + y_offset * scale as i32,
+                );
+
+                // Skip glyphs that don't exist anymore because the sprites are cleared
+                if let Some(image) = sprites.get(index) {
+                    let blend = image.composite() == Composite::Alpha;
+                    let draw = |src: u32, dst: &mut u32, x, y| {
+                        if !dither::is_visible(x, y, style.opacity) {
+                            return;
+                        }
+
+                        // Smooth edges of glyphs rasterized from vector fonts are blended
+                        let alpha = src >> 24;
+                        let src = color.unwrap_or(src | 0xFF_00_00_00);
+                        *dst = if blend && alpha < 0xFF {
+                            effect::blend_alpha(src, *dst, alpha)
+                        } else {
+                            src
+                        };
+                    };
+
+                    if scale == 1 {
+                        image.blit_with(buffer, sprite_pos, draw);
+                    } else {
+                        let size = (image.width() * scale, image.height() * scale);
+                        image.blit_scaled_with(buffer, sprite_pos, size, draw);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Split a line into the characters and icons, applying the codes when it has markup.
+    fn spans(&self, line: &str, color: Option<u32>, markup: bool) -> Vec<Span<'_>> {
+        let mut spans = Vec::new();
+        let mut current = color;
+
+        let mut characters = line.chars().peekable();
+        while let Some(character) = characters.next() {
+            if !markup || character != '{' {
+                spans.push(Span::Glyph(self.glyph(character), current));
+                continue;
+            }
+
+            // An escaped brace is drawn as is
+            if characters.peek() == Some(&'{') {
+                characters.next();
+                spans.push(Span::Glyph(self.glyph('{'), current));
+                continue;
+            }
+
+            let code: String = characters.by_ref().take_while(|&c| c != '}').collect();
+            if code == "/" {
+                current = color;
+            } else if let Some(name) = code.strip_prefix("icon:") {
+                if let Some(icon) = self.0.icons.get(name) {
+                    spans.push(Span::Icon(icon));
+                }
+            } else if let Some(hex) = code.strip_prefix('#') {
+                if let Ok(hex) = u32::from_str_radix(hex, 16) {
+                    current = Some(hex | 0xFF_00_00_00);
+                }
+            } else if let Some(named) = self.0.colors.get(&code) {
+                current = Some(named | 0xFF_00_00_00);
+            }
+        }
+
+        spans
+    }
+
+    /// Get the glyph of the character, or the fallback glyph when it's not in the font.
+    fn glyph(&self, character: char) -> Option<&SpriteRef> {
+        self.0.glyphs.get(&character).or(self.0.fallback.as_ref())
+    }
+
+    /// Width and height in pixels a part of a line takes up.
+    fn span_size(&self, span: &Span<'_>) -> (usize, usize) {
+        match span {
+            Span::Glyph(_, _) => self.0.glyph_size,
+            Span::Icon(icon) => (icon.size.0 as usize, icon.size.1 as usize),
+        }
+    }
+}
+
+/// Specs component for text drawn by the [`TextRenderSystem`].
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{Font, Text};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// # let sheet = BlitBuffer::from_buffer(&[0xFFFFFF; 4], 2, 0xFF00FF);
+/// let font = Font::load(sheet, (1, 1), '0')?;
+///
+/// let mut world = World::new();
+/// world.register::<Text>();
+///
+/// let mut text = Text::new(font, "0101");
+/// text.set_pos(10, 10);
+/// text.set_color(Some(0xFF_00_00));
+///
+/// world.create_entity().with(text).build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Text {
+    font: Font,
+    text: String,
+    /// Top left corner of the first character.
+    pos: (i32, i32),
+    /// Color of all glyph pixels, when `None` the colors of the glyphs are used.
+    color: Option<u32>,
+    /// Rotation in degrees around the top left corner.
+    rot: i16,
+    /// Size in pixels every pixel of the glyphs is drawn with.
+    scale: usize,
+    /// Whether the color and icon codes in the text are applied.
+    markup: bool,
+}
+
+impl Component for Text {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Text {
+    /// Create a text drawn with the font.
+    pub fn new<S>(font: Font, text: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            font,
+            text: text.into(),
+            pos: (0, 0),
+            color: None,
+            rot: 0,
+            scale: 1,
+            markup: false,
+        }
+    }
+
+    /// Replace the text.
+    pub fn set_text<S>(&mut self, text: S)
+    where
+        S: Into<String>,
+    {
+        self.text = text.into();
+    }
+
+    /// Get the text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Set the pixel position of the top left corner of the text.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the pixel position of the top left corner of the text.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Draw all glyphs with a single color, or use the colors of the font when `None`.
+    pub fn set_color(&mut self, color: Option<u32>) {
+        self.color = color;
+    }
+
+    /// Get the color the glyphs are drawn with.
+    pub fn color(&self) -> Option<u32> {
+        self.color
+    }
+
+    /// Turn the text around its top left corner.
+    ///
+    /// The glyphs are drawn with the rotated version closest to the degrees, so the font should
+    /// be loaded with [`Font::load_rotations`].
+    pub fn set_rot(&mut self, rotation: i16) {
+        self.rot = rotation.rem_euclid(360);
+    }
+
+    /// Get the rotation in degrees.
+    pub fn rot(&self) -> i16 {
+        self.rot
+    }
+
+    /// Draw every pixel of the glyphs as a square of the size, a scale of `0` is treated as `1`.
+    pub fn set_scale(&mut self, scale: usize) {
+        self.scale = scale.max(1);
+    }
+
+    /// Get the size every pixel of the glyphs is drawn with.
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    /// Apply the color and icon codes in the text, see [`Font`] for the markup.
+    pub fn set_markup(&mut self, markup: bool) {
+        self.markup = markup;
+    }
+
+    /// Whether the color and icon codes in the text are applied.
+    pub fn has_markup(&self) -> bool {
+        self.markup
+    }
+
+    /// Get the font.
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+}
+
+/// Specs system for rendering text to a buffer.
+///
+/// Texts are drawn over everything that's already in the buffer, so this system should be added
+/// after the [`RenderSystem`](crate::RenderSystem).
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{RenderSystem, TextRenderSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     .with_thread_local(TextRenderSystem)
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct TextRenderSystem;
+impl<'a> System<'a> for TextRenderSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        ReadStorage<'a, Text>,
+        ReadStorage<'a, Lifetime>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, texts, lifetimes, profiler): Self::SystemData) {
+        let start = Instant::now();
+        for (text, lifetime) in (&texts, lifetimes.maybe()).join() {
+            let style = TextStyle {
+                color: text.color,
+                rot: text.rot,
+                scale: text.scale,
+                opacity: lifetime.map_or(u8::MAX, Lifetime::opacity),
+                markup: text.markup,
+            };
+            text.font
+                .draw_with(&mut buffer, &text.text, text.pos, style);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "TextRenderSystem", start);
+    }
+}
+
+/// Text rising up and fading out before it disappears, e.g. for damage numbers.
+///
+/// Spawn it with [`FloatingText::spawn`], the [`FloatingTextSystem`] moves the [`Text`] and the
+/// [`LifetimeSystem`](crate::LifetimeSystem) fades it out and deletes it.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{FloatingText, Font, Text};
+/// use std::time::Duration;
+///
+/// struct DamageSystem {
+///     font: Font,
+/// }
+///
+/// impl<'a> System<'a> for DamageSystem {
+///     type SystemData = (Entities<'a>, Read<'a, LazyUpdate>);
+///
+///     fn run(&mut self, (entities, lazy): Self::SystemData) {
+///         let mut text = Text::new(self.font.clone(), "12");
+///         text.set_pos(100, 50);
+///
+///         // Rise 20 pixels per second for a second
+///         FloatingText::spawn(&entities, &lazy, text, 20.0, Duration::from_secs(1));
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FloatingText {
+    /// Pixels per second the text moves up.
+    rise_speed: f32,
+    /// Pixels the text moved up so far, including the fractional part that's not applied yet.
+    risen: f32,
+}
+
+impl Component for FloatingText {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl FloatingText {
+    /// Move the text up with the speed in pixels per second.
+    pub fn new(rise_speed: f32) -> Self {
+        Self {
+            rise_speed,
+            risen: 0.0,
+        }
+    }
+
+    /// Create an entity with the text that rises and fades out during the second half of its
+    /// lifetime, after which it's deleted.
+    ///
+    /// The entity is created the next time `world.maintain()` is called.
+    pub fn spawn(
+        entities: &Entities,
+        lazy: &LazyUpdate,
+        text: Text,
+        rise_speed: f32,
+        duration: Duration,
+    ) -> Entity {
+        lazy.create_entity(entities)
+            .with(text)
+            .with(Self::new(rise_speed))
+            .with(Lifetime::new(duration).with_fade_out(duration / 2))
+            .build()
+    }
+}
+
+/// Specs system moving every [`FloatingText`] up with the [`Time`](crate::Time) resource.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{FloatingTextSystem, LifetimeSystem, RenderSystem, TextRenderSystem};
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(FloatingTextSystem, "floating_text", &[])
+///     .with(LifetimeSystem, "lifetime", &[])
+///     .with_thread_local(RenderSystem::default())
+///     .with_thread_local(TextRenderSystem)
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct FloatingTextSystem;
+impl<'a> System<'a> for FloatingTextSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        WriteStorage<'a, FloatingText>,
+        WriteStorage<'a, Text>,
+    );
+
+    fn run(&mut self, (time, mut floating_texts, mut texts): Self::SystemData) {
+        for (floating_text, text) in (&mut floating_texts, &mut texts).join() {
+            let previous = floating_text.risen as i32;
+            floating_text.risen += floating_text.rise_speed * time.delta().as_secs_f32();
+
+            // Only move whole pixels so the text doesn't drift
+            text.pos.1 -= floating_text.risen as i32 - previous;
+        }
+    }
+}
@@ -0,0 +1,599 @@
+//! Grids of sprites drawn as the ground of the world.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    Animation, Camera, CollisionLayer, OverdrawHeatmap, PixelBuffer, SpriteImage, SpriteRef, Time,
+    SPRITES,
+};
+use specs::prelude::*;
+use std::time::Duration;
+use web_time::Instant;
+
+/// How the tiles of a [`Tilemap`] are placed in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileProjection {
+    /// Tiles are placed in rows and columns of the tile size.
+    #[default]
+    Orthogonal,
+    /// Tiles are diamonds with the width and height of the tile size, the x axis of the grid goes
+    /// down to the right and the y axis goes down to the left.
+    ///
+    /// Most pixel art uses a tile size where the width is twice the height, which is technically a
+    /// dimetric projection. Any other ratio works as well.
+    Isometric,
+    /// Tiles are hexagons with the width and height of the tile size, stored in offset
+    /// coordinates where every odd row or column is shifted by half a tile.
+    ///
+    /// Rows overlap by a quarter of the tile height for pointy top hexagons and columns overlap by
+    /// a quarter of the tile width for flat top hexagons, so a tile size divisible by 4 in that
+    /// direction keeps the hexagons pixel perfect.
+    Hexagonal(HexOrientation),
+}
+
+/// Which side of the hexagons of a [`TileProjection::Hexagonal`] map points up.
+///
+/// Tiles are stored in offset coordinates, pointy top maps shift the odd rows half a tile to the
+/// right and flat top maps shift the odd columns half a tile down. Axial coordinates are easier to
+/// do math with, they can be converted from and to offset coordinates.
+///
+/// ```rust
+/// use specs_blit::HexOrientation;
+///
+/// let orientation = HexOrientation::PointyTop;
+///
+/// // The tile at the start of the second row is shifted to the right
+/// assert_eq!(orientation.offset_to_axial(0, 1), (0, 1));
+/// assert_eq!(orientation.offset_to_axial(0, 2), (-1, 2));
+/// assert_eq!(orientation.axial_to_offset(-1, 2), (0, 2));
+///
+/// assert_eq!(orientation.distance((0, 0), (2, 2)), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexOrientation {
+    /// A corner of the hexagons points up, the tiles form rows.
+    PointyTop,
+    /// An edge of the hexagons is on top, the tiles form columns.
+    FlatTop,
+}
+
+impl HexOrientation {
+    /// Axial directions to the neighbors of a hexagon, clockwise.
+    const DIRECTIONS: [(i32, i32); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+    /// Convert an offset tile coordinate to an axial coordinate.
+    pub fn offset_to_axial(self, x: i32, y: i32) -> (i32, i32) {
+        match self {
+            HexOrientation::PointyTop => (x - (y - (y & 1)) / 2, y),
+            HexOrientation::FlatTop => (x, y - (x - (x & 1)) / 2),
+        }
+    }
+
+    /// Convert an axial coordinate to an offset tile coordinate.
+    pub fn axial_to_offset(self, q: i32, r: i32) -> (i32, i32) {
+        match self {
+            HexOrientation::PointyTop => (q + (r - (r & 1)) / 2, r),
+            HexOrientation::FlatTop => (q, r + (q - (q & 1)) / 2),
+        }
+    }
+
+    /// The six offset tile coordinates around the tile, they can be outside of a map.
+    pub fn neighbors(self, x: i32, y: i32) -> [(i32, i32); 6] {
+        let (q, r) = self.offset_to_axial(x, y);
+
+        let mut neighbors = [(0, 0); 6];
+        for (neighbor, (dq, dr)) in neighbors.iter_mut().zip(Self::DIRECTIONS.iter()) {
+            *neighbor = self.axial_to_offset(q + dq, r + dr);
+        }
+
+        neighbors
+    }
+
+    /// Amount of steps between two offset tile coordinates.
+    pub fn distance(self, from: (i32, i32), to: (i32, i32)) -> i32 {
+        let (q1, r1) = self.offset_to_axial(from.0, from.1);
+        let (q2, r2) = self.offset_to_axial(to.0, to.1);
+        let (dq, dr) = (q1 - q2, r1 - r2);
+
+        (dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+    }
+
+    /// Round a fractional axial coordinate to the hexagon it's in.
+    fn round_axial(q: f64, r: f64) -> (i32, i32) {
+        let s = -q - r;
+        let (mut rounded_q, mut rounded_r, rounded_s) = (q.round(), r.round(), s.round());
+        let (diff_q, diff_r, diff_s) = (
+            (rounded_q - q).abs(),
+            (rounded_r - r).abs(),
+            (rounded_s - s).abs(),
+        );
+
+        // The component that's rounded the most is derived from the others
+        if diff_q > diff_r && diff_q > diff_s {
+            rounded_q = -rounded_r - rounded_s;
+        } else if diff_r > diff_s {
+            rounded_r = -rounded_q - rounded_s;
+        }
+
+        (rounded_q as i32, rounded_r as i32)
+    }
+}
+
+/// Specs component with a grid of tiles drawn by the [`TilemapRenderSystem`].
+///
+/// Every tile is an index in the list of sprites the tilemap is created with, or empty. Sprites
+/// taller than the tile size are aligned with the bottom of the tile, so blocks and walls can
+/// stick out above the ground.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, testing, TileProjection, Tilemap};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Tilemap>();
+///
+/// let grass = load(BlitBuffer::from_buffer(&[0x00_FF_00; 4 * 2], 4, 0xFF_00_FF))?;
+///
+/// // A diamond shaped map of 2x2 tiles which are 4 pixels wide and 2 pixels high
+/// let mut tilemap = Tilemap::new(2, 2, (4, 2), vec![grass]);
+/// tilemap.set_projection(TileProjection::Isometric);
+/// tilemap.fill(Some(0));
+/// tilemap.set_pos(2, 0);
+///
+/// // The tile on the right side of the diamond
+/// assert_eq!(tilemap.tile_to_world(1, 0), (4, 1));
+/// assert_eq!(tilemap.world_to_tile(6, 2), Some((1, 0)));
+///
+/// world.create_entity().with(tilemap).build();
+///
+/// let frame = testing::render(&mut world, 8, 4);
+/// assert_eq!(frame.pixels()[1 * 8 + 5] & 0xFF_FF_FF, 0x00_FF_00);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Hexagonal maps are stored in offset coordinates as well:
+///
+/// ```rust
+/// use specs_blit::{HexOrientation, TileProjection, Tilemap};
+///
+/// let mut tilemap = Tilemap::new(4, 4, (8, 8), vec![]);
+/// tilemap.set_projection(TileProjection::Hexagonal(HexOrientation::PointyTop));
+///
+/// // Odd rows are shifted half a tile to the right and rows overlap by a quarter of a tile
+/// assert_eq!(tilemap.tile_to_world(1, 1), (12, 6));
+/// assert_eq!(tilemap.world_to_tile(16, 10), Some((1, 1)));
+///
+/// // The corner tile only has two neighbors inside of the map
+/// assert_eq!(tilemap.neighbors(0, 0), vec![(1, 0), (0, 1)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tilemap {
+    /// Amount of tiles in a row.
+    width: usize,
+    /// Amount of rows.
+    height: usize,
+    /// Index of the sprite of every tile.
+    tiles: Vec<Option<usize>>,
+    /// How many pixels every tile is raised.
+    elevations: Vec<i32>,
+    /// Sprites of the tiles.
+    tileset: Vec<SpriteRef>,
+    /// Whether tiles using the sprite with the index in the tileset are solid.
+    solid: Vec<bool>,
+    /// Animations replacing the sprites of tiles in the tileset.
+    animations: Vec<(usize, Animation)>,
+    /// Size of the ground of a single tile in pixels.
+    tile_size: (i32, i32),
+    /// How the tiles are placed.
+    projection: TileProjection,
+    /// World position of the top left corner of the bounding box of the first tile.
+    pos: (i32, i32),
+}
+
+impl Component for Tilemap {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Tilemap {
+    /// Create an empty orthogonal map with the amount of tiles, the size of a single tile and the
+    /// sprites the tiles refer to.
+    pub fn new(
+        width: usize,
+        height: usize,
+        tile_size: (i32, i32),
+        tileset: Vec<SpriteRef>,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![None; width * height],
+            elevations: vec![0; width * height],
+            tileset,
+            solid: Vec::new(),
+            animations: Vec::new(),
+            tile_size,
+            projection: TileProjection::default(),
+            pos: (0, 0),
+        }
+    }
+
+    /// Amount of tiles in a row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Amount of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size of the ground of a single tile in pixels.
+    pub fn tile_size(&self) -> (i32, i32) {
+        self.tile_size
+    }
+
+    /// Set how the tiles are placed in the world.
+    pub fn set_projection(&mut self, projection: TileProjection) {
+        self.projection = projection;
+    }
+
+    /// Get how the tiles are placed in the world.
+    pub fn projection(&self) -> TileProjection {
+        self.projection
+    }
+
+    /// Set the world position of the top left corner of the bounding box of the first tile.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the world position of the top left corner of the bounding box of the first tile.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Index in the grid, `None` when it's outside of it.
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Set the sprite of the tile, ignored when it's outside of the map.
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: Option<usize>) {
+        if let Some(index) = self.index(x, y) {
+            self.tiles[index] = tile;
+        }
+    }
+
+    /// Get the sprite of the tile, `None` when it's empty or outside of the map.
+    pub fn tile(&self, x: usize, y: usize) -> Option<usize> {
+        self.index(x, y).and_then(|index| self.tiles[index])
+    }
+
+    /// Set the sprite of all tiles.
+    pub fn fill(&mut self, tile: Option<usize>) {
+        self.tiles.fill(tile);
+    }
+
+    /// Animate every tile using the sprite with the index in the tileset, replacing its sprite.
+    ///
+    /// All tiles share the animation so they show the same frame, the animations are advanced by
+    /// the [`TilemapRenderSystem`].
+    ///
+    /// ```rust
+    /// use blit::BlitBuffer;
+    /// use specs::prelude::*;
+    /// use specs_blit::{load, testing, Animation, Tilemap, Time};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut world = World::new();
+    /// world.register::<Tilemap>();
+    ///
+    /// let water1 = load(BlitBuffer::from_buffer(&[0x00_00_FF], 1, 0xFF_00_FF))?;
+    /// let water2 = load(BlitBuffer::from_buffer(&[0x00_FF_FF], 1, 0xFF_00_FF))?;
+    ///
+    /// let mut tilemap = Tilemap::new(2, 2, (1, 1), vec![water1.clone()]);
+    /// tilemap.fill(Some(0));
+    /// tilemap.set_animation(
+    ///     0,
+    ///     Animation::new(vec![
+    ///         (water1, Duration::from_millis(200)),
+    ///         (water2, Duration::from_millis(200)),
+    ///     ]),
+    /// );
+    /// world.create_entity().with(tilemap).build();
+    ///
+    /// let mut time = Time::default();
+    /// time.advance(Duration::from_millis(300));
+    /// world.insert(time);
+    ///
+    /// // All tiles show the second frame
+    /// let frame = testing::render(&mut world, 2, 2);
+    /// assert!(frame.pixels().iter().all(|pixel| pixel & 0xFF_FF_FF == 0x00_FF_FF));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_animation(&mut self, tile: usize, animation: Animation) {
+        match self.animations.iter_mut().find(|(index, _)| *index == tile) {
+            Some((_, existing)) => *existing = animation,
+            None => self.animations.push((tile, animation)),
+        }
+    }
+
+    /// Stop animating the sprite with the index in the tileset, the tiles show the sprite again.
+    pub fn remove_animation(&mut self, tile: usize) {
+        self.animations.retain(|(index, _)| *index != tile);
+    }
+
+    /// Move all tile animations forward in time.
+    pub fn advance(&mut self, delta: Duration) {
+        for (_, animation) in self.animations.iter_mut() {
+            animation.advance(delta);
+        }
+    }
+
+    /// The sprite currently shown for the index in the tileset.
+    fn tile_sprite(&self, tile: usize) -> Option<&SpriteRef> {
+        match self.animations.iter().find(|(index, _)| *index == tile) {
+            Some((_, animation)) => Some(animation.current_frame()),
+            None => self.tileset.get(tile),
+        }
+    }
+
+    /// Mark every tile using the sprite with the index in the tileset as solid or empty in the
+    /// [`CollisionLayer`], all sprites are empty by default.
+    pub fn set_solid(&mut self, tile: usize, solid: bool) {
+        if tile >= self.solid.len() {
+            self.solid.resize(tile + 1, false);
+        }
+        self.solid[tile] = solid;
+    }
+
+    /// Whether tiles using the sprite with the index in the tileset are solid.
+    pub fn is_solid(&self, tile: usize) -> bool {
+        self.solid.get(tile).copied().unwrap_or(false)
+    }
+
+    /// Grid of the solid and empty tiles of the map, empty tiles are never solid.
+    pub fn collision_layer(&self) -> CollisionLayer {
+        let solid = self
+            .tiles
+            .iter()
+            .map(|tile| tile.is_some_and(|tile| self.is_solid(tile)))
+            .collect();
+
+        CollisionLayer::new(self.width, self.height, solid)
+    }
+
+    /// Raise the tile by the amount of pixels, ignored when it's outside of the map.
+    pub fn set_elevation(&mut self, x: usize, y: usize, elevation: i32) {
+        if let Some(index) = self.index(x, y) {
+            self.elevations[index] = elevation;
+        }
+    }
+
+    /// Get how many pixels the tile is raised, `0` when it's outside of the map.
+    pub fn elevation(&self, x: usize, y: usize) -> i32 {
+        self.index(x, y).map_or(0, |index| self.elevations[index])
+    }
+
+    /// Convert a tile coordinate to the world position of the top left corner of the bounding box
+    /// of its ground, without the elevation.
+    pub fn tile_to_world(&self, x: i32, y: i32) -> (i32, i32) {
+        let (width, height) = self.tile_size;
+
+        let (x, y) = match self.projection {
+            TileProjection::Orthogonal => (x * width, y * height),
+            TileProjection::Isometric => ((x - y) * width / 2, (x + y) * height / 2),
+            TileProjection::Hexagonal(HexOrientation::PointyTop) => {
+                (x * width + (y & 1) * width / 2, y * (height * 3 / 4))
+            }
+            TileProjection::Hexagonal(HexOrientation::FlatTop) => {
+                (x * (width * 3 / 4), y * height + (x & 1) * height / 2)
+            }
+        };
+
+        (self.pos.0 + x, self.pos.1 + y)
+    }
+
+    /// Convert a world position to the coordinate of the tile whose ground is at that position,
+    /// `None` when it's outside of the map.
+    ///
+    /// The elevation of the tiles is ignored.
+    pub fn world_to_tile(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let (width, height) = (f64::from(self.tile_size.0), f64::from(self.tile_size.1));
+        if width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+
+        let (x, y) = (f64::from(x - self.pos.0), f64::from(y - self.pos.1));
+        let (tile_x, tile_y) = match self.projection {
+            TileProjection::Orthogonal => (x / width, y / height),
+            TileProjection::Isometric => {
+                // Relative to the top corner of the first tile
+                let x = x - width / 2.0;
+
+                (y / height + x / width, y / height - x / width)
+            }
+            TileProjection::Hexagonal(orientation) => {
+                // Relative to the center of the first tile
+                let (x, y) = (x - width / 2.0, y - height / 2.0);
+
+                let (q, r) = match orientation {
+                    HexOrientation::PointyTop => {
+                        let r = y / f64::from(self.tile_size.1 * 3 / 4);
+                        (x / width - r / 2.0, r)
+                    }
+                    HexOrientation::FlatTop => {
+                        let q = x / f64::from(self.tile_size.0 * 3 / 4);
+                        (q, y / height - q / 2.0)
+                    }
+                };
+
+                let (q, r) = HexOrientation::round_axial(q, r);
+                let (x, y) = orientation.axial_to_offset(q, r);
+
+                (f64::from(x), f64::from(y))
+            }
+        };
+
+        let (tile_x, tile_y) = (tile_x.floor(), tile_y.floor());
+        if tile_x < 0.0 || tile_y < 0.0 {
+            return None;
+        }
+
+        let (tile_x, tile_y) = (tile_x as usize, tile_y as usize);
+        self.index(tile_x, tile_y).map(|_| (tile_x, tile_y))
+    }
+
+    /// Z-order for sprites standing on the tile, so sprites further back are drawn first.
+    pub fn z_order(&self, x: i32, y: i32) -> i32 {
+        match self.projection {
+            TileProjection::Orthogonal => y,
+            TileProjection::Isometric => x + y,
+            TileProjection::Hexagonal(HexOrientation::PointyTop) => y,
+            // Odd columns are half a tile lower
+            TileProjection::Hexagonal(HexOrientation::FlatTop) => y * 2 + (x & 1),
+        }
+    }
+
+    /// Coordinates of the tiles sharing an edge with the tile, without the ones outside of the map.
+    ///
+    /// Orthogonal and isometric tiles have four neighbors, hexagonal tiles have six.
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let (x, y) = (x as i32, y as i32);
+        let neighbors = match self.projection {
+            TileProjection::Orthogonal | TileProjection::Isometric => {
+                vec![(x + 1, y), (x, y - 1), (x - 1, y), (x, y + 1)]
+            }
+            TileProjection::Hexagonal(orientation) => orientation.neighbors(x, y).to_vec(),
+        };
+
+        neighbors
+            .into_iter()
+            .filter(|&(x, y)| x >= 0 && y >= 0)
+            .map(|(x, y)| (x as usize, y as usize))
+            .filter(|&(x, y)| self.index(x, y).is_some())
+            .collect()
+    }
+
+    /// All tile coordinates in the order they must be drawn, from the back to the front.
+    pub fn draw_order(&self) -> Vec<(usize, usize)> {
+        let mut order: Vec<(usize, usize)> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .collect();
+
+        match self.projection {
+            // Already sorted row by row
+            TileProjection::Orthogonal | TileProjection::Hexagonal(HexOrientation::PointyTop) => (),
+            TileProjection::Isometric => {
+                // Draw the diagonals from the top corner to the bottom corner
+                order.sort_by_key(|&(x, y)| (x + y, x));
+            }
+            TileProjection::Hexagonal(HexOrientation::FlatTop) => {
+                // Draw the even columns of a row before the lower odd columns overlapping them
+                order.sort_by_key(|&(x, y)| (y * 2 + (x & 1), x));
+            }
+        }
+
+        order
+    }
+
+    /// Draw all tiles on the buffer as seen by the camera.
+    pub fn draw(&self, buffer: &mut PixelBuffer, camera: &Camera) {
+        self.for_each_tile(camera, |image, pos, size| {
+            if size == (image.width(), image.height()) {
+                image.blit_with(buffer, pos, |src, dst, _, _| *dst = src);
+            } else {
+                image.blit_scaled_with(buffer, pos, size, |src, dst, _, _| *dst = src);
+            }
+        });
+    }
+
+    /// Call the function with the image, the buffer position and the size of every tile in the
+    /// order they are drawn.
+    pub(crate) fn for_each_tile<F>(&self, camera: &Camera, mut f: F)
+    where
+        F: FnMut(&SpriteImage, (i32, i32), (usize, usize)),
+    {
+        let sprites = SPRITES.read().unwrap();
+        for (x, y) in self.draw_order() {
+            let sprite_ref = match self.tile(x, y).and_then(|tile| self.tile_sprite(tile)) {
+                Some(sprite_ref) => sprite_ref,
+                None => continue,
+            };
+
+            // Skip tiles that don't exist anymore because the sprites are cleared
+            let image = match sprites.get(sprite_ref.sprites[0].0) {
+                Some(image) => image,
+                None => continue,
+            };
+
+            // Align the bottom of the sprite with the bottom of the ground of the tile
+            let ground = self.tile_to_world(x as i32, y as i32);
+            let top_left = (
+                ground.0,
+                ground.1 + self.tile_size.1 - image.height() as i32 - self.elevation(x, y),
+            );
+            let bottom_right = (
+                top_left.0 + image.width() as i32,
+                top_left.1 + image.height() as i32,
+            );
+
+            // Calculate both corners so zoomed tiles don't leave gaps
+            let pos = camera.to_screen(top_left);
+            let end = camera.to_screen(bottom_right);
+            let size = (
+                (end.0 - pos.0).max(0) as usize,
+                (end.1 - pos.1).max(0) as usize,
+            );
+
+            f(image, pos, size);
+        }
+    }
+}
+
+/// Specs system advancing the tile animations and drawing every [`Tilemap`] component through
+/// the [`Camera`] resource.
+///
+/// Run it before the [`RenderSystem`](crate::RenderSystem) with `.with_thread_local()` so the
+/// sprites are drawn on top of the tiles. Tilemaps are drawn in the order of their entities.
+#[derive(Debug, Default)]
+pub struct TilemapRenderSystem;
+impl<'a> System<'a> for TilemapRenderSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Camera>>,
+        Read<'a, Time>,
+        WriteStorage<'a, Tilemap>,
+        Option<Write<'a, RenderProfiler>>,
+        Option<Write<'a, OverdrawHeatmap>>,
+    );
+
+    fn run(
+        &mut self,
+        (mut buffer, camera, time, mut tilemaps, profiler, mut heatmap): Self::SystemData,
+    ) {
+        let start = Instant::now();
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+        let buffer_size = (buffer.width(), buffer.height());
+        for tilemap in (&mut tilemaps).join() {
+            tilemap.advance(time.delta());
+            tilemap.draw(&mut buffer, &camera);
+
+            if let Some(heatmap) = heatmap.as_mut() {
+                tilemap.for_each_tile(&camera, |image, pos, size| {
+                    heatmap.add(image, pos, size, buffer_size)
+                });
+            }
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "TilemapRenderSystem", start);
+    }
+}
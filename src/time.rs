@@ -0,0 +1,295 @@
+//! Time keeping for the systems that change over time.
+
+use crate::Sprite;
+use std::{collections::HashSet, time::Duration};
+use web_time::Instant;
+
+/// Resource with the time passed, used by all systems in this crate that animate something.
+///
+/// Animations, particles, transitions, blinking and every other effect read it instead of keeping
+/// their own clock, so they all stay in sync. The game loop is responsible for updating it every
+/// frame, either with [`tick`](Time::tick) which measures the real time passed or with
+/// [`advance`](Time::advance) for a fixed timestep.
+///
+/// The time can be slowed down or paused with [`set_scale`](Time::set_scale) and
+/// [`hit_stop`](Time::hit_stop), everything driven by it slows down with it while rendering keeps
+/// running.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::Time;
+/// use std::time::Duration;
+///
+/// let mut world = World::new();
+/// world.insert(Time::default());
+///
+/// // Call this every frame before dispatching
+/// world.write_resource::<Time>().advance(Duration::from_millis(16));
+/// world.write_resource::<Time>().advance(Duration::from_millis(16));
+///
+/// let time = world.read_resource::<Time>();
+/// assert_eq!(time.delta(), Duration::from_millis(16));
+/// assert_eq!(time.elapsed(), Duration::from_millis(32));
+/// assert_eq!(time.frame(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Time {
+    /// Scaled time passed since the previous frame.
+    delta: Duration,
+    /// Scaled time passed since the start.
+    elapsed: Duration,
+    /// Real time passed since the previous frame.
+    unscaled_delta: Duration,
+    /// Real time passed since the start.
+    unscaled_elapsed: Duration,
+    /// Speed of the time, `1.0` is real time.
+    scale: f64,
+    /// Real time left during which the time is frozen.
+    hit_stop: Duration,
+    /// Amount of frames started.
+    frame: u64,
+    /// Moment of the previous tick.
+    last_tick: Option<Instant>,
+}
+
+impl Time {
+    /// Start a new frame which happens `delta` of real time after the previous one.
+    pub fn advance(&mut self, delta: Duration) {
+        self.unscaled_delta = delta;
+        self.unscaled_elapsed += delta;
+
+        // The part of the frame during the hit-stop doesn't count
+        let frozen = delta.min(self.hit_stop);
+        self.hit_stop -= frozen;
+
+        // Huge or infinite scales saturate instead of overflowing
+        self.delta = Duration::try_from_secs_f64(self.scale * (delta - frozen).as_secs_f64())
+            .unwrap_or(Duration::MAX);
+        self.elapsed = self.elapsed.saturating_add(self.delta);
+        self.frame += 1;
+    }
+
+    /// Start a new frame with the real time passed since the previous tick.
+    ///
+    /// The first tick doesn't advance the time because there's no previous frame to measure from.
+    ///
+    /// ```rust
+    /// use specs_blit::Time;
+    /// use std::time::Duration;
+    ///
+    /// let mut time = Time::default();
+    /// time.tick();
+    /// assert_eq!(time.delta(), Duration::from_secs(0));
+    ///
+    /// std::thread::sleep(Duration::from_millis(5));
+    /// time.tick();
+    /// assert!(time.delta() >= Duration::from_millis(5));
+    /// assert_eq!(time.frame(), 2);
+    /// ```
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let delta = self
+            .last_tick
+            .map_or_else(Duration::default, |last_tick| now - last_tick);
+        self.last_tick = Some(now);
+
+        self.advance(delta);
+    }
+
+    /// Time passed since the previous frame, slowed down by the time scale.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Total time passed, slowed down by the time scale.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Real time passed since the previous frame.
+    pub fn unscaled_delta(&self) -> Duration {
+        self.unscaled_delta
+    }
+
+    /// Total real time passed.
+    pub fn unscaled_elapsed(&self) -> Duration {
+        self.unscaled_elapsed
+    }
+
+    /// Set the speed of the time, `1.0` is real time, `0.5` is slow-motion and `0.0` pauses it.
+    ///
+    /// Negative values and `NaN` are treated as `0.0`. When the scaled time doesn't fit in a
+    /// [`Duration`], e.g. with `f64::INFINITY`, the maximum duration is used.
+    ///
+    /// ```rust
+    /// use specs_blit::Time;
+    /// use std::time::Duration;
+    ///
+    /// let mut time = Time::default();
+    /// time.set_scale(0.5);
+    /// time.advance(Duration::from_millis(16));
+    ///
+    /// assert_eq!(time.delta(), Duration::from_millis(8));
+    /// assert_eq!(time.unscaled_delta(), Duration::from_millis(16));
+    ///
+    /// // An infinite scale saturates instead of panicking
+    /// time.set_scale(f64::INFINITY);
+    /// time.advance(Duration::from_millis(16));
+    /// time.advance(Duration::from_millis(16));
+    /// assert_eq!(time.delta(), Duration::MAX);
+    /// assert_eq!(time.elapsed(), Duration::MAX);
+    /// ```
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale.max(0.0);
+    }
+
+    /// Speed of the time, `1.0` is real time.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Freeze the time for a short period of real time, e.g. when a hit lands.
+    ///
+    /// A longer hit-stop that's still running isn't shortened.
+    ///
+    /// ```rust
+    /// use specs_blit::Time;
+    /// use std::time::Duration;
+    ///
+    /// let mut time = Time::default();
+    /// time.hit_stop(Duration::from_millis(20));
+    ///
+    /// time.advance(Duration::from_millis(16));
+    /// assert_eq!(time.delta(), Duration::from_millis(0));
+    ///
+    /// // Only the part after the hit-stop counts
+    /// time.advance(Duration::from_millis(16));
+    /// assert_eq!(time.delta(), Duration::from_millis(12));
+    /// ```
+    pub fn hit_stop(&mut self, duration: Duration) {
+        self.hit_stop = self.hit_stop.max(duration);
+    }
+
+    /// Whether the time is standing still because it's paused or in a hit-stop.
+    pub fn is_paused(&self) -> bool {
+        self.scale == 0.0 || self.hit_stop > Duration::default()
+    }
+
+    /// Amount of frames started, the first frame is `1`.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            delta: Duration::default(),
+            elapsed: Duration::default(),
+            unscaled_delta: Duration::default(),
+            unscaled_elapsed: Duration::default(),
+            scale: 1.0,
+            hit_stop: Duration::default(),
+            frame: 0,
+            last_tick: None,
+        }
+    }
+}
+
+/// Resource with the sprite layers whose animations and effects are frozen.
+///
+/// The systems advancing [`Animation`](crate::Animation), [`SquashStretch`](crate::SquashStretch),
+/// [`Blink`](crate::Blink), [`Lifetime`](crate::Lifetime) and [`Wave`](crate::Wave) don't advance
+/// them for entities with a [`Sprite`] on a paused layer, while the other layers and rendering keep
+/// going. This is useful for freezing the world behind a pause menu.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Lifetime, LifetimeSystem, PausedLayers, Sprite, Time};
+/// use std::time::Duration;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(Time::default());
+///
+/// // Freeze the world on layer 0 while the UI on layer 1 keeps animating
+/// let mut paused_layers = PausedLayers::new();
+/// paused_layers.pause(0);
+/// world.insert(paused_layers);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(LifetimeSystem, "lifetime", &[])
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF], 1, 0xFF_00_FF))?;
+/// let mut ui_sprite = Sprite::new(sprite_ref.clone());
+/// ui_sprite.set_layer(1);
+///
+/// let explosion = world
+///     .create_entity()
+///     .with(Sprite::new(sprite_ref))
+///     .with(Lifetime::new(Duration::from_millis(100)))
+///     .build();
+/// let ui = world
+///     .create_entity()
+///     .with(ui_sprite)
+///     .with(Lifetime::new(Duration::from_millis(100)))
+///     .build();
+///
+/// world.write_resource::<Time>().advance(Duration::from_millis(40));
+/// dispatcher.dispatch(&world);
+///
+/// let lifetimes = world.read_storage::<Lifetime>();
+/// assert_eq!(lifetimes.get(explosion).unwrap().remaining(), Duration::from_millis(100));
+/// assert_eq!(lifetimes.get(ui).unwrap().remaining(), Duration::from_millis(60));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PausedLayers {
+    /// Layers that are paused.
+    layers: HashSet<u8>,
+}
+
+impl PausedLayers {
+    /// Create the resource without any paused layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Freeze everything on the layer.
+    pub fn pause(&mut self, layer: u8) {
+        self.layers.insert(layer);
+    }
+
+    /// Let everything on the layer animate again.
+    pub fn resume(&mut self, layer: u8) {
+        self.layers.remove(&layer);
+    }
+
+    /// Let everything on all layers animate again.
+    pub fn resume_all(&mut self) {
+        self.layers.clear();
+    }
+
+    /// Whether everything on the layer is frozen.
+    pub fn is_paused(&self, layer: u8) -> bool {
+        self.layers.contains(&layer)
+    }
+}
+
+/// Time passed for an entity, nothing when its sprite is on a paused layer.
+pub(crate) fn layer_delta(
+    time: &Time,
+    paused_layers: Option<&PausedLayers>,
+    sprite: Option<&Sprite>,
+) -> Duration {
+    match (paused_layers, sprite) {
+        (Some(paused_layers), Some(sprite)) if paused_layers.is_paused(sprite.layer()) => {
+            Duration::default()
+        }
+        _ => time.delta(),
+    }
+}
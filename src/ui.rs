@@ -0,0 +1,608 @@
+//! Interface widgets drawn in screen space.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    Camera, PixelBuffer, Sprite, SpriteImage, SpriteRef, SPRITES,
+};
+use specs::prelude::*;
+use std::ops::Range;
+use web_time::Instant;
+
+/// Resource with the position and the button state of the mouse or another pointer, used by the
+/// [`ButtonSystem`].
+///
+/// Fill it from the input of the window every frame. Positions are in buffer pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cursor {
+    /// Position in buffer pixels.
+    pos: (i32, i32),
+    /// Whether the button is held down.
+    pressed: bool,
+}
+
+impl Cursor {
+    /// Set the position in buffer pixels.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the position in buffer pixels.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Set whether the button is held down.
+    pub fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+    }
+
+    /// Whether the button is held down.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+}
+
+/// Specs component drawing a sprite stretched to a size without stretching its border, also
+/// called nine-slice scaling.
+///
+/// The corners are drawn as they are, the edges are only stretched along them and the middle is
+/// stretched in both directions. It's drawn by the [`UiRenderSystem`].
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Panel, PixelBuffer, UiRenderSystem};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(5, 1));
+///
+/// let mut system = UiRenderSystem;
+/// System::setup(&mut system, &mut world);
+///
+/// // A frame with a border of a single pixel on the left and the right
+/// let frame = [0xFF_FF_FF, 0x33_33_33, 0xAA_AA_AA];
+/// let sprite_ref = load(BlitBuffer::from_buffer(&frame, 3, 0xFF_00_FF))?;
+/// let mut panel = Panel::new(sprite_ref, 1);
+/// panel.set_size(5, 1);
+/// world.create_entity().with(panel).build();
+///
+/// system.run_now(&world);
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// let colors: Vec<u32> = buffer.pixels().iter().map(|pixel| pixel & 0xFF_FF_FF).collect();
+/// assert_eq!(
+///     colors,
+///     [0xFF_FF_FF, 0x33_33_33, 0x33_33_33, 0x33_33_33, 0xAA_AA_AA]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Panel {
+    /// Sprite the parts are cut from.
+    sprite: SpriteRef,
+    /// Width in pixels of the left, top, right and bottom border of the sprite.
+    border: (usize, usize, usize, usize),
+    /// Top left corner in buffer pixels.
+    pos: (i32, i32),
+    /// Width and height in buffer pixels.
+    size: (usize, usize),
+}
+
+impl Component for Panel {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Panel {
+    /// Create a panel with the size of the sprite that has a border of the same width on all
+    /// sides.
+    pub fn new(sprite: SpriteRef, border: usize) -> Self {
+        let size = (sprite.size.0 as usize, sprite.size.1 as usize);
+
+        Self {
+            sprite,
+            border: (border, border, border, border),
+            pos: (0, 0),
+            size,
+        }
+    }
+
+    /// Use a different width in pixels for the left, top, right and bottom border of the sprite.
+    pub fn with_border(mut self, left: usize, top: usize, right: usize, bottom: usize) -> Self {
+        self.border = (left, top, right, bottom);
+
+        self
+    }
+
+    /// Set the position of the top left corner in buffer pixels.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the position of the top left corner in buffer pixels.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Set the width and height in buffer pixels the sprite is stretched to.
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        self.size = (width, height);
+    }
+
+    /// Get the width and height in buffer pixels.
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Draw the stretched sprite on the buffer.
+    fn draw(&self, buffer: &mut PixelBuffer, images: &[SpriteImage]) {
+        let image = match images.get(self.sprite.sprites[0].0) {
+            Some(image) if image.width() > 0 && image.height() > 0 => image,
+            _ => return,
+        };
+        let (left, top, right, bottom) = self.border;
+
+        for local_y in 0..self.size.1 {
+            let y = self.pos.1 + local_y as i32;
+            if y < 0 || y >= buffer.height() as i32 {
+                continue;
+            }
+            let src_y = slice(local_y, self.size.1, image.height(), top, bottom);
+
+            for local_x in 0..self.size.0 {
+                let x = self.pos.0 + local_x as i32;
+                if x < 0 || x >= buffer.width() as i32 {
+                    continue;
+                }
+                let src_x = slice(local_x, self.size.0, image.width(), left, right);
+
+                let src = image.pixels()[src_y * image.width() + src_x];
+                if image.is_visible(src) {
+                    buffer.set_pixel(x as usize, y as usize, src);
+                }
+            }
+        }
+    }
+}
+
+/// Find the pixel of the sprite drawn at the position of a stretched length along one axis,
+/// without stretching the borders at the start and the end.
+fn slice(pos: usize, length: usize, source: usize, start: usize, end: usize) -> usize {
+    // Borders that don't fit are cut off in the middle
+    let start = start.min(source);
+    let end = end.min(source - start);
+    let middle = source - start - end;
+
+    if pos < start.min(length) {
+        pos
+    } else if pos >= length.saturating_sub(end) {
+        source - (length - pos)
+    } else if middle == 0 {
+        start.saturating_sub(1)
+    } else {
+        start + (pos - start) * middle / (length - start - end)
+    }
+}
+
+/// State of a [`Button`] under the [`Cursor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ButtonState {
+    /// The cursor isn't on the button.
+    #[default]
+    Normal,
+    /// The cursor is on the button.
+    Hover,
+    /// The cursor is on the button and its button is held down.
+    Pressed,
+}
+
+/// Specs component for a clickable sprite that looks different when the [`Cursor`] is on it or
+/// presses it.
+///
+/// The [`ButtonSystem`] updates the state and the [`UiRenderSystem`] draws the sprite of the
+/// state.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Button, ButtonState, ButtonSystem, Cursor};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(ButtonSystem, "button", &[])
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 16], 4, 0xFF_00_FF))?;
+/// let mut button = Button::new(sprite_ref);
+/// button.set_pos(10, 10);
+/// let entity = world.create_entity().with(button).build();
+///
+/// // Press the button and release it again
+/// world.write_resource::<Cursor>().set_pos(12, 12);
+/// world.write_resource::<Cursor>().set_pressed(true);
+/// dispatcher.dispatch(&world);
+/// let state = world.read_storage::<Button>().get(entity).unwrap().state();
+/// assert_eq!(state, ButtonState::Pressed);
+///
+/// world.write_resource::<Cursor>().set_pressed(false);
+/// dispatcher.dispatch(&world);
+/// assert!(world.read_storage::<Button>().get(entity).unwrap().is_clicked());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Button {
+    /// Sprite drawn when the cursor isn't on the button.
+    normal: SpriteRef,
+    /// Sprite drawn when the cursor is on the button.
+    hover: Option<SpriteRef>,
+    /// Sprite drawn when the button is pressed.
+    pressed: Option<SpriteRef>,
+    /// Top left corner in buffer pixels.
+    pos: (i32, i32),
+    /// State under the cursor.
+    state: ButtonState,
+    /// Whether the button was released while the cursor is on it in the last update.
+    clicked: bool,
+}
+
+impl Component for Button {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Button {
+    /// Create a button drawn with the sprite in all states.
+    ///
+    /// The sprite determines the area that can be clicked.
+    pub fn new(normal: SpriteRef) -> Self {
+        Self {
+            normal,
+            hover: None,
+            pressed: None,
+            pos: (0, 0),
+            state: ButtonState::Normal,
+            clicked: false,
+        }
+    }
+
+    /// Draw another sprite when the cursor is on the button.
+    pub fn with_hover(mut self, hover: SpriteRef) -> Self {
+        self.hover = Some(hover);
+
+        self
+    }
+
+    /// Draw another sprite when the button is pressed.
+    pub fn with_pressed(mut self, pressed: SpriteRef) -> Self {
+        self.pressed = Some(pressed);
+
+        self
+    }
+
+    /// Set the position of the top left corner in buffer pixels.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the position of the top left corner in buffer pixels.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Get the state under the cursor.
+    pub fn state(&self) -> ButtonState {
+        self.state
+    }
+
+    /// Whether the button was released with the cursor on it in the last update.
+    pub fn is_clicked(&self) -> bool {
+        self.clicked
+    }
+
+    /// Get the sprite for the current state.
+    fn sprite(&self) -> &SpriteRef {
+        let sprite = match self.state {
+            ButtonState::Normal => None,
+            ButtonState::Hover => self.hover.as_ref(),
+            ButtonState::Pressed => self.pressed.as_ref().or(self.hover.as_ref()),
+        };
+
+        sprite.unwrap_or(&self.normal)
+    }
+
+    /// Whether the position in buffer pixels is on the button.
+    fn contains(&self, (x, y): (i32, i32)) -> bool {
+        let (width, height) = self.normal.size;
+
+        x >= self.pos.0 && y >= self.pos.1 && x < self.pos.0 + width && y < self.pos.1 + height
+    }
+}
+
+/// What a part of a [`Bar`] is filled with.
+#[derive(Debug, Clone)]
+pub enum BarFill {
+    /// A single color.
+    Color(u32),
+    /// The unrotated sprite, cut off at the edge of the part.
+    Sprite(SpriteRef),
+}
+
+impl From<u32> for BarFill {
+    fn from(color: u32) -> Self {
+        Self::Color(color)
+    }
+}
+
+impl From<SpriteRef> for BarFill {
+    fn from(sprite: SpriteRef) -> Self {
+        Self::Sprite(sprite)
+    }
+}
+
+impl BarFill {
+    /// Fill the columns of the bar at the position with the height.
+    fn draw(
+        &self,
+        buffer: &mut PixelBuffer,
+        pos: (i32, i32),
+        columns: Range<usize>,
+        height: usize,
+        images: &[SpriteImage],
+    ) {
+        let start = (pos.0 + columns.start as i32, pos.1);
+        match self {
+            BarFill::Color(color) => buffer.fill_rect(start, (columns.len(), height), *color),
+            BarFill::Sprite(sprite) => {
+                if let Some(image) = images.get(sprite.sprites[0].0) {
+                    // Only the part of the sprite in the columns is drawn
+                    let region = (columns.start, 0, columns.len(), height);
+                    image.blit_region_with(buffer, start, region, |src, dst, _, _| *dst = src);
+                }
+            }
+        }
+    }
+}
+
+/// Specs component for a rectangle partly filled from the left, e.g. a loading or a health bar.
+///
+/// Both the filled part and the background can be a color or a sprite, sprites are cut off where
+/// the filled part ends. It's drawn by the [`UiRenderSystem`] in screen space, or above the
+/// [`Sprite`] of the same entity with [`Bar::with_above_sprite`].
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{Bar, PixelBuffer, UiRenderSystem};
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(4, 1));
+///
+/// let mut system = UiRenderSystem;
+/// System::setup(&mut system, &mut world);
+///
+/// let mut bar = Bar::new(4, 1, 0x00_FF_00).with_background(0xFF_00_00);
+/// bar.set_fraction(0.5);
+/// world.create_entity().with(bar).build();
+///
+/// system.run_now(&world);
+/// assert_eq!(
+///     world.read_resource::<PixelBuffer>().pixels(),
+///     &[0x00_FF_00, 0x00_FF_00, 0xFF_00_00, 0xFF_00_00]
+/// );
+/// ```
+///
+/// A health bar following an enemy:
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::prelude::*;
+/// use specs_blit::{load, Bar, PixelBuffer, Sprite, UiRenderSystem};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(6, 8));
+///
+/// let mut system = UiRenderSystem;
+/// System::setup(&mut system, &mut world);
+///
+/// let enemy = load(BlitBuffer::from_buffer(&[0xFF_FF_FF; 4], 2, 0xFF_00_FF))?;
+/// let mut enemy = Sprite::new(enemy);
+/// enemy.set_pos(2, 4);
+///
+/// // A bar of 4 pixels wide centered 1 pixel above the enemy
+/// let health = load(BlitBuffer::from_buffer(&[0xFF_00_00; 4], 4, 0xFF_00_FF))?;
+/// let mut bar = Bar::new(4, 1, health).with_above_sprite(1);
+/// bar.set_fraction(0.5);
+/// world.create_entity().with(enemy).with(bar).build();
+///
+/// system.run_now(&world);
+/// let buffer = world.read_resource::<PixelBuffer>();
+/// assert_eq!(buffer.pixels()[2 * 6 + 1] & 0xFF_FF_FF, 0xFF_00_00);
+/// assert_eq!(buffer.pixels()[2 * 6 + 2] & 0xFF_FF_FF, 0xFF_00_00);
+/// assert_eq!(buffer.pixels()[2 * 6 + 3], 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bar {
+    /// Top left corner in buffer pixels.
+    pos: (i32, i32),
+    /// Width and height in buffer pixels.
+    size: (usize, usize),
+    /// Part that's filled from the left, between `0.0` and `1.0`.
+    fraction: f32,
+    /// What the filled part is filled with.
+    fill: BarFill,
+    /// What the part that's not filled is filled with, when `None` it's not drawn.
+    background: Option<BarFill>,
+    /// Pixels between the bar and the top of the sprite of the entity, when `None` the bar is
+    /// drawn at its position.
+    above_sprite: Option<i32>,
+}
+
+impl Component for Bar {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Bar {
+    /// Create a completely filled bar with the size in pixels, filled with a color or a sprite.
+    pub fn new<F>(width: usize, height: usize, fill: F) -> Self
+    where
+        F: Into<BarFill>,
+    {
+        Self {
+            pos: (0, 0),
+            size: (width, height),
+            fraction: 1.0,
+            fill: fill.into(),
+            background: None,
+            above_sprite: None,
+        }
+    }
+
+    /// Fill the part that's not filled with a color or a sprite.
+    pub fn with_background<F>(mut self, background: F) -> Self
+    where
+        F: Into<BarFill>,
+    {
+        self.background = Some(background.into());
+
+        self
+    }
+
+    /// Draw the bar horizontally centered above the [`Sprite`] of the same entity, with the
+    /// amount of pixels between them.
+    ///
+    /// The bar follows the sprite through the [`Camera`], but isn't zoomed or turned with it. The
+    /// position of the bar is ignored and it's not drawn when the entity doesn't have a sprite.
+    pub fn with_above_sprite(mut self, gap: i32) -> Self {
+        self.above_sprite = Some(gap);
+
+        self
+    }
+
+    /// Set the position of the top left corner in buffer pixels.
+    pub fn set_pos(&mut self, x: i32, y: i32) {
+        self.pos = (x, y);
+    }
+
+    /// Get the position of the top left corner in buffer pixels.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// Set the part that's filled from the left, it's clamped between `0.0` and `1.0`.
+    pub fn set_fraction(&mut self, fraction: f32) {
+        self.fraction = fraction.clamp(0.0, 1.0);
+    }
+
+    /// Get the part that's filled from the left.
+    pub fn fraction(&self) -> f32 {
+        self.fraction
+    }
+
+    /// Find the top left corner in buffer pixels, `None` when it should follow a sprite that
+    /// can't be found.
+    fn screen_pos(
+        &self,
+        sprite: Option<&Sprite>,
+        camera: &Camera,
+        images: &[SpriteImage],
+    ) -> Option<(i32, i32)> {
+        let gap = match self.above_sprite {
+            Some(gap) => gap,
+            None => return Some(self.pos),
+        };
+
+        // Calculate where the sprite is drawn the same way as the render system
+        let sprite = sprite?;
+        let render_info = sprite.render_info(camera.zoom());
+        let width = render_info.size(images.get(render_info.index)?).0 as i32;
+        let pos = camera.to_screen(sprite.pos());
+
+        Some((
+            pos.0 + render_info.offset.0 + width / 2 - self.size.0 as i32 / 2,
+            pos.1 + render_info.offset.1 - gap - self.size.1 as i32,
+        ))
+    }
+
+    /// Draw the bar on the buffer with the top left corner at the position.
+    fn draw(&self, buffer: &mut PixelBuffer, pos: (i32, i32), images: &[SpriteImage]) {
+        let filled = (self.size.0 as f32 * self.fraction).round() as usize;
+
+        self.fill.draw(buffer, pos, 0..filled, self.size.1, images);
+        if let Some(background) = &self.background {
+            background.draw(buffer, pos, filled..self.size.0, self.size.1, images);
+        }
+    }
+}
+
+/// Specs system updating the state of every [`Button`] with the [`Cursor`] resource.
+///
+/// Run it after the cursor is updated, the cursor is inserted when the system is set up.
+#[derive(Debug, Default)]
+pub struct ButtonSystem;
+impl<'a> System<'a> for ButtonSystem {
+    type SystemData = (Read<'a, Cursor>, WriteStorage<'a, Button>);
+
+    fn run(&mut self, (cursor, mut buttons): Self::SystemData) {
+        for button in (&mut buttons).join() {
+            let hovered = button.contains(cursor.pos);
+
+            // A click is a release on the button after pressing it
+            button.clicked = hovered && !cursor.pressed && button.state == ButtonState::Pressed;
+            button.state = match (hovered, cursor.pressed) {
+                (false, _) => ButtonState::Normal,
+                (true, false) => ButtonState::Hover,
+                (true, true) => ButtonState::Pressed,
+            };
+        }
+    }
+}
+
+/// Specs system drawing every [`Panel`], [`Bar`] and [`Button`] in screen space, ignoring the
+/// [`Camera`] except for bars above a sprite.
+///
+/// Panels are drawn first, then the bars and the buttons on top. Run it after everything else is
+/// drawn.
+#[derive(Debug, Default)]
+pub struct UiRenderSystem;
+impl<'a> System<'a> for UiRenderSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Option<Read<'a, Camera>>,
+        ReadStorage<'a, Sprite>,
+        ReadStorage<'a, Panel>,
+        ReadStorage<'a, Bar>,
+        ReadStorage<'a, Button>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(
+        &mut self,
+        (mut buffer, camera, sprites, panels, bars, buttons, profiler): Self::SystemData,
+    ) {
+        let start = Instant::now();
+        let camera = camera.map(|camera| camera.clone()).unwrap_or_default();
+
+        {
+            let images = SPRITES.read().unwrap();
+            for panel in panels.join() {
+                panel.draw(&mut buffer, &images);
+            }
+
+            for (bar, sprite) in (&bars, sprites.maybe()).join() {
+                if let Some(pos) = bar.screen_pos(sprite, &camera, &images) {
+                    bar.draw(&mut buffer, pos, &images);
+                }
+            }
+        }
+
+        for button in buttons.join() {
+            buffer.blit_sprite(button.sprite(), button.pos.0, button.pos.1, 0);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "UiRenderSystem", start);
+    }
+}
@@ -0,0 +1,157 @@
+//! Drawing restricted to a rectangular region of a buffer.
+
+use crate::PixelBuffer;
+use std::{
+    cmp,
+    ops::{Deref, DerefMut},
+};
+
+/// Mutable borrow of a rectangular region of a [`PixelBuffer`].
+///
+/// The view dereferences to a buffer with the size of the region, so everything that draws on a
+/// [`PixelBuffer`] can draw on the view, with `(0, 0)` being the top left corner of the region.
+/// Nothing outside of the region is touched.
+///
+/// The view isn't drawn on in place: the region is copied into its own buffer when the view is
+/// created and copied back when the view is dropped. That's cheap for small regions like a
+/// minimap, but a view of most of the buffer costs two copies of it. What's drawn only reaches
+/// the borrowed buffer when the view is dropped, it's lost when the view is leaked with
+/// `std::mem::forget`. When the buffer of the view is replaced by one of another size only the
+/// part that fits in the region is copied back.
+///
+/// ```rust
+/// use specs_blit::PixelBuffer;
+///
+/// let mut buffer = PixelBuffer::new(8, 8);
+///
+/// // Only allow drawing on the minimap in the bottom right corner
+/// let mut minimap = buffer.view_mut((6, 6), (2, 2));
+/// minimap.fill_rect((0, 0), (8, 8), 0xFF_00_00);
+/// minimap.draw_line((0, 0), (8, 8), 0x00_FF_00);
+/// drop(minimap);
+///
+/// assert_eq!(buffer.get_pixel(5, 5), Some(0));
+/// assert_eq!(buffer.get_pixel(6, 6), Some(0x00_FF_00));
+/// assert_eq!(buffer.get_pixel(7, 6), Some(0xFF_00_00));
+/// ```
+#[derive(Debug)]
+pub struct PixelBufferView<'a> {
+    /// Pixels of the borrowed buffer from the first row of the region on.
+    target: &'a mut [u32],
+    /// Width of the rows of the borrowed buffer.
+    stride: usize,
+    /// Position of the region in the borrowed buffer.
+    pos: (usize, usize),
+    /// Copy of the region that's drawn on.
+    buffer: PixelBuffer,
+}
+
+impl<'a> PixelBufferView<'a> {
+    /// Copy the region from the rows, the region must fit in them.
+    fn new(
+        target: &'a mut [u32],
+        stride: usize,
+        pos: (usize, usize),
+        size: (usize, usize),
+    ) -> Self {
+        let mut buffer = PixelBuffer::new(size.0, size.1);
+        if size.0 > 0 {
+            for (row, target_row) in buffer.rows_mut().zip(target.chunks_exact(stride)) {
+                row.copy_from_slice(&target_row[pos.0..pos.0 + size.0]);
+            }
+        }
+
+        Self {
+            target,
+            stride,
+            pos,
+            buffer,
+        }
+    }
+
+    /// Get the position of the top left corner of the region in the borrowed buffer.
+    pub fn pos(&self) -> (usize, usize) {
+        self.pos
+    }
+}
+
+impl Deref for PixelBufferView<'_> {
+    type Target = PixelBuffer;
+
+    fn deref(&self) -> &PixelBuffer {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PixelBufferView<'_> {
+    fn deref_mut(&mut self) -> &mut PixelBuffer {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PixelBufferView<'_> {
+    fn drop(&mut self) {
+        let (x, width) = (self.pos.0, self.buffer.width);
+        if width == 0 || self.stride == 0 {
+            return;
+        }
+
+        // The region might have been replaced by a buffer of another size, only the part that
+        // still fits is written back
+        let end = cmp::min(x + width, self.stride);
+        for (row, target_row) in self
+            .buffer
+            .pixels
+            .chunks_exact(width)
+            .zip(self.target.chunks_exact_mut(self.stride))
+        {
+            target_row[x..end].copy_from_slice(&row[..end - x]);
+        }
+    }
+}
+
+impl PixelBuffer {
+    /// Borrow a region of the buffer to draw on, see [`PixelBufferView`].
+    ///
+    /// The region is the position of the top left corner and the size in pixels, it's cut off at
+    /// the edges of the buffer.
+    pub fn view_mut(&mut self, pos: (usize, usize), size: (usize, usize)) -> PixelBufferView<'_> {
+        let pos = (cmp::min(pos.0, self.width), cmp::min(pos.1, self.height));
+        let size = (
+            cmp::min(size.0, self.width - pos.0),
+            cmp::min(size.1, self.height - pos.1),
+        );
+
+        let rows = &mut self.pixels[pos.1 * self.width..(pos.1 + size.1) * self.width];
+        PixelBufferView::new(rows, self.width, pos, size)
+    }
+
+    /// Split the buffer into the rows above and the rows from the row on, e.g. for split-screen.
+    ///
+    /// Both views are copies like every [`PixelBufferView`], so splitting copies the whole buffer
+    /// and copies it back when the views are dropped. It's not a way to speed up drawing.
+    ///
+    /// ```rust
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let mut buffer = PixelBuffer::new(4, 4);
+    ///
+    /// let (mut top, mut bottom) = buffer.split_at_row_mut(2);
+    /// top.clear(0xFF_00_00);
+    /// bottom.clear(0x00_FF_00);
+    /// drop((top, bottom));
+    ///
+    /// assert_eq!(buffer.get_pixel(3, 1), Some(0xFF_00_00));
+    /// assert_eq!(buffer.get_pixel(0, 2), Some(0x00_FF_00));
+    /// ```
+    pub fn split_at_row_mut(&mut self, y: usize) -> (PixelBufferView<'_>, PixelBufferView<'_>) {
+        let y = cmp::min(y, self.height);
+        let (width, height) = (self.width, self.height);
+
+        let (top, bottom) = self.pixels.split_at_mut(y * width);
+        (
+            PixelBufferView::new(top, width, (0, 0), (width, y)),
+            PixelBufferView::new(bottom, width, (0, y), (width, height - y)),
+        )
+    }
+}
@@ -0,0 +1,72 @@
+//! Problems encountered while rendering that don't stop the frame from being drawn.
+
+use specs::prelude::*;
+
+/// Event describing something that went wrong while rendering.
+///
+/// The render systems write these to a `specs::shrev::EventChannel<RenderWarning>` resource when
+/// it's added to the world, otherwise they are silently ignored.
+///
+/// ```rust
+/// use blit::BlitBuffer;
+/// use specs::{prelude::*, shrev::EventChannel};
+/// use specs_blit::{clear_all, load, testing, RenderWarning, Sprite};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut world = World::new();
+/// world.register::<Sprite>();
+/// world.insert(EventChannel::<RenderWarning>::new());
+///
+/// let mut reader = world
+///     .write_resource::<EventChannel<RenderWarning>>()
+///     .register_reader();
+///
+/// let sprite_ref = load(BlitBuffer::from_buffer(&[0xFF_00_00], 1, 0xFF_00_FF))?;
+/// world.create_entity().with(Sprite::new(sprite_ref)).build();
+///
+/// // Invalidate the sprite reference, the sprite will be skipped when rendering
+/// clear_all();
+/// testing::render(&mut world, 1, 1);
+///
+/// let channel = world.read_resource::<EventChannel<RenderWarning>>();
+/// let warnings = channel.read(&mut reader).collect::<Vec<_>>();
+/// assert!(matches!(warnings[..], [RenderWarning::InvalidSprite { index: 0, .. }]));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RenderWarning {
+    /// The sprite of the entity refers to a sprite that's not loaded anymore, it's skipped.
+    InvalidSprite {
+        /// The entity with the sprite component.
+        entity: Entity,
+        /// Index of the sprite that doesn't exist.
+        index: usize,
+    },
+    /// The [`RenderSystem`](crate::RenderSystem) or the
+    /// [`CompositorSystem`](crate::CompositorSystem) uses a buffer that's not in the
+    /// [`PixelBuffers`](crate::PixelBuffers) resource, nothing is drawn from or into it.
+    MissingBuffer {
+        /// Name of the buffer that doesn't exist.
+        name: String,
+    },
+    /// The sprite of the entity is drawn without any pixels, e.g. because it's scaled to
+    /// nothing, so it can't be seen.
+    EmptySprite {
+        /// The entity with the sprite component.
+        entity: Entity,
+        /// Index of the drawn image of the sprite.
+        index: usize,
+    },
+    /// The sprite of the entity is drawn larger than the buffer, only the part inside of the
+    /// buffer can be seen.
+    OversizedSprite {
+        /// The entity with the sprite component.
+        entity: Entity,
+        /// Width and height in pixels the sprite is drawn with.
+        size: (usize, usize),
+        /// Width and height in pixels of the buffer.
+        buffer_size: (usize, usize),
+    },
+}
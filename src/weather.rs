@@ -0,0 +1,305 @@
+//! Full screen rain and snow.
+
+use crate::{
+    profiler::{self, RenderPhase, RenderProfiler},
+    PixelBuffer, Time,
+};
+use specs::prelude::*;
+use std::f32::consts::TAU;
+use web_time::Instant;
+
+/// What falls from the sky of the [`Weather`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeatherKind {
+    /// Nothing new falls, the particles that are still falling land as usual.
+    #[default]
+    Clear,
+    /// Fast streaks that splash when they hit the ground.
+    Rain,
+    /// Slow flakes swaying from side to side.
+    Snow,
+}
+
+/// Kind of a single falling particle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParticleKind {
+    /// Streak of a few pixels.
+    Drop,
+    /// Single pixel swaying with the phase.
+    Flake(f32),
+    /// Pixel bouncing up from the ground for the amount of seconds left.
+    Splash(f32),
+}
+
+/// A single pixel moving over the buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Particle {
+    /// Position in pixels.
+    pos: (f32, f32),
+    /// Pixels per second moved.
+    velocity: (f32, f32),
+    /// How it moves and is drawn.
+    kind: ParticleKind,
+}
+
+/// Pixels per second a rain drop falls.
+const RAIN_SPEED: f32 = 240.0;
+/// Pixels per second a snow flake falls.
+const SNOW_SPEED: f32 = 24.0;
+/// Pixels a snow flake sways to both sides.
+const SNOW_SWAY: f32 = 8.0;
+/// Length of a rain streak in pixels.
+const RAIN_LENGTH: usize = 3;
+/// Seconds a splash stays visible.
+const SPLASH_DURATION: f32 = 0.25;
+/// Pixels per second squared pulling splashes down.
+const SPLASH_GRAVITY: f32 = 400.0;
+
+/// Resource with the rain or snow moved and drawn by the [`WeatherSystem`].
+///
+/// Every particle is drawn directly as single pixels, so thousands of them are cheap. Particles
+/// are spawned above the buffer and fall down to the ground, changing the kind only affects the
+/// particles spawned afterwards.
+///
+/// ```rust
+/// use specs::prelude::*;
+/// use specs_blit::{PixelBuffer, RenderSystem, Time, Weather, WeatherKind, WeatherSystem};
+/// use std::time::Duration;
+///
+/// let mut world = World::new();
+/// world.insert(PixelBuffer::new(64, 64));
+///
+/// // Heavy rain blown to the right
+/// let mut weather = Weather::new(WeatherKind::Rain);
+/// weather.density = 200;
+/// weather.wind = 40.0;
+/// world.insert(weather);
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with_thread_local(RenderSystem::default())
+///     // Draw the rain on top of the sprites
+///     .with_thread_local(WeatherSystem)
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// for _ in 0..10 {
+///     world.write_resource::<Time>().advance(Duration::from_millis(16));
+///     dispatcher.dispatch(&world);
+/// }
+///
+/// assert!(world.read_resource::<Weather>().particles() > 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weather {
+    /// What falls from the sky.
+    pub kind: WeatherKind,
+    /// Amount of particles falling at the same time when the weather has settled.
+    pub density: usize,
+    /// Pixels per second the particles are blown to the right, negative values blow them to the
+    /// left.
+    pub wind: f32,
+    /// Color of the particles.
+    pub color: u32,
+    /// Row of the buffer where the particles land, `None` for the bottom of the buffer.
+    pub ground: Option<usize>,
+    /// Whether rain drops splash when they land.
+    pub splashes: bool,
+    /// Particles currently falling.
+    particles: Vec<Particle>,
+    /// Particles that still have to be spawned.
+    spawn_budget: f32,
+    /// State of the random number generator.
+    seed: u64,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self::new(WeatherKind::Clear)
+    }
+}
+
+impl Weather {
+    /// Create weather without any particles falling yet, with a color fitting the kind.
+    pub fn new(kind: WeatherKind) -> Self {
+        Self {
+            kind,
+            density: 100,
+            wind: 0.0,
+            color: match kind {
+                WeatherKind::Snow => 0xFF_FF_FF,
+                _ => 0x90_A0_D0,
+            },
+            ground: None,
+            splashes: true,
+            particles: Vec::new(),
+            spawn_budget: 0.0,
+            seed: 0x5EED,
+        }
+    }
+
+    /// Amount of particles currently falling or splashing.
+    pub fn particles(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Remove all particles immediately.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.spawn_budget = 0.0;
+    }
+
+    /// Random number between `0.0` and `1.0`, based on SplitMix64.
+    fn random(&mut self) -> f32 {
+        self.seed = self.seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut hash = self.seed;
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+
+        (hash & 0xFFFF) as f32 / 65536.0
+    }
+
+    /// Spawn, move and land the particles on a buffer of the size.
+    pub fn update(&mut self, seconds: f32, width: usize, height: usize) {
+        let ground = self.ground.unwrap_or(height) as f32;
+        let fall_speed = match self.kind {
+            WeatherKind::Clear => 0.0,
+            WeatherKind::Rain => RAIN_SPEED,
+            WeatherKind::Snow => SNOW_SPEED,
+        };
+
+        // Spawn as many particles per second as land, so the density stays the same
+        if fall_speed > 0.0 && ground > 0.0 {
+            self.spawn_budget += self.density as f32 * fall_speed / ground * seconds;
+
+            // Wind blows particles sideways so they must also be spawned outside of the buffer
+            let drift = self.wind * ground / fall_speed;
+            let start = (-drift).min(0.0);
+            let range = width as f32 + drift.abs();
+            while self.spawn_budget >= 1.0 {
+                self.spawn_budget -= 1.0;
+
+                let pos = (start + self.random() * range, -1.0);
+                let kind = match self.kind {
+                    WeatherKind::Snow => ParticleKind::Flake(self.random()),
+                    _ => ParticleKind::Drop,
+                };
+                self.particles.push(Particle {
+                    pos,
+                    velocity: (self.wind, fall_speed),
+                    kind,
+                });
+            }
+        } else {
+            self.spawn_budget = 0.0;
+        }
+
+        let mut splashes = Vec::new();
+        let wind = self.wind;
+        self.particles.retain_mut(|particle| {
+            particle.pos.0 += particle.velocity.0 * seconds;
+            particle.pos.1 += particle.velocity.1 * seconds;
+
+            match &mut particle.kind {
+                ParticleKind::Drop => {
+                    if particle.pos.1 >= ground {
+                        splashes.push(particle.pos.0);
+                        return false;
+                    }
+                }
+                ParticleKind::Flake(phase) => {
+                    *phase += seconds * 0.5;
+                    particle.velocity.0 = wind + (*phase * TAU).cos() * SNOW_SWAY;
+                    if particle.pos.1 >= ground {
+                        return false;
+                    }
+                }
+                ParticleKind::Splash(time_left) => {
+                    *time_left -= seconds;
+                    particle.velocity.1 += SPLASH_GRAVITY * seconds;
+                    if *time_left <= 0.0 || particle.pos.1 >= ground {
+                        return false;
+                    }
+                }
+            }
+
+            true
+        });
+
+        // Every landed drop bounces up in two directions
+        if self.splashes {
+            for x in splashes {
+                for direction in [-1.0, 1.0] {
+                    let velocity = (
+                        direction * (20.0 + self.random() * 30.0),
+                        -60.0 - self.random() * 40.0,
+                    );
+                    self.particles.push(Particle {
+                        pos: (x, ground - 1.0),
+                        velocity,
+                        kind: ParticleKind::Splash(SPLASH_DURATION),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Draw all particles on the buffer.
+    pub fn draw(&self, buffer: &mut PixelBuffer) {
+        let (width, height) = (buffer.width() as i32, buffer.height() as i32);
+        let pixels = buffer.pixels_mut();
+        let mut plot = |x: f32, y: f32| {
+            let (x, y) = (x.floor() as i32, y.floor() as i32);
+            if x >= 0 && y >= 0 && x < width && y < height {
+                pixels[(y * width + x) as usize] = self.color;
+            }
+        };
+
+        for particle in self.particles.iter() {
+            match particle.kind {
+                ParticleKind::Drop => {
+                    // Draw a streak behind the drop in the direction it's falling
+                    let (dx, dy) = particle.velocity;
+                    let length = (dx * dx + dy * dy).sqrt().max(1.0);
+                    for step in 0..RAIN_LENGTH {
+                        let step = step as f32;
+                        plot(
+                            particle.pos.0 - dx / length * step,
+                            particle.pos.1 - dy / length * step,
+                        );
+                    }
+                }
+                ParticleKind::Flake(_) | ParticleKind::Splash(_) => {
+                    plot(particle.pos.0, particle.pos.1)
+                }
+            }
+        }
+    }
+}
+
+/// Specs system moving and drawing the [`Weather`] resource with the [`Time`] resource.
+///
+/// Does nothing when the resource doesn't exist. Run it after the
+/// [`RenderSystem`](crate::RenderSystem) with `.with_thread_local()` so the weather is drawn in
+/// front of the sprites.
+#[derive(Debug, Default)]
+pub struct WeatherSystem;
+impl<'a> System<'a> for WeatherSystem {
+    type SystemData = (
+        Write<'a, PixelBuffer>,
+        Read<'a, Time>,
+        Option<Write<'a, Weather>>,
+        Option<Write<'a, RenderProfiler>>,
+    );
+
+    fn run(&mut self, (mut buffer, time, weather, profiler): Self::SystemData) {
+        let start = Instant::now();
+        if let Some(mut weather) = weather {
+            let (width, height) = (buffer.width(), buffer.height());
+            weather.update(time.delta().as_secs_f32(), width, height);
+            weather.draw(&mut buffer);
+        }
+
+        profiler::record(profiler, RenderPhase::Blit, "WeatherSystem", start);
+    }
+}
@@ -0,0 +1,93 @@
+//! Uploading the rendered pixels to a [wgpu](https://github.com/gfx-rs/wgpu) texture.
+
+use crate::{PixelBuffer, PixelFormat};
+use wgpu::{
+    Extent3d, Origin3d, Queue, TexelCopyBufferLayout, TexelCopyTextureInfo, Texture, TextureAspect,
+    TextureFormat, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+impl PixelBuffer {
+    /// Write all pixels into the first mip level of a wgpu texture.
+    ///
+    /// The rows are padded to [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`] so the same data layout can
+    /// also be used for buffer to texture copies.
+    ///
+    /// # Panics
+    ///
+    /// When the texture is smaller than the buffer or its format is not one of the 8 bit RGBA or
+    /// BGRA formats.
+    ///
+    /// ```rust,no_run
+    /// # fn upload(device: &wgpu::Device, queue: &wgpu::Queue) {
+    /// use specs_blit::PixelBuffer;
+    ///
+    /// let buffer = PixelBuffer::new(320, 240);
+    ///
+    /// let texture = device.create_texture(&wgpu::TextureDescriptor {
+    ///     label: Some("specs-blit"),
+    ///     size: wgpu::Extent3d {
+    ///         width: 320,
+    ///         height: 240,
+    ///         depth_or_array_layers: 1,
+    ///     },
+    ///     mip_level_count: 1,
+    ///     sample_count: 1,
+    ///     dimension: wgpu::TextureDimension::D2,
+    ///     format: wgpu::TextureFormat::Bgra8UnormSrgb,
+    ///     usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    ///     view_formats: &[],
+    /// });
+    ///
+    /// // Call this every frame after the render system has run
+    /// buffer.write_to_wgpu_texture(queue, &texture);
+    /// # }
+    /// ```
+    pub fn write_to_wgpu_texture(&self, queue: &Queue, texture: &Texture) {
+        let format = match texture.format() {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => PixelFormat::Rgba8,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => PixelFormat::Bgra8,
+            other => panic!("texture format {:?} is not supported", other),
+        };
+        assert!(
+            texture.width() as usize >= self.width && texture.height() as usize >= self.height,
+            "texture of {}x{} can't hold a buffer of {}x{}",
+            texture.width(),
+            texture.height(),
+            self.width,
+            self.height
+        );
+
+        let bytes_per_row = self.wgpu_bytes_per_row();
+        let mut data = vec![0; bytes_per_row * self.height];
+        self.copy_to_format_with_stride(format, &mut data, bytes_per_row);
+
+        queue.write_texture(
+            TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            &data,
+            TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row as u32),
+                rows_per_image: Some(self.height as u32),
+            },
+            Extent3d {
+                width: self.width as u32,
+                height: self.height as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Amount of bytes of a single 4 byte per pixel row padded to the alignment wgpu requires for
+    /// buffer to texture copies.
+    pub fn wgpu_bytes_per_row(&self) -> usize {
+        let alignment = COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+        let unpadded = self.width * PixelFormat::Rgba8.bytes_per_pixel();
+
+        unpadded.div_ceil(alignment) * alignment
+    }
+}